@@ -0,0 +1,154 @@
+//! Bangumi / PGC (番剧, 纪录片, ...) extraction and download.
+//!
+//! Seasons and episodes are resolved through `pgc/view/web/season`, and
+//! streams come from `pgc/player/web/playurl` instead of the UGC playurl.
+
+use crate::bilibili::core::{download_media, select_media};
+use crate::bilibili::types::{PgcEpisode, PgcPlayUrlResponse, PgcSeason, PgcSeasonResponse};
+use crate::bilibili::utils::{api_error, normalize_url};
+use crate::core::{Platform, Quality};
+use crate::download::download_json;
+use crate::error::{MusicFreeError, Result};
+use crate::{Audio, AudioFormat, Playlist};
+use reqwest::header::HeaderMap;
+
+/// Episode status for episodes restricted to premium members
+const EPISODE_STATUS_PREMIUM: u64 = 13;
+
+/// Playurl code returned when the episode needs a premium membership
+const CODE_PREMIUM_ONLY: i64 = -10403;
+
+/// What a bangumi URL points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BangumiTarget {
+    /// A whole season (`ss<season_id>`)
+    Season(u64),
+    /// A single episode (`ep<ep_id>`)
+    Episode(u64),
+}
+
+/// Build the web URL of an episode
+pub fn build_episode_url(ep_id: u64) -> String {
+    format!("https://www.bilibili.com/bangumi/play/ep{ep_id}")
+}
+
+/// Build the web URL of a season
+pub fn build_season_url(season_id: u64) -> String {
+    format!("https://www.bilibili.com/bangumi/play/ss{season_id}")
+}
+
+/// Fetch season info by season id or by one of its episodes
+pub async fn get_season(target: BangumiTarget) -> Result<PgcSeason> {
+    let (query, context) = match target {
+        BangumiTarget::Season(id) => (format!("season_id={id}"), format!("Season ss{id}")),
+        BangumiTarget::Episode(id) => (format!("ep_id={id}"), format!("Episode ep{id}")),
+    };
+    let api_url = format!("https://api.bilibili.com/pgc/view/web/season?{query}");
+    let resp: PgcSeasonResponse = download_json(&api_url, HeaderMap::new()).await?;
+    match resp.result {
+        Some(season) if resp.code == 0 => Ok(season),
+        _ => Err(api_error(&context, resp.code, &resp.message)),
+    }
+}
+
+fn episode_title(episode: &PgcEpisode) -> String {
+    if episode.long_title.is_empty() {
+        episode.title.clone()
+    } else {
+        format!("{} - {}", episode.title, episode.long_title)
+    }
+}
+
+/// Extract every episode of a season into a playlist
+pub async fn extract_bangumi(target: BangumiTarget) -> Result<(Playlist, Option<usize>)> {
+    let season = get_season(target).await?;
+
+    let mut position = None;
+    let mut audios = vec![];
+
+    for (index, episode) in season.episodes.iter().enumerate() {
+        if target == BangumiTarget::Episode(episode.id) {
+            position = Some(index);
+        }
+
+        let audio = Audio::new(
+            format!("ep{}", episode.id),
+            episode_title(episode),
+            build_episode_url(episode.id),
+            Platform::Bilibili,
+        )
+        .with_format(AudioFormat::M4A)
+        .with_duration(episode.duration / 1000)
+        .with_cover(normalize_url(&episode.cover))
+        .with_album(season.title.clone());
+
+        audios.push(audio);
+    }
+
+    let playlist = Playlist {
+        id: Some(format!("ss{}", season.season_id)),
+        download_url: Some(build_season_url(season.season_id)),
+        title: Some(season.title),
+        audios,
+        cover: Some(normalize_url(&season.cover)),
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    };
+
+    Ok((playlist, position))
+}
+
+/// Download the audio of a single episode
+pub async fn download_episode(ep_id: u64, quality: Quality) -> Result<Vec<u8>> {
+    let season = get_season(BangumiTarget::Episode(ep_id)).await?;
+    let Some(episode) = season.episodes.iter().find(|e| e.id == ep_id) else {
+        return Err(MusicFreeError::VideoNotFound);
+    };
+    let title = episode_title(episode);
+
+    if episode.status == EPISODE_STATUS_PREMIUM {
+        return Err(MusicFreeError::PremiumRequired(format!(
+            "{} {title} ({})",
+            season.title, episode.badge
+        )));
+    }
+
+    let cid = episode.cid;
+    let fnval = 16; //dash
+    let play_url = format!(
+        "https://api.bilibili.com/pgc/player/web/playurl?ep_id={ep_id}&cid={cid}&fnval={fnval}&fourk=1"
+    );
+    let resp: PgcPlayUrlResponse = download_json(&play_url, HeaderMap::new()).await?;
+
+    let result = match resp.result {
+        Some(result) if resp.code == 0 => result,
+        _ if resp.code == CODE_PREMIUM_ONLY => {
+            return Err(MusicFreeError::PremiumRequired(format!(
+                "{} {title}: {}",
+                season.title, resp.message
+            )));
+        }
+        _ => {
+            return Err(api_error(
+                &format!("Episode ep{ep_id}"),
+                resp.code,
+                &resp.message,
+            ));
+        }
+    };
+
+    // Trial clips are only a few minutes long, don't save them as the episode
+    if result.is_preview == 1 {
+        return Err(MusicFreeError::PremiumRequired(format!(
+            "{} {title}: only a preview is available",
+            season.title
+        )));
+    }
+
+    let Some(media) = select_media(result.play_data, quality) else {
+        return Err(MusicFreeError::DownloadFailed(
+            "not found media_url".to_string(),
+        ));
+    };
+    download_media(media, &build_episode_url(ep_id)).await
+}
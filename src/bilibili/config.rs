@@ -0,0 +1,28 @@
+//! Process-wide Bilibili extraction settings.
+//!
+//! Like `youtube::config`, settings `Extractor::extract` can't receive with
+//! the URL are installed once with `set_config`.
+
+use std::sync::{Arc, RwLock};
+
+/// Bilibili extraction settings
+#[derive(Debug, Clone, Default)]
+pub struct BilibiliConfig {
+    /// Stop fetching uploader, series and collection lists once this many
+    /// videos are collected, see `SpaceOptions::limit`
+    pub playlist_limit: Option<usize>,
+}
+
+static CONFIG: RwLock<Option<Arc<BilibiliConfig>>> = RwLock::new(None);
+
+/// Install the settings used by subsequent extractions
+pub fn set_config(config: BilibiliConfig) {
+    let mut guard = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(Arc::new(config));
+}
+
+/// Current settings, the defaults when none were installed
+pub fn config() -> Arc<BilibiliConfig> {
+    let guard = CONFIG.read().unwrap_or_else(|e| e.into_inner());
+    guard.clone().unwrap_or_default()
+}
@@ -1,6 +1,17 @@
 use crate::Playlist;
-use crate::bilibili::types::{AudioInfo, PlayUrlResponse, ViewResponse};
-use crate::core::{Platform, Quality};
+use crate::bilibili::bangumi::{BangumiTarget, download_episode, extract_bangumi};
+use crate::bilibili::fav::extract_fav;
+use crate::bilibili::flv::AacTrack;
+use crate::bilibili::live::extract_live;
+use crate::bilibili::music::{MusicTarget, download_song, extract_music};
+use crate::bilibili::search::extract_search;
+use crate::bilibili::space::{SpaceOptions, extract_space};
+use crate::bilibili::types::{AudioInfo, PlayData, PlayUrlResponse, ViewResponse};
+use crate::bilibili::utils::{
+    parse_bangumi_target, parse_fav_target, parse_live_room, parse_music_target,
+    parse_search_query, parse_space_target,
+};
+use crate::core::{Locator, Platform, Quality};
 use crate::download::{download_binary_chunked, download_json, download_text};
 use crate::error::{MusicFreeError, Result};
 use crate::headers::{download_headers, with_accept_encoding, with_accept_language, with_origin};
@@ -48,15 +59,52 @@ pub fn get_audio_info(view: &ViewResponse) -> Vec<AudioInfo> {
     v
 }
 
-/// Extract playlist information from Bilibili URL
-pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
-    // Get cookies first
+/// Visit the homepage and fingerprint API so the cookie jar holds
+/// `buvid3`/`b_nut`, which most Bilibili APIs require
+pub(crate) async fn init_cookies() -> Result<()> {
     download_text("https://www.bilibili.com", HeaderMap::new()).await?;
     download_text(
         "https://api.bilibili.com/x/frontend/finger/spi",
         HeaderMap::new(),
     )
     .await?;
+    Ok(())
+}
+
+/// Extract playlist information from Bilibili URL
+pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
+    // Search input, sets up its own cookies
+    if let Some(query) = parse_search_query(url) {
+        return extract_search(&query).await;
+    }
+
+    // Get cookies first
+    init_cookies().await?;
+
+    // Live room
+    if let Some(room_id) = parse_live_room(url) {
+        return extract_live(room_id).await;
+    }
+
+    // Bangumi season or episode
+    if let Some(target) = parse_bangumi_target(url) {
+        return extract_bangumi(target).await;
+    }
+
+    // Music song or menu
+    if let Some(target) = parse_music_target(url) {
+        return extract_music(target).await;
+    }
+
+    // Favourites folder
+    if let Some(target) = parse_fav_target(url) {
+        return extract_fav(target).await;
+    }
+
+    // Uploader videos, series or collection
+    if let Some(target) = parse_space_target(url) {
+        return extract_space(target, &SpaceOptions::from_url(url)).await;
+    }
 
     let bvid = crate::bilibili::utils::parse_id(url).await?;
     let api_url = format!("https://api.bilibili.com/x/web-interface/view?bvid={bvid}");
@@ -90,7 +138,11 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
         let audio = Audio::new(id, info.title, audio_url, Platform::Bilibili)
             .with_format(AudioFormat::M4A)
             .with_duration(info.duration)
-            .with_cover(info.cover);
+            .with_cover(info.cover)
+            .with_locator(Locator::Bilibili {
+                bvid: info.bvid,
+                cid: info.cid,
+            });
 
         audios.push(audio);
     }
@@ -122,6 +174,7 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
         title: Some(title),
         audios,
         cover: Some(cover),
+        playlists: Vec::new(),
         platform: Platform::Bilibili,
     };
 
@@ -135,17 +188,30 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
     Ok((playlist, final_position))
 }
 
-/// Download audio from Bilibili video
+/// Download audio from Bilibili video, bangumi episode or music song
 pub async fn download_audio(url: &str) -> Result<Vec<u8>> {
+    if let Some(MusicTarget::Song(sid)) = parse_music_target(url) {
+        return download_song(sid, Quality::Super).await;
+    }
+
+    if let Some(BangumiTarget::Episode(ep_id)) = parse_bangumi_target(url) {
+        return download_episode(ep_id, Quality::Super).await;
+    }
+
+    // A live stream has no end to download up to, it is written to a file
+    // with `Extractor::record` instead
+    if let Some(room_id) = parse_live_room(url) {
+        return Err(MusicFreeError::DownloadFailed(format!(
+            "Live room {room_id} is a live stream, record it with a duration instead"
+        )));
+    }
+
     let bvid = crate::bilibili::utils::parse_id(url).await?;
-    let p = url::Url::parse(url)
-        .ok()
-        .and_then(|u| {
-            u.query_pairs()
-                .find(|(k, _v)| k == "p")
-                .and_then(|(_, v)| v.parse::<usize>().ok())
-        })
-        .unwrap_or(1);
+    let p = url::Url::parse(url).ok().and_then(|u| {
+        u.query_pairs()
+            .find(|(k, _v)| k == "p")
+            .and_then(|(_, v)| v.parse::<usize>().ok())
+    });
 
     let quality = Quality::Super;
     let url = format!("https://api.bilibili.com/x/web-interface/view?bvid={bvid}");
@@ -153,20 +219,54 @@ pub async fn download_audio(url: &str) -> Result<Vec<u8>> {
 
     let infos: Vec<_> = get_audio_info(&view).into_iter().collect();
 
-    let Some(info) = infos.get(p - 1) else {
+    // Without `?p=`, the URL names the video itself, which may sit anywhere
+    // inside its ugc_season
+    let info = match p {
+        Some(p) => infos.get(p.saturating_sub(1)),
+        None => infos
+            .iter()
+            .find(|i| i.bvid == bvid)
+            .or_else(|| infos.first()),
+    };
+    let p = p.unwrap_or(1);
+
+    let Some(info) = info else {
         return Err(MusicFreeError::DownloadFailed(format!(
             "Not found cid of page({p}) from bvid({bvid})"
         )));
     };
-    let cid = &info.cid;
-    let bvid = &info.bvid;
+    download_page(&info.bvid, info.cid, quality).await
+}
+
+/// Download the audio of a single video page
+pub async fn download_page(bvid: &str, cid: u64, quality: Quality) -> Result<Vec<u8>> {
     let fnval = 16; //dash
     let play_url =
         format!("https://api.bilibili.com/x/player/playurl?bvid={bvid}&cid={cid}&fnval={fnval}");
 
     let resp: PlayUrlResponse = download_json(&play_url, HeaderMap::new()).await?;
-    let play_data = resp.data;
-    let Some(media_url) = (if let Some(dash) = play_data.dash {
+    let Some(media) = select_media(resp.data, quality) else {
+        return Err(MusicFreeError::DownloadFailed(
+            "not found media_url".to_string(),
+        ));
+    };
+    let audio_url = format!("https://www.bilibili.com/video/{}", bvid);
+    download_media(media, &audio_url).await
+}
+
+/// Media selected from a playurl response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MediaStream {
+    /// DASH audio-only stream, already an M4A file
+    Dash(String),
+    /// Legacy FLV or MP4 segments holding both video and audio
+    Durl(Vec<String>),
+}
+
+/// Pick the media from a playurl response: the DASH audio stream matching
+/// `quality`, or every legacy `durl` segment
+pub(crate) fn select_media(play_data: PlayData, quality: Quality) -> Option<MediaStream> {
+    if let Some(dash) = play_data.dash {
         let mut audios = dash.audio;
         audios.sort_by_key(|a| a.bandwidth);
 
@@ -180,22 +280,44 @@ pub async fn download_audio(url: &str) -> Result<Vec<u8>> {
         audios
             .get(idx)
             .or_else(|| audios.last())
-            .map(|i| i.base_url.clone())
-            .clone()
+            .map(|i| MediaStream::Dash(i.base_url.clone()))
     } else {
-        play_data
-            .durl
-            .and_then(|d| d.first().map(|i| i.url.clone()))
-    }) else {
-        return Err(MusicFreeError::DownloadFailed(
-            "not found media_url".to_string(),
-        ));
-    };
-    let audio_url = format!("https://www.bilibili.com/video/{}", bvid);
-    let mut headers = download_headers(DEFAULT_USER_AGENT, &audio_url);
+        let urls: Vec<_> = play_data.durl?.into_iter().map(|d| d.url).collect();
+        (!urls.is_empty()).then_some(MediaStream::Durl(urls))
+    }
+}
+
+/// Download a media stream with the browser headers the Bilibili CDN expects
+///
+/// FLV segments are downloaded in order and their AAC track remuxed to M4A,
+/// a single MP4 segment is returned as is.
+pub(crate) async fn download_media(media: MediaStream, referer: &str) -> Result<Vec<u8>> {
+    let mut headers = download_headers(DEFAULT_USER_AGENT, referer);
     with_accept_encoding(&mut headers, "gzip, deflate, br");
     with_accept_language(&mut headers, "zh-CN,zh;q=0.9,en;q=0.8");
     with_origin(&mut headers, "https://www.bilibili.com");
-    let bin = download_binary_chunked(&media_url, headers).await?;
-    Ok(bin)
+
+    match media {
+        MediaStream::Dash(url) => download_binary_chunked(&url, headers).await,
+        MediaStream::Durl(urls) => {
+            let mut track = AacTrack::new();
+            for url in &urls {
+                let data = download_binary_chunked(url, headers.clone()).await?;
+                match AudioFormat::from_signature(&data) {
+                    Some(AudioFormat::Flv) => track.push_flv(&data)?,
+                    // A single MP4 file is already playable as M4A
+                    Some(format) if format.same_container(&AudioFormat::M4A) && urls.len() == 1 => {
+                        return Ok(data);
+                    }
+                    format => {
+                        return Err(MusicFreeError::ExtractionFailed(format!(
+                            "Unsupported durl container {format:?} in {} segment(s)",
+                            urls.len()
+                        )));
+                    }
+                }
+            }
+            track.into_m4a()
+        }
+    }
 }
@@ -0,0 +1,259 @@
+//! Favourites folder (收藏夹) extraction.
+//!
+//! Pages through the `x/v3/fav/resource/list` API and flattens every video
+//! of the folder, including all parts of multi-page videos, into a `Playlist`.
+//! Space links without a `fid` open the default folder, which is the first
+//! one `x/v3/fav/folder/created/list-all` returns.
+
+use crate::bilibili::types::{FavFolderListResponse, FavMedia, FavResourceResponse, ViewResponse};
+use crate::bilibili::utils::api_error;
+use crate::core::{Locator, Platform};
+use crate::download::download_json;
+use crate::error::{MusicFreeError, Result};
+use crate::{Audio, AudioFormat, Playlist};
+use reqwest::header::HeaderMap;
+
+/// Page size used by the web client for favourites folders
+const FAV_PAGE_SIZE: u32 = 20;
+
+/// Media type of a video resource inside a favourites folder
+const FAV_MEDIA_TYPE_VIDEO: u64 = 2;
+
+/// `attr` bit of resources that were deleted or made invalid (`1` deleted,
+/// `9` deleted by the uploader), other bits are unrelated flags
+const FAV_ATTR_INVALID: u64 = 1;
+
+/// Title the API gives deleted videos
+const FAV_INVALID_TITLE: &str = "已失效视频";
+
+/// What a favourites URL points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FavTarget {
+    /// A folder by its media ID
+    Folder(u64),
+    /// The default folder of `mid`
+    Default { mid: u64 },
+}
+
+/// Whether a favourite is a deleted or hidden video
+fn is_invalid(media: &FavMedia) -> bool {
+    media.attr & FAV_ATTR_INVALID != 0 || media.title == FAV_INVALID_TITLE
+}
+
+/// Build the canonical web URL of a favourites folder
+pub fn build_favlist_url(media_id: u64) -> String {
+    format!("https://www.bilibili.com/medialist/detail/ml{media_id}")
+}
+
+/// Extract every audio of a favourites folder, resolving default folders first
+pub async fn extract_fav(target: FavTarget) -> Result<(Playlist, Option<usize>)> {
+    let media_id = match target {
+        FavTarget::Folder(media_id) => media_id,
+        FavTarget::Default { mid } => default_folder(mid).await?,
+    };
+    extract_favlist(media_id).await
+}
+
+/// Media ID of the default favourites folder of `mid`
+async fn default_folder(mid: u64) -> Result<u64> {
+    let api_url = format!("https://api.bilibili.com/x/v3/fav/folder/created/list-all?up_mid={mid}");
+    let resp: FavFolderListResponse = download_json(&api_url, HeaderMap::new()).await?;
+    first_folder(resp, mid)
+}
+
+/// The default folder is always listed first
+fn first_folder(resp: FavFolderListResponse, mid: u64) -> Result<u64> {
+    let Some(data) = resp.data else {
+        return Err(api_error(
+            &format!("Favourites of uploader {mid}"),
+            resp.code,
+            &resp.message,
+        ));
+    };
+    data.list
+        .and_then(|list| list.first().map(|folder| folder.id))
+        .ok_or_else(|| {
+            MusicFreeError::ExtractionFailed(format!(
+                "Uploader {mid} has no public favourites folder"
+            ))
+        })
+}
+
+/// Extract every audio of a favourites folder
+pub async fn extract_favlist(media_id: u64) -> Result<(Playlist, Option<usize>)> {
+    let mut audios = vec![];
+    let mut folder = None;
+    let mut pn = 1;
+
+    loop {
+        let api_url = format!(
+            "https://api.bilibili.com/x/v3/fav/resource/list?media_id={media_id}&pn={pn}&ps={FAV_PAGE_SIZE}&platform=web"
+        );
+        let resp: FavResourceResponse = download_json(&api_url, HeaderMap::new()).await?;
+        let Some(data) = resp.data else {
+            return Err(api_error(
+                &format!("Favourites folder {media_id}"),
+                resp.code,
+                &resp.message,
+            ));
+        };
+
+        for media in data.medias.unwrap_or_default() {
+            // Skip non-video resources and videos that were deleted or hidden
+            if media.media_type != FAV_MEDIA_TYPE_VIDEO || is_invalid(&media) {
+                continue;
+            }
+            audios.extend(media_audios(&media).await?);
+        }
+
+        if folder.is_none() {
+            folder = Some(data.info);
+        }
+
+        if !data.has_more {
+            break;
+        }
+        pn += 1;
+    }
+
+    let Some(folder) = folder else {
+        return Err(MusicFreeError::ExtractionFailed(format!(
+            "Favourites folder {media_id} returned no data"
+        )));
+    };
+
+    let playlist = Playlist {
+        id: Some(folder.id.to_string()),
+        download_url: Some(build_favlist_url(folder.id)),
+        title: Some(folder.title),
+        audios,
+        cover: Some(folder.cover),
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    };
+
+    Ok((playlist, None))
+}
+
+/// Convert a favourites folder entry into audios, expanding multi-page videos
+async fn media_audios(media: &FavMedia) -> Result<Vec<Audio>> {
+    let bvid = &media.bvid;
+
+    if media.page <= 1 {
+        let cid = media.ugc.as_ref().map(|ugc| ugc.first_cid);
+        let id = cid.map_or_else(|| bvid.clone(), |cid| cid.to_string());
+        let mut audio = Audio::new(
+            id,
+            media.title.clone(),
+            format!("https://www.bilibili.com/video/{bvid}"),
+            Platform::Bilibili,
+        )
+        .with_format(AudioFormat::M4A)
+        .with_duration(media.duration)
+        .with_cover(media.cover.clone());
+        if let Some(cid) = cid {
+            audio = audio.with_locator(Locator::Bilibili {
+                bvid: bvid.clone(),
+                cid,
+            });
+        }
+        return Ok(vec![audio]);
+    }
+
+    // Multi-page videos only expose the page count here, fetch the pages
+    let api_url = format!("https://api.bilibili.com/x/web-interface/view?bvid={bvid}");
+    let view: ViewResponse = download_json(&api_url, HeaderMap::new()).await?;
+
+    let audios = view
+        .data
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| {
+            Audio::new(
+                page.cid.to_string(),
+                page.part.clone(),
+                format!("https://www.bilibili.com/video/{bvid}?p={}", index + 1),
+                Platform::Bilibili,
+            )
+            .with_format(AudioFormat::M4A)
+            .with_duration(page.duration)
+            .with_cover(media.cover.clone())
+            .with_locator(Locator::Bilibili {
+                bvid: bvid.clone(),
+                cid: page.cid,
+            })
+        })
+        .collect();
+
+    Ok(audios)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn media(attr: u64, title: &str) -> FavMedia {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "type": FAV_MEDIA_TYPE_VIDEO,
+            "title": title,
+            "cover": "",
+            "duration": 60,
+            "page": 1,
+            "attr": attr,
+            "bvid": "BV1xx411c7mD",
+            "upper": { "mid": 1, "name": "up" },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_valid_videos_with_other_attr_bits() {
+        assert!(!is_invalid(&media(0, "title")));
+        assert!(!is_invalid(&media(2, "title")));
+    }
+
+    #[test]
+    fn skips_deleted_videos() {
+        assert!(is_invalid(&media(1, "title")));
+        assert!(is_invalid(&media(9, FAV_INVALID_TITLE)));
+        assert!(is_invalid(&media(0, FAV_INVALID_TITLE)));
+    }
+
+    fn folders(json: serde_json::Value) -> FavFolderListResponse {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn picks_the_default_folder() {
+        let resp = folders(serde_json::json!({
+            "code": 0,
+            "message": "0",
+            "data": {
+                "count": 2,
+                "list": [
+                    { "id": 1052622027, "title": "默认收藏夹", "media_count": 5 },
+                    { "id": 1052622127, "title": "music", "media_count": 3 },
+                ],
+            },
+        }));
+        assert_eq!(first_folder(resp, 2).unwrap(), 1052622027);
+    }
+
+    #[test]
+    fn rejects_spaces_without_public_folders() {
+        let hidden = folders(serde_json::json!({ "code": 0, "message": "0", "data": null }));
+        assert!(first_folder(hidden, 2).is_err());
+
+        let empty = folders(serde_json::json!({
+            "code": 0,
+            "message": "0",
+            "data": { "count": 0, "list": null },
+        }));
+        assert!(matches!(
+            first_folder(empty, 2),
+            Err(MusicFreeError::ExtractionFailed(_))
+        ));
+    }
+}
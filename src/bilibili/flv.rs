@@ -0,0 +1,606 @@
+//! Pull the AAC track out of legacy FLV (`durl`) streams and remux it to M4A.
+//!
+//! Old videos only have `durl` segments: complete FLV files holding both
+//! video and audio. The AAC audio tags of every segment are collected in
+//! order and written into a single-track MP4 container, so the result
+//! matches the declared `AudioFormat::M4A`.
+//!
+//! Live HTTP-FLV streams never end, their AAC frames are written out as
+//! ADTS while they arrive instead.
+
+use std::io::{self, Write};
+
+use crate::error::{MusicFreeError, Result};
+
+const FLV_TAG_AUDIO: u8 = 8;
+const SOUND_FORMAT_AAC: u8 = 10;
+const AAC_SEQUENCE_HEADER: u8 = 0;
+const AAC_RAW: u8 = 1;
+
+/// Samples per AAC frame
+const AAC_FRAME_SAMPLES: u32 = 1024;
+
+const ADTS_HEADER_SIZE: usize = 7;
+
+const SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// AAC audio collected from one or more FLV segments
+#[derive(Debug, Clone, Default)]
+pub struct AacTrack {
+    /// AudioSpecificConfig from the AAC sequence header
+    config: Vec<u8>,
+    /// Raw AAC frames
+    frames: Vec<Vec<u8>>,
+}
+
+fn read_u24(b: &[u8]) -> usize {
+    (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize
+}
+
+fn invalid(msg: &str) -> MusicFreeError {
+    MusicFreeError::ParseError(format!("Invalid FLV: {msg}"))
+}
+
+impl AacTrack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the audio of a complete FLV segment
+    ///
+    /// A truncated trailing tag is ignored, segments cut mid-tag by the
+    /// CDN still yield every complete frame.
+    pub fn push_flv(&mut self, flv: &[u8]) -> Result<()> {
+        if flv.len() < 9 || &flv[..3] != b"FLV" {
+            return Err(invalid("missing FLV signature"));
+        }
+        let header_size = u32::from_be_bytes([flv[5], flv[6], flv[7], flv[8]]) as usize;
+        // Skip the header and the first PreviousTagSize
+        let mut pos = header_size + 4;
+
+        while pos + 11 <= flv.len() {
+            let tag_type = flv[pos] & 0x1f;
+            let size = read_u24(&flv[pos + 1..]);
+            let start = pos + 11;
+            let end = start + size;
+            if end > flv.len() {
+                break;
+            }
+            if tag_type == FLV_TAG_AUDIO && size >= 2 {
+                self.push_audio_tag(&flv[start..end])?;
+            }
+            pos = end + 4;
+        }
+        Ok(())
+    }
+
+    fn push_audio_tag(&mut self, data: &[u8]) -> Result<()> {
+        let sound_format = data[0] >> 4;
+        if sound_format != SOUND_FORMAT_AAC {
+            return Err(MusicFreeError::ExtractionFailed(format!(
+                "Unsupported FLV audio codec (SoundFormat {sound_format})"
+            )));
+        }
+        match data[1] {
+            AAC_SEQUENCE_HEADER if self.config.is_empty() => self.config = data[2..].to_vec(),
+            AAC_RAW if data.len() > 2 => self.frames.push(data[2..].to_vec()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Sample rate and channel count from the AudioSpecificConfig
+    fn audio_params(&self) -> Result<(u32, u16)> {
+        let c = &self.config;
+        if c.len() < 2 {
+            return Err(invalid("missing AAC sequence header"));
+        }
+        let rate_index = ((c[0] & 0x07) << 1 | c[1] >> 7) as usize;
+        let (sample_rate, channels) = if rate_index == 0x0f {
+            // Explicit 24-bit sample rate follows the index
+            if c.len() < 5 {
+                return Err(invalid("truncated AudioSpecificConfig"));
+            }
+            let bits = u32::from_be_bytes([c[1], c[2], c[3], c[4]]);
+            ((bits >> 7) & 0xff_ffff, (c[4] >> 3) & 0x0f)
+        } else {
+            let rate = SAMPLE_RATES
+                .get(rate_index)
+                .copied()
+                .ok_or_else(|| invalid("bad AAC sample rate index"))?;
+            (rate, (c[1] >> 3) & 0x0f)
+        };
+        // Channel config 0 means "defined elsewhere", assume stereo
+        let channels = if channels == 0 { 2 } else { channels as u16 };
+        Ok((sample_rate, channels))
+    }
+
+    /// ADTS header of a frame of `frame_len` bytes
+    fn adts_header(&self, frame_len: usize) -> Result<[u8; ADTS_HEADER_SIZE]> {
+        let c = &self.config;
+        if c.len() < 2 {
+            return Err(invalid("missing AAC sequence header"));
+        }
+        let object_type = c[0] >> 3;
+        let rate_index = (c[0] & 0x07) << 1 | c[1] >> 7;
+        let channels = (c[1] >> 3) & 0x0f;
+        // ADTS only has the four base profiles and the indexed rates
+        if !(1..=4).contains(&object_type) || rate_index >= SAMPLE_RATES.len() as u8 {
+            return Err(MusicFreeError::ExtractionFailed(format!(
+                "AAC object type {object_type} at rate index {rate_index} has no ADTS form"
+            )));
+        }
+        let len = frame_len + ADTS_HEADER_SIZE;
+        if len >= 1 << 13 {
+            return Err(invalid("AAC frame too large"));
+        }
+        Ok([
+            0xff,
+            0xf1, // MPEG-4, no CRC
+            (object_type - 1) << 6 | rate_index << 2 | channels >> 2,
+            (channels & 0x03) << 6 | (len >> 11) as u8,
+            (len >> 3) as u8,
+            (len as u8 & 0x07) << 5 | 0x1f,
+            0xfc, // buffer fullness 0x7ff: variable bitrate
+        ])
+    }
+
+    /// Mux the collected frames into an M4A file
+    pub fn into_m4a(self) -> Result<Vec<u8>> {
+        if self.frames.is_empty() {
+            return Err(MusicFreeError::AudioNotFound);
+        }
+        let (sample_rate, channels) = self.audio_params()?;
+
+        let ftyp = mp4_box(b"ftyp", &[b"M4A ", &0u32.to_be_bytes(), b"M4A mp42isom"]);
+        let mdat_len: usize = self.frames.iter().map(Vec::len).sum();
+        let mdat_size = u32::try_from(mdat_len + 8)
+            .map_err(|_| MusicFreeError::DownloadFailed("audio track too large".to_string()))?;
+
+        // stco does not change size with its value, so the offset is known
+        // from a first pass
+        let moov_len = self.moov(sample_rate, channels, 0).len();
+        let data_offset = (ftyp.len() + moov_len + 8) as u32;
+        let moov = self.moov(sample_rate, channels, data_offset);
+
+        let mut out = Vec::with_capacity(data_offset as usize + mdat_len);
+        out.extend_from_slice(&ftyp);
+        out.extend_from_slice(&moov);
+        out.extend_from_slice(&mdat_size.to_be_bytes());
+        out.extend_from_slice(b"mdat");
+        for frame in &self.frames {
+            out.extend_from_slice(frame);
+        }
+        Ok(out)
+    }
+
+    fn moov(&self, sample_rate: u32, channels: u16, data_offset: u32) -> Vec<u8> {
+        let count = self.frames.len() as u32;
+        // Over 4G samples (a day at 48 kHz) only fit the 64-bit version 1
+        // boxes, clamp instead
+        let duration = u32::try_from(u64::from(count) * u64::from(AAC_FRAME_SAMPLES))
+            .unwrap_or(u32::MAX)
+            .to_be_bytes();
+        let timescale = sample_rate.to_be_bytes();
+        let matrix: Vec<u8> = [0x0001_0000u32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+
+        let mvhd = full_box(
+            b"mvhd",
+            0,
+            &[
+                &[0; 8], // creation/modification time
+                &timescale,
+                &duration,
+                &0x0001_0000u32.to_be_bytes(), // rate 1.0
+                &0x0100u16.to_be_bytes(),      // volume 1.0
+                &[0; 10],
+                &matrix,
+                &[0; 24],
+                &2u32.to_be_bytes(), // next track id
+            ],
+        );
+        let tkhd = full_box(
+            b"tkhd",
+            0x03, // enabled | in movie
+            &[
+                &[0; 8],
+                &1u32.to_be_bytes(), // track id
+                &[0; 4],
+                &duration,
+                &[0; 8],
+                &[0; 4], // layer, alternate group
+                &0x0100u16.to_be_bytes(),
+                &[0; 2],
+                &matrix,
+                &[0; 8], // width, height
+            ],
+        );
+        let mdhd = full_box(
+            b"mdhd",
+            0,
+            &[
+                &[0; 8],
+                &timescale,
+                &duration,
+                &0x55c4u16.to_be_bytes(), // language "und"
+                &[0; 2],
+            ],
+        );
+        let hdlr = full_box(b"hdlr", 0, &[&[0; 4], b"soun", &[0; 12], b"SoundHandler\0"]);
+        let smhd = full_box(b"smhd", 0, &[&[0; 4]]);
+        let dref = full_box(
+            b"dref",
+            0,
+            &[&1u32.to_be_bytes(), &full_box(b"url ", 0x01, &[])],
+        );
+        let dinf = mp4_box(b"dinf", &[&dref]);
+
+        let stsd = full_box(
+            b"stsd",
+            0,
+            &[&1u32.to_be_bytes(), &self.mp4a(sample_rate, channels)],
+        );
+        let stts = full_box(
+            b"stts",
+            0,
+            &[
+                &1u32.to_be_bytes(),
+                &count.to_be_bytes(),
+                &AAC_FRAME_SAMPLES.to_be_bytes(),
+            ],
+        );
+        let stsc = full_box(
+            b"stsc",
+            0,
+            &[
+                &1u32.to_be_bytes(),
+                &1u32.to_be_bytes(), // first chunk
+                &count.to_be_bytes(),
+                &1u32.to_be_bytes(), // sample description index
+            ],
+        );
+        let sizes: Vec<u8> = self
+            .frames
+            .iter()
+            .flat_map(|f| (f.len() as u32).to_be_bytes())
+            .collect();
+        let stsz = full_box(b"stsz", 0, &[&[0; 4], &count.to_be_bytes(), &sizes]);
+        let stco = full_box(
+            b"stco",
+            0,
+            &[&1u32.to_be_bytes(), &data_offset.to_be_bytes()],
+        );
+        let stbl = mp4_box(b"stbl", &[&stsd, &stts, &stsc, &stsz, &stco]);
+
+        let minf = mp4_box(b"minf", &[&smhd, &dinf, &stbl]);
+        let mdia = mp4_box(b"mdia", &[&mdhd, &hdlr, &minf]);
+        let trak = mp4_box(b"trak", &[&tkhd, &mdia]);
+        mp4_box(b"moov", &[&mvhd, &trak])
+    }
+
+    fn mp4a(&self, sample_rate: u32, channels: u16) -> Vec<u8> {
+        let decoder_specific = descriptor(0x05, &[&self.config]);
+        let decoder_config = descriptor(
+            0x04,
+            &[
+                &[0x40],         // object type: MPEG-4 audio
+                &[0x15],         // stream type: audio
+                &[0; 3 + 4 + 4], // buffer size, max/avg bitrate
+                &decoder_specific,
+            ],
+        );
+        let sl_config = descriptor(0x06, &[&[0x02]]);
+        let es = descriptor(
+            0x03,
+            &[&1u16.to_be_bytes(), &[0], &decoder_config, &sl_config],
+        );
+        let esds = full_box(b"esds", 0, &[&es]);
+
+        // The 16.16 field can't hold rates above 65535, mdhd has the real one
+        let rate = sample_rate.min(0xffff) << 16;
+        mp4_box(
+            b"mp4a",
+            &[
+                &[0; 6],
+                &1u16.to_be_bytes(), // data reference index
+                &[0; 8],
+                &channels.to_be_bytes(),
+                &16u16.to_be_bytes(), // sample size
+                &[0; 4],
+                &rate.to_be_bytes(),
+                &esds,
+            ],
+        )
+    }
+}
+
+/// Writer turning an FLV stream into the ADTS stream of its AAC track
+///
+/// FLV data is buffered until a tag is complete, video and metadata tags
+/// are dropped.
+pub struct AdtsWriter<W: Write> {
+    inner: W,
+    track: AacTrack,
+    buffer: Vec<u8>,
+    /// Whether the FLV header has been skipped
+    header_read: bool,
+    written: u64,
+}
+
+impl<W: Write> AdtsWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            track: AacTrack::new(),
+            buffer: Vec::new(),
+            header_read: false,
+            written: 0,
+        }
+    }
+
+    /// Number of ADTS bytes written to the inner writer
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Write out every complete tag of the buffer
+    fn drain(&mut self) -> Result<()> {
+        let mut pos = 0;
+        if !self.header_read {
+            if self.buffer.len() < 9 {
+                return Ok(());
+            }
+            if &self.buffer[..3] != b"FLV" {
+                return Err(invalid("missing FLV signature"));
+            }
+            let b = &self.buffer;
+            // Skip the header and the first PreviousTagSize
+            pos = u32::from_be_bytes([b[5], b[6], b[7], b[8]]) as usize + 4;
+            if b.len() < pos {
+                return Ok(());
+            }
+            self.header_read = true;
+        }
+
+        while pos + 11 <= self.buffer.len() {
+            let tag_type = self.buffer[pos] & 0x1f;
+            let size = read_u24(&self.buffer[pos + 1..]);
+            let start = pos + 11;
+            let end = start + size;
+            if end + 4 > self.buffer.len() {
+                break;
+            }
+            if tag_type == FLV_TAG_AUDIO && size >= 2 {
+                self.track.push_audio_tag(&self.buffer[start..end])?;
+                for frame in std::mem::take(&mut self.track.frames) {
+                    self.inner
+                        .write_all(&self.track.adts_header(frame.len())?)?;
+                    self.inner.write_all(&frame)?;
+                    self.written += (ADTS_HEADER_SIZE + frame.len()) as u64;
+                }
+            }
+            pos = end + 4;
+        }
+        self.buffer.drain(..pos);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for AdtsWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        self.drain().map_err(|e| match e {
+            MusicFreeError::IoError(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+        })?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn mp4_box(kind: &[u8; 4], parts: &[&[u8]]) -> Vec<u8> {
+    let len = 8 + parts.iter().map(|p| p.len()).sum::<usize>();
+    let mut out = Vec::with_capacity(len);
+    out.extend_from_slice(&(len as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    for part in parts {
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+fn full_box(kind: &[u8; 4], flags: u32, parts: &[&[u8]]) -> Vec<u8> {
+    // version 0 in the high byte
+    let header = (flags & 0xff_ffff).to_be_bytes();
+    let mut all = vec![&header[..]];
+    all.extend_from_slice(parts);
+    mp4_box(kind, &all)
+}
+
+/// MPEG-4 descriptor with a 4-byte size field
+fn descriptor(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+    let len = parts.iter().map(|p| p.len()).sum::<usize>() as u32;
+    let mut out = vec![
+        tag,
+        0x80 | (len >> 21) as u8 & 0x7f,
+        0x80 | (len >> 14) as u8 & 0x7f,
+        0x80 | (len >> 7) as u8 & 0x7f,
+        len as u8 & 0x7f,
+    ];
+    for part in parts {
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::AudioFormat;
+
+    /// AudioSpecificConfig of AAC-LC, 44.1 kHz, stereo
+    const CONFIG: [u8; 2] = [0x12, 0x10];
+
+    fn tag(tag_type: u8, data: &[u8]) -> Vec<u8> {
+        let size = data.len() as u32;
+        let mut out = vec![tag_type];
+        out.extend_from_slice(&size.to_be_bytes()[1..]);
+        out.extend_from_slice(&[0; 7]); // timestamp, stream id
+        out.extend_from_slice(data);
+        out.extend_from_slice(&(size + 11).to_be_bytes());
+        out
+    }
+
+    fn aac_tag(packet_type: u8, payload: &[u8]) -> Vec<u8> {
+        // AAC, 44 kHz, 16-bit, stereo
+        let mut data = vec![0xaf, packet_type];
+        data.extend_from_slice(payload);
+        tag(FLV_TAG_AUDIO, &data)
+    }
+
+    fn flv(tags: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = b"FLV\x01\x05\x00\x00\x00\x09\x00\x00\x00\x00".to_vec();
+        for tag in tags {
+            out.extend_from_slice(tag);
+        }
+        out
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .unwrap()
+    }
+
+    fn read_u32(data: &[u8], pos: usize) -> u32 {
+        u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn remuxes_aac_frames_across_segments() {
+        let video = tag(9, &[0x17, 0x01, 0, 0, 0, 0xaa]);
+        let first = flv(&[
+            aac_tag(AAC_SEQUENCE_HEADER, &CONFIG),
+            video.clone(),
+            aac_tag(AAC_RAW, b"frame1"),
+        ]);
+        // Later segments repeat the sequence header
+        let second = flv(&[
+            aac_tag(AAC_SEQUENCE_HEADER, &CONFIG),
+            aac_tag(AAC_RAW, b"frame02"),
+            video,
+        ]);
+
+        let mut track = AacTrack::new();
+        track.push_flv(&first).unwrap();
+        track.push_flv(&second).unwrap();
+        assert_eq!(track.config, CONFIG);
+        assert_eq!(track.audio_params().unwrap(), (44100, 2));
+
+        let m4a = track.into_m4a().unwrap();
+        assert_eq!(&m4a[4..12], b"ftypM4A ");
+        assert_eq!(AudioFormat::from_signature(&m4a), Some(AudioFormat::M4A));
+
+        // stco points at the frames inside mdat
+        let mdat = find(&m4a, b"mdat") - 4;
+        assert_eq!(read_u32(&m4a, mdat), 8 + 13);
+        assert_eq!(&m4a[mdat + 8..], b"frame1frame02");
+        let stco = find(&m4a, b"stco");
+        assert_eq!(read_u32(&m4a, stco + 12) as usize, mdat + 8);
+
+        let stsz = find(&m4a, b"stsz");
+        assert_eq!(read_u32(&m4a, stsz + 12), 2);
+        assert_eq!(read_u32(&m4a, stsz + 16), 6);
+        assert_eq!(read_u32(&m4a, stsz + 20), 7);
+
+        let mdhd = find(&m4a, b"mdhd");
+        assert_eq!(read_u32(&m4a, mdhd + 16), 44100);
+        assert_eq!(read_u32(&m4a, mdhd + 20), 2 * AAC_FRAME_SAMPLES);
+    }
+
+    #[test]
+    fn ignores_a_truncated_trailing_tag() {
+        let mut data = flv(&[
+            aac_tag(AAC_SEQUENCE_HEADER, &CONFIG),
+            aac_tag(AAC_RAW, b"whole"),
+        ]);
+        let cut = aac_tag(AAC_RAW, b"cut off by the CDN");
+        data.extend_from_slice(&cut[..cut.len() - 8]);
+
+        let mut track = AacTrack::new();
+        track.push_flv(&data).unwrap();
+        assert_eq!(track.frames, [b"whole".to_vec()]);
+    }
+
+    #[test]
+    fn reads_explicit_sample_rates() {
+        // AAC-LC, escape index, 37800 Hz, mono
+        let mut track = AacTrack::new();
+        track.config = vec![0x17, 0x80, 0x49, 0xd4, 0x08];
+        assert_eq!(track.audio_params().unwrap(), (37800, 1));
+
+        track.config = vec![0x17, 0x80];
+        assert!(track.audio_params().is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_input() {
+        let mut track = AacTrack::new();
+        assert!(track.push_flv(b"\x00\x00\x00\x20ftypisom").is_err());
+
+        // MP3 audio tag
+        let mp3 = flv(&[tag(FLV_TAG_AUDIO, &[0x2f, 0xff, 0xfb])]);
+        assert!(matches!(
+            track.push_flv(&mp3),
+            Err(MusicFreeError::ExtractionFailed(_))
+        ));
+
+        // Video only
+        let video = flv(&[tag(9, &[0x17, 0x01])]);
+        track.push_flv(&video).unwrap();
+        assert!(matches!(
+            track.into_m4a(),
+            Err(MusicFreeError::AudioNotFound)
+        ));
+    }
+
+    #[test]
+    fn streams_aac_frames_as_adts() {
+        let data = flv(&[
+            tag(18, b"onMetaData"),
+            aac_tag(AAC_SEQUENCE_HEADER, &CONFIG),
+            aac_tag(AAC_RAW, b"frame1"),
+            tag(9, &[0x17, 0x01]),
+            aac_tag(AAC_RAW, b"frame02"),
+        ]);
+        let mut out = Vec::new();
+        let mut adts = AdtsWriter::new(&mut out);
+        // Network chunks split tags anywhere
+        for chunk in data.chunks(5) {
+            adts.write_all(chunk).unwrap();
+        }
+        assert_eq!(adts.written(), 2 * 7 + 13);
+
+        assert_eq!(AudioFormat::from_signature(&out), Some(AudioFormat::AAC));
+        // AAC-LC, 44.1 kHz, stereo, 13 bytes
+        assert_eq!(&out[..7], [0xff, 0xf1, 0x50, 0x80, 0x01, 0xbf, 0xfc]);
+        assert_eq!(&out[7..13], b"frame1");
+        assert_eq!(&out[13..15], [0xff, 0xf1]);
+        assert_eq!(&out[20..], b"frame02");
+    }
+
+    #[test]
+    fn rejects_non_flv_streams() {
+        let mut adts = AdtsWriter::new(Vec::new());
+        assert!(adts.write_all(b"<html>error</html>").is_err());
+    }
+}
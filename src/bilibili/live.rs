@@ -0,0 +1,185 @@
+//! Bilibili live room (直播间) extraction and recording.
+//!
+//! Room metadata comes from `getInfoByRoom`, the stream from
+//! `getRoomPlayInfo` with `only_audio=1`. Audio-only fMP4 HLS is preferred
+//! since it concatenates into a valid M4A; FLV is used as fallback, its AAC
+//! track is written out as ADTS.
+
+use std::io::Write;
+use std::time::Instant;
+
+use crate::bilibili::flv::AdtsWriter;
+use crate::bilibili::types::{
+    LiveCodec, LivePlayInfoResponse, LiveRoomInfoData, LiveRoomInfoResponse,
+};
+use crate::bilibili::utils::{api_error, normalize_url};
+use crate::core::{Platform, RecordOptions};
+use crate::download::{DEFAULT_USER_AGENT, download_json, record_stream};
+use crate::error::{MusicFreeError, Result};
+use crate::headers::{download_headers, with_origin};
+use crate::hls::record_hls;
+use crate::{Audio, AudioFormat, Playlist};
+use reqwest::header::HeaderMap;
+
+/// `live_status` of a room that is currently streaming
+const LIVE_STATUS_LIVE: u64 = 1;
+
+/// Stream protocol of a live room
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiveProtocol {
+    /// HTTP-FLV, a single endless response, recorded as ADTS AAC
+    Flv,
+    /// HLS with fMP4 segments
+    Hls,
+}
+
+/// Resolved stream of a live room
+#[derive(Debug, Clone)]
+pub struct LiveStreamUrl {
+    pub protocol: LiveProtocol,
+    pub url: String,
+}
+
+impl LiveStreamUrl {
+    /// Format of the recorded file
+    pub fn format(&self) -> AudioFormat {
+        match self.protocol {
+            LiveProtocol::Flv => AudioFormat::AAC,
+            LiveProtocol::Hls => AudioFormat::M4A,
+        }
+    }
+}
+
+/// Build the web URL of a live room
+pub fn build_live_url(room_id: u64) -> String {
+    format!("https://live.bilibili.com/{room_id}")
+}
+
+/// Fetch room title, status and streamer (accepts short room ids)
+pub async fn get_room_info(room_id: u64) -> Result<LiveRoomInfoData> {
+    let api_url = format!(
+        "https://api.live.bilibili.com/xlive/web-room/v1/index/getInfoByRoom?room_id={room_id}"
+    );
+    let resp: LiveRoomInfoResponse = download_json(&api_url, HeaderMap::new()).await?;
+    match resp.data {
+        Some(data) if resp.code == 0 => Ok(data),
+        _ => Err(api_error(
+            &format!("Live room {room_id}"),
+            resp.code,
+            &resp.message,
+        )),
+    }
+}
+
+fn codec_url(codec: &LiveCodec) -> Option<String> {
+    codec
+        .url_info
+        .first()
+        .map(|info| format!("{}{}{}", info.host, codec.base_url, info.extra))
+}
+
+/// Resolve the audio stream of a live room
+pub async fn get_live_stream(room_id: u64) -> Result<LiveStreamUrl> {
+    let api_url = format!(
+        "https://api.live.bilibili.com/xlive/web-room/v2/index/getRoomPlayInfo?room_id={room_id}&protocol=0,1&format=0,2&codec=0&qn=10000&platform=web&ptype=8&only_audio=1"
+    );
+    let resp: LivePlayInfoResponse = download_json(&api_url, HeaderMap::new()).await?;
+    let Some(data) = resp.data.filter(|_| resp.code == 0) else {
+        return Err(api_error(
+            &format!("Live room {room_id}"),
+            resp.code,
+            &resp.message,
+        ));
+    };
+
+    if data.live_status != LIVE_STATUS_LIVE {
+        return Err(MusicFreeError::ExtractionFailed(format!(
+            "Live room {room_id} is not streaming"
+        )));
+    }
+
+    let streams = data
+        .playurl_info
+        .map(|info| info.playurl.stream)
+        .unwrap_or_default();
+    let find = |protocol_name: &str, format_name: &str| {
+        streams
+            .iter()
+            .filter(|s| s.protocol_name == protocol_name)
+            .flat_map(|s| &s.format)
+            .filter(|f| f.format_name == format_name)
+            .flat_map(|f| &f.codec)
+            .find_map(codec_url)
+    };
+
+    if let Some(url) = find("http_hls", "fmp4") {
+        return Ok(LiveStreamUrl {
+            protocol: LiveProtocol::Hls,
+            url,
+        });
+    }
+    if let Some(url) = find("http_stream", "flv") {
+        return Ok(LiveStreamUrl {
+            protocol: LiveProtocol::Flv,
+            url,
+        });
+    }
+
+    Err(MusicFreeError::AudioNotFound)
+}
+
+/// Extract a live room as a single live audio
+pub async fn extract_live(room_id: u64) -> Result<(Playlist, Option<usize>)> {
+    let info = get_room_info(room_id).await?;
+    let room = info.room_info;
+    let stream = get_live_stream(room.room_id).await?;
+    let cover = normalize_url(&room.cover);
+
+    let audio = Audio::new(
+        room.room_id.to_string(),
+        room.title.clone(),
+        build_live_url(room.room_id),
+        Platform::Bilibili,
+    )
+    .with_format(stream.format())
+    .with_cover(cover.clone())
+    .with_artist(info.anchor_info.base_info.uname)
+    .with_live(true);
+
+    let playlist = Playlist {
+        id: Some(room.room_id.to_string()),
+        download_url: Some(audio.download_url.clone()),
+        title: Some(room.title),
+        audios: vec![audio],
+        cover: Some(cover),
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    };
+
+    Ok((playlist, Some(0)))
+}
+
+/// Record a live room's audio into `writer`
+///
+/// Stops when `options.duration` elapses or the stream ends.
+/// Returns the number of bytes written.
+pub async fn record_live(
+    room_id: u64,
+    options: &RecordOptions,
+    writer: &mut (dyn Write + Send),
+) -> Result<u64> {
+    let stream = get_live_stream(room_id).await?;
+    let deadline = options.duration.map(|d| Instant::now() + d);
+
+    let mut headers = download_headers(DEFAULT_USER_AGENT, &build_live_url(room_id));
+    with_origin(&mut headers, "https://live.bilibili.com");
+
+    match stream.protocol {
+        LiveProtocol::Flv => {
+            let mut adts = AdtsWriter::new(writer);
+            record_stream(&stream.url, headers, deadline, &mut adts).await?;
+            Ok(adts.written())
+        }
+        LiveProtocol::Hls => record_hls(&stream.url, headers, deadline, writer).await,
+    }
+}
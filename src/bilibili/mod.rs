@@ -1,20 +1,45 @@
 use crate::Playlist;
 use crate::bilibili::core::{download_audio, extract_audio};
-use crate::core::{Extractor, Platform};
-use crate::error::Result;
+use crate::core::{Extractor, Locator, Platform, Quality, RecordOptions, Subtitle};
+use crate::error::{MusicFreeError, Result};
+use crate::subtitle::SubtitleLine;
 use async_trait::async_trait;
+use std::io::Write;
 
+pub mod bangumi;
+pub mod config;
 pub mod core;
+pub mod fav;
+pub mod flv;
+pub mod live;
+pub mod music;
+pub mod search;
+pub mod space;
+pub mod subtitle;
 pub mod types;
 pub mod utils;
+pub mod wbi;
 
 // Re-export commonly used types and functions
-pub use core::get_audio_info;
+pub use bangumi::{BangumiTarget, download_episode, extract_bangumi};
+pub use config::{BilibiliConfig, config, set_config};
+pub use core::{download_page, get_audio_info};
+pub use fav::{FavTarget, extract_fav, extract_favlist};
+pub use live::{LiveProtocol, LiveStreamUrl, extract_live, get_live_stream, record_live};
+pub use music::{MusicTarget, download_song, extract_music};
+pub use search::{SearchQuery, extract_search, search};
+pub use space::{SpaceOptions, SpaceOrder, SpaceTarget, extract_space};
+pub use subtitle::{download_subtitle, get_subtitles};
 pub use types::{
-    Audio, AudioInfo, Dash, Durl, Episode, EpisodeArc, EpisodePage, Owner, PlayData,
-    PlayUrlResponse, Section, UgcSession, ViewData, ViewResponse,
+    Audio, AudioInfo, Dash, Durl, Episode, EpisodeArc, EpisodePage, FavFolder, FavFolderListData,
+    FavFolderListResponse, FavInfo, FavMedia, FavResourceData, FavResourceResponse, Owner,
+    PlayData, PlayUrlResponse, Section, UgcSession, ViewData, ViewResponse,
+};
+pub use utils::{
+    is_bilibili_short_url, is_bilibili_url, parse_bangumi_target, parse_fav_target, parse_id,
+    parse_live_room, parse_music_target, parse_search_query, parse_space_target,
+    resolve_short_link,
 };
-pub use utils::{is_bilibili_short_url, is_bilibili_url, parse_id, resolve_short_link};
 
 /// Bilibili extractor implementing the Extractor trait
 #[derive(Debug, Clone)]
@@ -37,4 +62,40 @@ impl Extractor for BilibiliExtractor {
     async fn download(&self, url: &str) -> Result<Vec<u8>> {
         download_audio(url).await
     }
+
+    async fn download_audio(&self, audio: &crate::Audio) -> Result<Vec<u8>> {
+        match &audio.locator {
+            Some(Locator::Bilibili { bvid, cid }) => {
+                download_page(bvid, *cid, Quality::Super).await
+            }
+            _ => download_audio(&audio.download_url).await,
+        }
+    }
+
+    async fn record(
+        &self,
+        url: &str,
+        options: &RecordOptions,
+        writer: &mut (dyn Write + Send),
+    ) -> Result<u64> {
+        let room_id = parse_live_room(url).ok_or_else(|| {
+            MusicFreeError::InvalidUrl(format!("Not a Bilibili live room: {}", url))
+        })?;
+        record_live(room_id, options, writer).await
+    }
+
+    /// Subtitles cost a request per page, so only the requested page has
+    /// them from extraction, the others are looked up when asked for
+    async fn get_subtitles(&self, audio: &crate::Audio) -> Result<Vec<Subtitle>> {
+        match &audio.locator {
+            Some(Locator::Bilibili { bvid, cid }) if audio.subtitles.is_empty() => {
+                get_subtitles(bvid, *cid).await
+            }
+            _ => Ok(audio.subtitles.clone()),
+        }
+    }
+
+    async fn download_subtitle(&self, subtitle: &Subtitle) -> Result<Vec<SubtitleLine>> {
+        download_subtitle(&subtitle.url).await
+    }
 }
@@ -0,0 +1,168 @@
+//! Bilibili music (音频区) extraction and download.
+//!
+//! Songs (`au<sid>`) and menus/albums (`am<sid>`) are served by the
+//! `audio/music-service-c/web` APIs, which return real audio files together
+//! with artist and lyric metadata, unlike the video `playurl` path.
+
+use crate::bilibili::types::{MusicMenu, MusicMenuSongs, MusicResponse, MusicSong, MusicStream};
+use crate::bilibili::utils::{api_error, normalize_url};
+use crate::core::{Platform, Quality};
+use crate::download::{DEFAULT_USER_AGENT, download_binary_chunked, download_json};
+use crate::error::{MusicFreeError, Result};
+use crate::headers::{download_headers, with_origin};
+use crate::{Audio, AudioFormat, Playlist};
+use reqwest::header::HeaderMap;
+use serde::de::DeserializeOwned;
+
+const MUSIC_API: &str = "https://www.bilibili.com/audio/music-service-c/web";
+
+/// Page size used when listing the songs of a menu
+const MENU_PAGE_SIZE: u64 = 100;
+
+/// Menu type used for albums, other menus are user playlists
+const MENU_TYPE_ALBUM: u64 = 2;
+
+/// What a music URL points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicTarget {
+    /// A single song (`au<sid>`)
+    Song(u64),
+    /// A menu or album (`am<sid>`)
+    Menu(u64),
+}
+
+/// Build the web URL of a song
+pub fn build_song_url(sid: u64) -> String {
+    format!("https://www.bilibili.com/audio/au{sid}")
+}
+
+/// Build the web URL of a menu
+pub fn build_menu_url(sid: u64) -> String {
+    format!("https://www.bilibili.com/audio/am{sid}")
+}
+
+async fn music_json<T: DeserializeOwned>(api_url: &str, context: &str) -> Result<T> {
+    let resp: MusicResponse<T> = download_json(api_url, HeaderMap::new()).await?;
+    match resp.data {
+        Some(data) if resp.code == 0 => Ok(data),
+        _ => Err(api_error(context, resp.code, &resp.msg)),
+    }
+}
+
+fn song_audio(song: MusicSong) -> Audio {
+    let mut audio = Audio::new(
+        format!("au{}", song.id),
+        song.title,
+        build_song_url(song.id),
+        Platform::Bilibili,
+    )
+    .with_format(AudioFormat::M4A)
+    .with_duration(song.duration)
+    .with_cover(normalize_url(&song.cover));
+
+    // User uploads leave `author` empty, fall back to the uploader
+    let artist = if song.author.is_empty() {
+        song.uname
+    } else {
+        song.author
+    };
+    if !artist.is_empty() {
+        audio = audio.with_artist(artist);
+    }
+    if !song.lyric.is_empty() {
+        audio = audio.with_lyric_url(song.lyric);
+    }
+    audio
+}
+
+/// Extract a song or every song of a menu
+pub async fn extract_music(target: MusicTarget) -> Result<(Playlist, Option<usize>)> {
+    match target {
+        MusicTarget::Song(sid) => extract_song(sid).await,
+        MusicTarget::Menu(sid) => extract_menu(sid).await,
+    }
+}
+
+async fn extract_song(sid: u64) -> Result<(Playlist, Option<usize>)> {
+    let api_url = format!("{MUSIC_API}/song/info?sid={sid}");
+    let song: MusicSong = music_json(&api_url, &format!("Song au{sid}")).await?;
+
+    let title = song.title.clone();
+    let cover = normalize_url(&song.cover);
+    let audio = song_audio(song);
+
+    let playlist = Playlist {
+        id: Some(format!("au{sid}")),
+        download_url: Some(audio.download_url.clone()),
+        title: Some(title),
+        audios: vec![audio],
+        cover: Some(cover),
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    };
+
+    Ok((playlist, Some(0)))
+}
+
+async fn extract_menu(sid: u64) -> Result<(Playlist, Option<usize>)> {
+    let context = format!("Menu am{sid}");
+    let info_url = format!("{MUSIC_API}/menu/info?sid={sid}");
+    let menu: MusicMenu = music_json(&info_url, &context).await?;
+    let album = (menu.menu_type == MENU_TYPE_ALBUM).then(|| menu.title.clone());
+
+    let mut audios = vec![];
+    let mut pn = 1;
+
+    loop {
+        let api_url = format!("{MUSIC_API}/song/of-menu?sid={sid}&pn={pn}&ps={MENU_PAGE_SIZE}");
+        let page: MusicMenuSongs = music_json(&api_url, &context).await?;
+
+        for song in page.data.unwrap_or_default() {
+            let mut audio = song_audio(song);
+            if let Some(album) = &album {
+                audio = audio.with_album(album.clone());
+            }
+            audios.push(audio);
+        }
+
+        if page.cur_page >= page.page_count {
+            break;
+        }
+        pn += 1;
+    }
+
+    let playlist = Playlist {
+        id: Some(format!("am{sid}")),
+        download_url: Some(build_menu_url(sid)),
+        title: Some(menu.title),
+        audios,
+        cover: Some(normalize_url(&menu.cover)),
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    };
+
+    Ok((playlist, None))
+}
+
+/// Download a song from the music CDN
+///
+/// Quality: 0 = 128K, 1 = 192K, 2 = 320K. Lossless FLAC (3) is never
+/// requested because songs are declared as `AudioFormat::M4A`.
+/// The API falls back to the best quality the session is allowed to play.
+pub async fn download_song(sid: u64, quality: Quality) -> Result<Vec<u8>> {
+    let quality = match quality {
+        Quality::Low => 0,
+        Quality::Standard => 1,
+        Quality::High | Quality::Super => 2,
+    };
+    let api_url = format!("{MUSIC_API}/url?sid={sid}&privilege=2&quality={quality}");
+    let stream: MusicStream = music_json(&api_url, &format!("Song au{sid}")).await?;
+
+    let Some(media_url) = stream.cdns.and_then(|cdns| cdns.into_iter().next()) else {
+        return Err(MusicFreeError::AudioNotFound);
+    };
+
+    let mut headers = download_headers(DEFAULT_USER_AGENT, "https://www.bilibili.com/");
+    with_origin(&mut headers, "https://www.bilibili.com");
+    download_binary_chunked(&media_url, headers).await
+}
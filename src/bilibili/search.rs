@@ -0,0 +1,139 @@
+//! Bilibili video search.
+//!
+//! Queries the WBI-signed `x/web-interface/wbi/search/type` API and turns the
+//! results into a `Playlist`, so callers can search and then download.
+
+use crate::bilibili::core::init_cookies;
+use crate::bilibili::types::{SearchResponse, SearchVideo};
+use crate::bilibili::utils::{api_error, normalize_url};
+use crate::bilibili::wbi::{get_wbi_mixin_key, sign_wbi_query};
+use crate::core::Platform;
+use crate::download::download_json;
+use crate::error::Result;
+use crate::utils::parse_length;
+use crate::{Audio, AudioFormat, Playlist};
+use reqwest::header::{HeaderMap, HeaderValue, REFERER};
+
+/// Input prefix selecting a search instead of a URL
+pub const SEARCH_PREFIX: &str = "bilisearch:";
+
+/// Number of results returned when the input has no explicit limit
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Page size of the search API
+const SEARCH_PAGE_SIZE: usize = 20;
+
+/// A parsed `bilisearch:` input
+pub use crate::core::SearchQuery;
+
+/// Build the web URL of a search
+pub fn build_search_url(keyword: &str) -> String {
+    format!(
+        "https://search.bilibili.com/video?keyword={}",
+        urlencoding::encode(keyword)
+    )
+}
+
+/// Drop the highlight markup of search titles and decode HTML entities
+fn clean_title(title: &str) -> String {
+    let mut text = String::with_capacity(title.len());
+    let mut in_tag = false;
+    for c in title.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn search_audio(video: SearchVideo) -> Audio {
+    let bvid = video.bvid;
+    let mut audio = Audio::new(
+        bvid.clone(),
+        clean_title(&video.title),
+        format!("https://www.bilibili.com/video/{bvid}"),
+        Platform::Bilibili,
+    )
+    .with_format(AudioFormat::M4A)
+    .with_duration(parse_length(&video.duration).unwrap_or_default())
+    .with_cover(normalize_url(&video.pic));
+    if !video.author.is_empty() {
+        audio = audio.with_artist(video.author);
+    }
+    audio
+}
+
+/// Search videos by keyword, returning at most `limit` results
+pub async fn search(keyword: &str, limit: usize) -> Result<Playlist> {
+    // The search API answers -412 without the `buvid3` cookie
+    init_cookies().await?;
+    let mixin_key = get_wbi_mixin_key().await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(REFERER, HeaderValue::from_str(&build_search_url(keyword))?);
+
+    let mut audios = vec![];
+    let mut page = 1;
+
+    while audios.len() < limit {
+        let params = [
+            ("search_type", "video".to_string()),
+            ("keyword", keyword.to_string()),
+            ("page", page.to_string()),
+            ("page_size", SEARCH_PAGE_SIZE.to_string()),
+        ];
+        let api_url = format!(
+            "https://api.bilibili.com/x/web-interface/wbi/search/type?{}",
+            sign_wbi_query(&params, &mixin_key)
+        );
+        let resp: SearchResponse = download_json(&api_url, headers.clone()).await?;
+        let Some(data) = resp.data.filter(|_| resp.code == 0) else {
+            return Err(api_error(
+                &format!("Search {keyword:?}"),
+                resp.code,
+                &resp.message,
+            ));
+        };
+
+        let results = data.result.unwrap_or_default();
+        if results.is_empty() {
+            break;
+        }
+        audios.extend(
+            results
+                .into_iter()
+                .filter(|v| v.result_type == "video" && !v.bvid.is_empty())
+                .map(search_audio),
+        );
+
+        if page >= data.num_pages {
+            break;
+        }
+        page += 1;
+    }
+    audios.truncate(limit);
+
+    let cover = audios.first().and_then(|a| a.cover.clone());
+    Ok(Playlist {
+        id: Some(format!("{SEARCH_PREFIX}{keyword}")),
+        download_url: Some(build_search_url(keyword)),
+        title: Some(keyword.to_string()),
+        audios,
+        cover,
+        playlists: Vec::new(),
+        platform: Platform::Bilibili,
+    })
+}
+
+/// Extract the results of a `bilisearch:` input
+pub async fn extract_search(query: &SearchQuery) -> Result<(Playlist, Option<usize>)> {
+    let playlist = search(&query.query, query.limit).await?;
+    Ok((playlist, None))
+}
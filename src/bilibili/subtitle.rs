@@ -0,0 +1,54 @@
+//! Bilibili CC subtitles.
+//!
+//! The subtitle list of a page comes from `x/player/v2`, each track is a
+//! JSON file of `{from, to, content}` lines.
+
+use crate::bilibili::types::{PlayerInfoResponse, SubtitleJson};
+use crate::bilibili::utils::{api_error, normalize_url};
+use crate::core::Subtitle;
+use crate::download::download_json;
+use crate::error::Result;
+use crate::subtitle::SubtitleLine;
+use reqwest::header::HeaderMap;
+
+/// List the subtitle tracks of a video page
+pub async fn get_subtitles(bvid: &str, cid: u64) -> Result<Vec<Subtitle>> {
+    let api_url = format!("https://api.bilibili.com/x/player/v2?bvid={bvid}&cid={cid}");
+    let resp: PlayerInfoResponse = download_json(&api_url, HeaderMap::new()).await?;
+    let Some(data) = resp.data.filter(|_| resp.code == 0) else {
+        return Err(api_error(
+            &format!("Subtitles of {bvid}"),
+            resp.code,
+            &resp.message,
+        ));
+    };
+
+    let subtitles = data
+        .subtitle
+        .subtitles
+        .into_iter()
+        .filter(|s| !s.subtitle_url.is_empty())
+        .map(|s| Subtitle {
+            lang: s.lan,
+            name: s.lan_doc,
+            url: normalize_url(&s.subtitle_url),
+            translatable: false,
+        })
+        .collect();
+    Ok(subtitles)
+}
+
+/// Download a subtitle track
+pub async fn download_subtitle(url: &str) -> Result<Vec<SubtitleLine>> {
+    let json: SubtitleJson = download_json(url, HeaderMap::new()).await?;
+    let lines = json
+        .body
+        .into_iter()
+        .map(|line| SubtitleLine {
+            start: line.from,
+            end: line.to,
+            text: line.content,
+        })
+        .collect();
+    Ok(lines)
+}
@@ -112,3 +112,501 @@ pub struct AudioInfo {
     pub cover: String,
     pub duration: u64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavResourceResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<FavResourceData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavResourceData {
+    pub info: FavInfo,
+    #[serde(default)]
+    pub medias: Option<Vec<FavMedia>>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavInfo {
+    pub id: u64,
+    pub title: String,
+    pub cover: String,
+    pub media_count: u64,
+    pub upper: FavUpper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavUpper {
+    pub mid: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavFolderListResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<FavFolderListData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavFolderListData {
+    pub count: u64,
+    #[serde(default)]
+    pub list: Option<Vec<FavFolder>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavFolder {
+    pub id: u64,
+    pub title: String,
+    pub media_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavMedia {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub media_type: u64,
+    pub title: String,
+    pub cover: String,
+    pub duration: u64,
+    pub page: u64,
+    pub attr: u64,
+    pub bvid: String,
+    pub upper: FavUpper,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ugc: Option<FavUgc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavUgc {
+    pub first_cid: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArcSearchResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<SpaceArcSearchData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArcSearchData {
+    pub list: SpaceArcList,
+    pub page: SpaceArcPage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArcList {
+    #[serde(default)]
+    pub vlist: Option<Vec<SpaceArc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArc {
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub pic: String,
+    /// Duration formatted as `mm:ss` or `h:mm:ss`
+    pub length: String,
+    pub created: u64,
+    pub author: String,
+    pub mid: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArcPage {
+    pub pn: u64,
+    pub ps: u64,
+    pub count: u64,
+}
+
+/// Video entry shared by the series and collection (season) list APIs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceArchive {
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub pic: String,
+    pub duration: u64,
+    pub pubdate: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesArchivesResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<SeriesArchivesData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesArchivesData {
+    #[serde(default)]
+    pub archives: Option<Vec<SpaceArchive>>,
+    pub page: SeriesPage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesPage {
+    pub num: u64,
+    pub size: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesInfoResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<SeriesInfoData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesInfoData {
+    pub meta: SeriesMeta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesMeta {
+    pub series_id: u64,
+    pub mid: u64,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionArchivesResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<CollectionArchivesData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionArchivesData {
+    #[serde(default)]
+    pub archives: Option<Vec<SpaceArchive>>,
+    pub meta: CollectionMeta,
+    pub page: CollectionPage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionMeta {
+    pub season_id: u64,
+    pub name: String,
+    pub cover: String,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionPage {
+    pub page_num: u64,
+    pub page_size: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicResponse<T> {
+    pub code: i64,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicSong {
+    pub id: u64,
+    pub uid: u64,
+    pub uname: String,
+    /// Performing artist(s), may be empty for user uploads
+    #[serde(default)]
+    pub author: String,
+    pub title: String,
+    pub cover: String,
+    /// Lyric (LRC) URL, empty when the song has no lyrics
+    #[serde(default)]
+    pub lyric: String,
+    pub duration: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicStream {
+    pub sid: u64,
+    /// Granted quality (`-1` for trial clips)
+    #[serde(rename = "type")]
+    pub quality: i64,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub cdns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicMenu {
+    #[serde(rename = "menuId")]
+    pub menu_id: u64,
+    pub uid: u64,
+    pub uname: String,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub intro: String,
+    #[serde(rename = "type")]
+    pub menu_type: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicMenuSongs {
+    #[serde(rename = "curPage")]
+    pub cur_page: u64,
+    #[serde(rename = "pageCount")]
+    pub page_count: u64,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    #[serde(default)]
+    pub data: Option<Vec<MusicSong>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgcSeasonResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<PgcSeason>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgcSeason {
+    pub season_id: u64,
+    pub season_title: String,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub episodes: Vec<PgcEpisode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgcEpisode {
+    /// Episode id (`ep<id>`)
+    pub id: u64,
+    pub aid: u64,
+    pub cid: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bvid: Option<String>,
+    /// Episode number, e.g. "1"
+    pub title: String,
+    #[serde(default)]
+    pub long_title: String,
+    pub cover: String,
+    /// Duration in milliseconds
+    pub duration: u64,
+    #[serde(default)]
+    pub badge: String,
+    /// 2 = free, 13 = premium members only
+    pub status: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgcPlayUrlResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<PgcPlayResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PgcPlayResult {
+    #[serde(flatten)]
+    pub play_data: PlayData,
+    /// 1 when only a trial clip is returned (premium episode)
+    #[serde(default)]
+    pub is_preview: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveRoomInfoResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<LiveRoomInfoData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveRoomInfoData {
+    pub room_info: LiveRoomInfo,
+    pub anchor_info: LiveAnchorInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveRoomInfo {
+    pub room_id: u64,
+    pub short_id: u64,
+    pub uid: u64,
+    pub title: String,
+    pub cover: String,
+    /// 0 = offline, 1 = live, 2 = replaying recordings
+    pub live_status: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveAnchorInfo {
+    pub base_info: LiveAnchorBaseInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveAnchorBaseInfo {
+    pub uname: String,
+    pub face: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePlayInfoResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<LivePlayInfoData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePlayInfoData {
+    pub room_id: u64,
+    pub live_status: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playurl_info: Option<LivePlayurlInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePlayurlInfo {
+    pub playurl: LivePlayurl,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivePlayurl {
+    pub stream: Vec<LiveStream>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveStream {
+    /// `http_stream` (FLV) or `http_hls` (HLS)
+    pub protocol_name: String,
+    pub format: Vec<LiveFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveFormat {
+    /// `flv`, `ts` or `fmp4`
+    pub format_name: String,
+    pub codec: Vec<LiveCodec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveCodec {
+    pub codec_name: String,
+    pub base_url: String,
+    pub url_info: Vec<LiveUrlInfo>,
+    pub current_qn: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveUrlInfo {
+    pub host: String,
+    pub extra: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfoResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<PlayerInfoData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfoData {
+    #[serde(default)]
+    pub subtitle: PlayerSubtitleInfo,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerSubtitleInfo {
+    #[serde(default)]
+    pub subtitles: Vec<PlayerSubtitle>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSubtitle {
+    pub lan: String,
+    #[serde(default)]
+    pub lan_doc: String,
+    pub subtitle_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleJson {
+    #[serde(default)]
+    pub body: Vec<SubtitleJsonLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleJsonLine {
+    pub from: f64,
+    pub to: f64,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<SearchData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchData {
+    #[serde(rename = "numPages", default)]
+    pub num_pages: u64,
+    /// Missing when nothing matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Vec<SearchVideo>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchVideo {
+    #[serde(rename = "type", default)]
+    pub result_type: String,
+    #[serde(default)]
+    pub bvid: String,
+    /// Contains `<em class="keyword">` highlight markup
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub pic: String,
+    /// `mm:ss`
+    #[serde(default)]
+    pub duration: String,
+}
@@ -1,3 +1,8 @@
+use crate::bilibili::bangumi::BangumiTarget;
+use crate::bilibili::fav::FavTarget;
+use crate::bilibili::music::MusicTarget;
+use crate::bilibili::search::{DEFAULT_SEARCH_LIMIT, SEARCH_PREFIX, SearchQuery};
+use crate::bilibili::space::SpaceTarget;
 use crate::download::get_http_client;
 use crate::error::{MusicFreeError, Result};
 use abv::av2bv;
@@ -39,77 +44,89 @@ pub async fn resolve_short_link(short_url: &str) -> Result<String> {
     Ok(final_url)
 }
 
-/// Parse BV ID from Bilibili URL
+/// Convert an `av<aid>` id (either case) into a BV ID
+fn parse_av(id: &str) -> Option<String> {
+    let aid = id.strip_prefix("av").or_else(|| id.strip_prefix("AV"))?;
+    aid.parse::<u64>().ok().and_then(|aid| av2bv(aid).ok())
+}
+
+/// Check if string is a bare BV ID
+fn is_bvid(id: &str) -> bool {
+    id.starts_with("BV") && id.len() == 12
+}
+
+/// Extract BV ID from a parsed Bilibili URL (path or query)
+///
+/// Covers `/video/BV...`, `/video/av...` on www/m.bilibili.com, and list or
+/// watch-later pages that carry the video as `?bvid=`, `?aid=` or `?oid=`.
+fn parse_id_from_url(parsed_url: &Url) -> Option<String> {
+    let path = parsed_url.path();
+
+    // BV ID in path
+    if let Some(pos) = path.find("BV") {
+        let bvid: String = path[pos..].chars().take(12).collect();
+        if bvid.len() == 12 {
+            return Some(bvid);
+        }
+    }
+
+    // AV ID in path
+    if let Some(bvid) = path.split('/').find_map(parse_av) {
+        return Some(bvid);
+    }
+
+    // Query parameters, `oid` is only the video's aid on list pages
+    let is_list = path.starts_with("/list/");
+    parsed_url
+        .query_pairs()
+        .find_map(|(k, v)| match k.as_ref() {
+            "bvid" if is_bvid(&v) => Some(v.into_owned()),
+            "aid" | "avid" => v.parse::<u64>().ok().and_then(|aid| av2bv(aid).ok()),
+            "oid" if is_list => v.parse::<u64>().ok().and_then(|aid| av2bv(aid).ok()),
+            _ => None,
+        })
+}
+
+/// Parse BV ID from Bilibili URL, BV ID or av ID
 pub async fn parse_id(url: &str) -> Result<String> {
     // Direct BV ID
-    if url.starts_with("BV") && url.len() == 12 {
+    if is_bvid(url) {
         return Ok(url[..12].to_string());
     }
 
+    // Direct AV ID
+    if let Some(bvid) = parse_av(url) {
+        return Ok(bvid);
+    }
+
     // Parse URL to extract path
     if let Ok(parsed_url) = Url::parse(url) {
-        let path = parsed_url.path();
+        // BV/av short links (b23.tv/BV..., b23.tv/av...) and regular URLs
+        if let Some(bvid) = parse_id_from_url(&parsed_url) {
+            return Ok(bvid);
+        }
 
-        // Handle short URLs (b23.tv)
+        // Handle general short codes (b23.tv/<7 alphanumeric chars>)
         if parsed_url.domain() == Some("b23.tv") {
-            let path_segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
-
-            for segment in path_segments {
-                if segment.is_empty() {
-                    continue;
-                }
-
-                // Check if it's a BV short link
-                if segment.starts_with("BV") && segment.len() == 12 {
-                    return Ok(segment.to_string());
-                }
-
-                // Check if it's an AV short link
-                if segment.starts_with("av")
-                    && let Ok(av_id) = segment[2..].parse::<u64>()
-                    && let Ok(bvid) = av2bv(av_id)
-                {
-                    return Ok(bvid);
-                }
-
-                // Check if it's a general short code (7 alphanumeric chars)
-                if segment.len() == 7 && segment.chars().all(|c| c.is_alphanumeric()) {
-                    // Resolve the short link by making HTTP request
-                    let resolved_url = resolve_short_link(url).await?;
-                    // Use a non-recursive approach: try to extract from the resolved URL directly
-                    let resolved_parsed = Url::parse(&resolved_url).map_err(|e| {
-                        MusicFreeError::InvalidUrl(format!("Failed to parse resolved URL: {}", e))
-                    })?;
-                    let resolved_path = resolved_parsed.path();
-
-                    if let Some(pos) = resolved_path.find("BV") {
-                        let bvid: String = resolved_path[pos..].chars().take(12).collect();
-                        if bvid.len() == 12 {
-                            return Ok(bvid);
-                        }
-                    }
-
-                    // If still can't extract, try using query parameters
-                    if let Some(query) = resolved_parsed.query()
-                        && let Some(bv_match) = query.split('&').find(|p| p.starts_with("bvid="))
-                        && let Some(bvid) = bv_match.split('=').nth(1)
-                    {
-                        return Ok(bvid.to_string());
-                    }
-
-                    return Err(MusicFreeError::InvalidUrl(format!(
+            let is_short_code = parsed_url
+                .path()
+                .split('/')
+                .any(|segment| segment.len() == 7 && segment.chars().all(|c| c.is_alphanumeric()));
+
+            if is_short_code {
+                // Resolve the short link by making HTTP request
+                let resolved_url = resolve_short_link(url).await?;
+                // Use a non-recursive approach: extract from the resolved URL directly
+                let resolved_parsed = Url::parse(&resolved_url).map_err(|e| {
+                    MusicFreeError::InvalidUrl(format!("Failed to parse resolved URL: {}", e))
+                })?;
+
+                return parse_id_from_url(&resolved_parsed).ok_or_else(|| {
+                    MusicFreeError::InvalidUrl(format!(
                         "Cannot extract BV ID from resolved URL: {}",
                         resolved_url
-                    )));
-                }
-            }
-        }
-
-        // Handle regular bilibili.com URLs
-        if let Some(pos) = path.find("BV") {
-            let bvid: String = path[pos..].chars().take(12).collect();
-            if bvid.len() == 12 {
-                return Ok(bvid);
+                    ))
+                });
             }
         }
     }
@@ -122,8 +139,18 @@ pub async fn parse_id(url: &str) -> Result<String> {
 
 /// Check if URL is a Bilibili link
 pub fn is_bilibili_url(url: &str) -> bool {
-    // Direct BV ID
-    if url.starts_with("BV") && url.len() == 12 {
+    // Direct BV or AV ID
+    if is_bvid(url) || parse_av(url).is_some() {
+        return true;
+    }
+
+    // Direct music song/menu ID
+    if parse_music_id(url).is_some() {
+        return true;
+    }
+
+    // Search input
+    if parse_search_query(url).is_some() {
         return true;
     }
 
@@ -131,11 +158,8 @@ pub fn is_bilibili_url(url: &str) -> bool {
     if let Ok(parsed_url) = Url::parse(url) {
         match parsed_url.domain() {
             Some(domain) => {
-                // Check for official bilibili domains
-                domain == "bilibili.com"
-                    || domain == "www.bilibili.com"
-                    || domain == "b23.tv"
-                    || domain == "m.bilibili.com"
+                // Check for official bilibili domains (www, m, space, ...)
+                domain == "bilibili.com" || domain.ends_with(".bilibili.com") || domain == "b23.tv"
             }
             None => false,
         }
@@ -144,6 +168,192 @@ pub fn is_bilibili_url(url: &str) -> bool {
     }
 }
 
+/// Parse favourites folder (media list) target from Bilibili URL
+///
+/// Supports:
+/// - `space.bilibili.com/<mid>/favlist?fid=<media_id>`
+/// - `space.bilibili.com/<mid>/favlist` (the default folder)
+/// - `www.bilibili.com/medialist/detail/ml<media_id>`
+/// - `www.bilibili.com/medialist/play/ml<media_id>`
+/// - `www.bilibili.com/list/ml<media_id>`
+pub fn parse_fav_target(url: &str) -> Option<FavTarget> {
+    let parsed_url = Url::parse(url).ok()?;
+    let domain = parsed_url.domain()?;
+    let path = parsed_url.path();
+
+    if domain == "space.bilibili.com" && path.contains("/favlist") {
+        return match parsed_url.query_pairs().find(|(k, _)| k == "fid") {
+            Some((_, fid)) => fid.parse().ok().map(FavTarget::Folder),
+            None => path
+                .split('/')
+                .find(|s| !s.is_empty())
+                .and_then(|mid| mid.parse().ok())
+                .map(|mid| FavTarget::Default { mid }),
+        };
+    }
+
+    if path.starts_with("/medialist/") || path.starts_with("/list/") {
+        return path
+            .split('/')
+            .find_map(|segment| segment.strip_prefix("ml"))
+            .and_then(|id| id.parse().ok())
+            .map(FavTarget::Folder);
+    }
+
+    None
+}
+
+/// Parse uploader space, series or collection target from Bilibili URL
+///
+/// Supports:
+/// - `space.bilibili.com/<mid>`, `.../<mid>/video`, `.../<mid>/upload/video`
+/// - `space.bilibili.com/<mid>/channel/seriesdetail?sid=<sid>`
+/// - `space.bilibili.com/<mid>/channel/collectiondetail?sid=<sid>`
+/// - `space.bilibili.com/<mid>/lists/<sid>?type=series|season`
+pub fn parse_space_target(url: &str) -> Option<SpaceTarget> {
+    let parsed_url = Url::parse(url).ok()?;
+    if parsed_url.domain()? != "space.bilibili.com" {
+        return None;
+    }
+
+    let segments: Vec<&str> = parsed_url
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let mid = segments.first()?.parse::<u64>().ok()?;
+    let query = |key: &str| {
+        parsed_url
+            .query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+    let sid = || query("sid").and_then(|v| v.parse::<u64>().ok());
+
+    match segments[1..] {
+        [] | ["video"] | ["upload"] | ["upload", "video"] => Some(SpaceTarget::Videos { mid }),
+        ["channel", "seriesdetail"] => Some(SpaceTarget::Series { mid, sid: sid()? }),
+        ["channel", "collectiondetail"] => Some(SpaceTarget::Collection { mid, sid: sid()? }),
+        ["lists", sid] => {
+            let sid = sid.parse::<u64>().ok()?;
+            if query("type").as_deref() == Some("series") {
+                Some(SpaceTarget::Series { mid, sid })
+            } else {
+                Some(SpaceTarget::Collection { mid, sid })
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parse `au<sid>` / `am<sid>` music ids
+fn parse_music_id(id: &str) -> Option<MusicTarget> {
+    if let Some(sid) = id.strip_prefix("au") {
+        return sid.parse().ok().map(MusicTarget::Song);
+    }
+    if let Some(sid) = id.strip_prefix("am") {
+        return sid.parse().ok().map(MusicTarget::Menu);
+    }
+    None
+}
+
+/// Parse music song or menu target from Bilibili URL or bare id
+///
+/// Supports:
+/// - `au<sid>` / `am<sid>`
+/// - `www.bilibili.com/audio/au<sid>`, `m.bilibili.com/audio/au<sid>`
+/// - `www.bilibili.com/audio/am<sid>`
+pub fn parse_music_target(url: &str) -> Option<MusicTarget> {
+    if let Some(target) = parse_music_id(url) {
+        return Some(target);
+    }
+
+    let parsed_url = Url::parse(url).ok()?;
+    let domain = parsed_url.domain()?;
+    if domain != "bilibili.com" && !domain.ends_with(".bilibili.com") {
+        return None;
+    }
+
+    let mut segments = parsed_url.path().split('/').filter(|s| !s.is_empty());
+    if segments.next()? != "audio" {
+        return None;
+    }
+    segments.find_map(parse_music_id)
+}
+
+/// Parse bangumi season or episode target from Bilibili URL
+///
+/// Supports:
+/// - `www.bilibili.com/bangumi/play/ss<season_id>`
+/// - `www.bilibili.com/bangumi/play/ep<ep_id>`
+/// - `m.bilibili.com/bangumi/play/ep<ep_id>`
+pub fn parse_bangumi_target(url: &str) -> Option<BangumiTarget> {
+    let parsed_url = Url::parse(url).ok()?;
+    let domain = parsed_url.domain()?;
+    if domain != "bilibili.com" && !domain.ends_with(".bilibili.com") {
+        return None;
+    }
+
+    let mut segments = parsed_url.path().split('/').filter(|s| !s.is_empty());
+    if segments.next()? != "bangumi" || segments.next()? != "play" {
+        return None;
+    }
+
+    let id = segments.next()?;
+    if let Some(ep_id) = id.strip_prefix("ep") {
+        return ep_id.parse().ok().map(BangumiTarget::Episode);
+    }
+    if let Some(season_id) = id.strip_prefix("ss") {
+        return season_id.parse().ok().map(BangumiTarget::Season);
+    }
+    None
+}
+
+/// Parse live room ID from Bilibili live URL
+///
+/// Supports:
+/// - `live.bilibili.com/<room_id>`
+/// - `live.bilibili.com/h5/<room_id>`, `live.bilibili.com/blanc/<room_id>`
+pub fn parse_live_room(url: &str) -> Option<u64> {
+    let parsed_url = Url::parse(url).ok()?;
+    if parsed_url.domain()? != "live.bilibili.com" {
+        return None;
+    }
+
+    parsed_url
+        .path()
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .find_map(|segment| segment.parse().ok())
+}
+
+/// Parse a search input: `bilisearch:<keyword>` or `bilisearch:<limit>:<keyword>`
+pub fn parse_search_query(input: &str) -> Option<SearchQuery> {
+    crate::utils::parse_search_query(input, SEARCH_PREFIX, DEFAULT_SEARCH_LIMIT)
+}
+
+/// Map a non-zero Bilibili API `code` into an error
+pub(crate) fn api_error(context: &str, code: i64, message: &str) -> MusicFreeError {
+    match code {
+        // Risk control / request frequency limits
+        -352 | -412 | -509 | -799 => {
+            MusicFreeError::RateLimited(format!("{context} ({code}): {message}"))
+        }
+        _ => MusicFreeError::ExtractionFailed(format!("{context} ({code}): {message}")),
+    }
+}
+
+/// Bilibili APIs sometimes return protocol-relative or plain http URLs
+pub(crate) fn normalize_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("//") {
+        format!("https://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("https://{rest}")
+    } else {
+        url.to_string()
+    }
+}
+
 /// Check if URL is a Bilibili short URL
 pub fn is_bilibili_short_url(url: &str) -> bool {
     if let Ok(parsed_url) = Url::parse(url)
@@ -170,3 +380,201 @@ pub fn is_bilibili_short_url(url: &str) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// av170001 and its BV ID
+    const AID: &str = "av170001";
+    const BVID: &str = "BV17x411w7KC";
+
+    #[tokio::test]
+    async fn parses_bv_and_av_ids() {
+        let cases = [
+            BVID,
+            AID,
+            "AV170001",
+            "https://www.bilibili.com/video/BV17x411w7KC/?spm_id_from=333.788",
+            "https://www.bilibili.com/video/av170001",
+            "https://m.bilibili.com/video/av170001?p=2",
+            "https://b23.tv/BV17x411w7KC",
+            "https://b23.tv/av170001",
+            "https://www.bilibili.com/list/watchlater?bvid=BV17x411w7KC",
+            "https://www.bilibili.com/medialist/play/watchlater?aid=170001",
+            "https://www.bilibili.com/list/ml1052622027?oid=170001",
+        ];
+        for url in cases {
+            assert_eq!(parse_id(url).await.ok().as_deref(), Some(BVID), "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_inputs_without_a_video() {
+        let cases = [
+            "av",
+            "avabc",
+            "BV17x4",
+            "https://www.bilibili.com/video/",
+            "https://www.bilibili.com/video/BV17x4",
+            "https://www.bilibili.com/list/watchlater?bvid=BV17x4",
+            // `oid` is only the aid of the video on list pages
+            "https://www.bilibili.com/read/cv123?oid=170001",
+        ];
+        for url in cases {
+            assert!(parse_id(url).await.is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn parses_fav_targets() {
+        let cases = [
+            "https://space.bilibili.com/2/favlist?fid=1052622027&ftype=create",
+            "https://www.bilibili.com/medialist/detail/ml1052622027",
+            "https://www.bilibili.com/medialist/play/ml1052622027?spm_id_from=333.999",
+            "https://www.bilibili.com/list/ml1052622027?oid=170001",
+        ];
+        for url in cases {
+            assert_eq!(
+                parse_fav_target(url),
+                Some(FavTarget::Folder(1052622027)),
+                "{url}"
+            );
+        }
+
+        let default = Some(FavTarget::Default { mid: 2 });
+        assert_eq!(
+            parse_fav_target("https://space.bilibili.com/2/favlist"),
+            default
+        );
+        assert_eq!(
+            parse_fav_target("https://space.bilibili.com/2/favlist?spm_id_from=333.1007"),
+            default
+        );
+
+        let rejected = [
+            "https://space.bilibili.com/2/favlist?fid=abc",
+            "https://space.bilibili.com/abc/favlist",
+            "https://space.bilibili.com/2/video",
+            "https://www.bilibili.com/list/watchlater?bvid=BV17x411w7KC",
+            "https://www.bilibili.com/video/BV17x411w7KC",
+            "ml1052622027",
+        ];
+        for url in rejected {
+            assert_eq!(parse_fav_target(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn parses_space_targets() {
+        let videos = Some(SpaceTarget::Videos { mid: 2 });
+        let cases = [
+            ("https://space.bilibili.com/2", videos),
+            ("https://space.bilibili.com/2/?spm_id_from=333.1007", videos),
+            ("https://space.bilibili.com/2/video", videos),
+            ("https://space.bilibili.com/2/upload/video", videos),
+            (
+                "https://space.bilibili.com/2/channel/seriesdetail?sid=5",
+                Some(SpaceTarget::Series { mid: 2, sid: 5 }),
+            ),
+            (
+                "https://space.bilibili.com/2/channel/collectiondetail?sid=6",
+                Some(SpaceTarget::Collection { mid: 2, sid: 6 }),
+            ),
+            (
+                "https://space.bilibili.com/2/lists/5?type=series",
+                Some(SpaceTarget::Series { mid: 2, sid: 5 }),
+            ),
+            (
+                "https://space.bilibili.com/2/lists/6?type=season",
+                Some(SpaceTarget::Collection { mid: 2, sid: 6 }),
+            ),
+            // Favourites are routed to `parse_fav_target`
+            ("https://space.bilibili.com/2/favlist?fid=1052622027", None),
+            ("https://space.bilibili.com/2/channel/seriesdetail", None),
+            ("https://space.bilibili.com/2/lists/abc?type=series", None),
+            ("https://space.bilibili.com/2/dynamic", None),
+            ("https://space.bilibili.com/abc", None),
+            ("https://www.bilibili.com/2", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_space_target(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn parses_music_targets() {
+        let cases = [
+            ("au12345", Some(MusicTarget::Song(12345))),
+            ("am34567", Some(MusicTarget::Menu(34567))),
+            (
+                "https://www.bilibili.com/audio/au12345",
+                Some(MusicTarget::Song(12345)),
+            ),
+            (
+                "https://m.bilibili.com/audio/au12345?type=3",
+                Some(MusicTarget::Song(12345)),
+            ),
+            (
+                "https://www.bilibili.com/audio/am34567",
+                Some(MusicTarget::Menu(34567)),
+            ),
+            ("au", None),
+            ("auxyz", None),
+            (AID, None),
+            ("https://www.bilibili.com/audio/", None),
+            ("https://www.bilibili.com/video/au12345", None),
+            ("https://example.com/audio/au12345", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_music_target(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn parses_bangumi_targets() {
+        let cases = [
+            (
+                "https://www.bilibili.com/bangumi/play/ss28747",
+                Some(BangumiTarget::Season(28747)),
+            ),
+            (
+                "https://www.bilibili.com/bangumi/play/ep285845?from=search",
+                Some(BangumiTarget::Episode(285845)),
+            ),
+            (
+                "https://m.bilibili.com/bangumi/play/ep285845",
+                Some(BangumiTarget::Episode(285845)),
+            ),
+            ("https://www.bilibili.com/bangumi/media/md28229233", None),
+            ("https://www.bilibili.com/bangumi/play/", None),
+            ("https://www.bilibili.com/bangumi/play/xx1", None),
+            ("https://www.bilibili.com/bangumi/play/epabc", None),
+            ("https://example.com/bangumi/play/ss28747", None),
+            ("ss28747", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_bangumi_target(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn parses_live_rooms() {
+        let cases = [
+            ("https://live.bilibili.com/22637261", Some(22637261)),
+            (
+                "https://live.bilibili.com/22637261?spm_id_from=333.1007",
+                Some(22637261),
+            ),
+            ("https://live.bilibili.com/h5/22637261", Some(22637261)),
+            ("https://live.bilibili.com/blanc/22637261", Some(22637261)),
+            ("https://live.bilibili.com/", None),
+            ("https://live.bilibili.com/p/eden/area-tags", None),
+            ("https://www.bilibili.com/22637261", None),
+            ("22637261", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_live_room(url), expected, "{url}");
+        }
+    }
+}
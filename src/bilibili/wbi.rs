@@ -0,0 +1,140 @@
+//! WBI request signing.
+//!
+//! Newer Bilibili web APIs (`x/space/wbi/*`, `x/web-interface/wbi/*`) reject
+//! requests without a `w_rid` signature. The signing key is derived from the
+//! `wbi_img` URLs returned by the `nav` API and rotates daily.
+
+use crate::download::download_json;
+use crate::error::{MusicFreeError, Result};
+use crate::utils::get_md5;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Permutation applied to `img_key + sub_key` to build the mixin key
+const MIXIN_KEY_ENC_TAB: [usize; 64] = [
+    46, 47, 18, 2, 53, 8, 23, 32, 15, 50, 10, 31, 58, 3, 45, 35, 27, 43, 5, 49, 33, 9, 42, 19, 29,
+    28, 14, 39, 12, 38, 41, 13, 37, 48, 7, 16, 24, 55, 40, 61, 26, 17, 0, 1, 60, 51, 30, 4, 22, 25,
+    54, 21, 56, 59, 6, 63, 57, 62, 11, 36, 20, 34, 44, 52,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NavResponse {
+    data: NavData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NavData {
+    wbi_img: WbiImg,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WbiImg {
+    img_url: String,
+    sub_url: String,
+}
+
+/// Take the file stem of a `wbi_img` URL, e.g. `.../7cd084941338484aae1ad9425b84077c.png`
+fn key_from_url(url: &str) -> Result<String> {
+    url.rsplit('/')
+        .next()
+        .and_then(|name| name.split('.').next())
+        .filter(|key| !key.is_empty())
+        .map(|key| key.to_string())
+        .ok_or_else(|| MusicFreeError::ParseError(format!("Invalid WBI key URL: {url}")))
+}
+
+/// Shuffle `img_key + sub_key` into the 32 character mixin key
+fn get_mixin_key(raw_key: &str) -> String {
+    let raw = raw_key.as_bytes();
+    MIXIN_KEY_ENC_TAB
+        .iter()
+        .filter_map(|&i| raw.get(i).map(|&b| b as char))
+        .take(32)
+        .collect()
+}
+
+/// Fetch the current WBI mixin key from the `nav` API
+///
+/// The API answers with `code = -101` for anonymous visitors but still
+/// includes `wbi_img`, so the response code is not checked.
+pub async fn get_wbi_mixin_key() -> Result<String> {
+    let nav: NavResponse = download_json(
+        "https://api.bilibili.com/x/web-interface/nav",
+        HeaderMap::new(),
+    )
+    .await?;
+    let img_key = key_from_url(&nav.data.wbi_img.img_url)?;
+    let sub_key = key_from_url(&nav.data.wbi_img.sub_url)?;
+    Ok(get_mixin_key(&(img_key + &sub_key)))
+}
+
+/// Build a signed query string (`...&wts=...&w_rid=...`) for a WBI API
+pub fn sign_wbi_query(params: &[(&str, String)], mixin_key: &str) -> String {
+    sign_wbi_query_at(params, mixin_key, chrono::Utc::now().timestamp())
+}
+
+/// `sign_wbi_query` with the timestamp `wts`
+fn sign_wbi_query_at(params: &[(&str, String)], mixin_key: &str, wts: i64) -> String {
+    let mut params: Vec<(String, String)> = params
+        .iter()
+        .map(|(k, v)| {
+            // Characters filtered out by the web client before signing
+            let v: String = v.chars().filter(|c| !"!'()*".contains(*c)).collect();
+            (k.to_string(), v)
+        })
+        .collect();
+    params.push(("wts".to_string(), wts.to_string()));
+    params.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", urlencoding::encode(k), urlencoding::encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let w_rid = get_md5(&format!("{query}{mixin_key}"));
+
+    format!("{query}&w_rid={w_rid}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keys and signature of the example in bilibili-API-collect
+    const IMG_KEY: &str = "7cd084941338484aae1ad9425b84077c";
+    const SUB_KEY: &str = "4932caff0ff746eab6f01bf08b70ac45";
+    const MIXIN_KEY: &str = "ea1db124af3c7062474693fa704f4ff8";
+    const WTS: i64 = 1702204169;
+
+    #[test]
+    fn derives_the_mixin_key() {
+        assert_eq!(
+            key_from_url(&format!("https://i0.hdslb.com/bfs/wbi/{IMG_KEY}.png")).unwrap(),
+            IMG_KEY
+        );
+        assert!(key_from_url("https://i0.hdslb.com/bfs/wbi/").is_err());
+        assert_eq!(get_mixin_key(&format!("{IMG_KEY}{SUB_KEY}")), MIXIN_KEY);
+    }
+
+    #[test]
+    fn signs_sorted_queries() {
+        let params = [
+            ("foo", "114".to_string()),
+            ("bar", "514".to_string()),
+            ("zab", "1919810".to_string()),
+        ];
+        assert_eq!(
+            sign_wbi_query_at(&params, MIXIN_KEY, WTS),
+            "bar=514&foo=114&wts=1702204169&zab=1919810&w_rid=8f6f2b5b3d485fe1886cec6a0be8c5d4"
+        );
+    }
+
+    #[test]
+    fn filters_and_encodes_values() {
+        let params = [("keyword", "中文 (test)!".to_string())];
+        assert_eq!(
+            sign_wbi_query_at(&params, MIXIN_KEY, WTS),
+            "keyword=%E4%B8%AD%E6%96%87%20test&wts=1702204169&w_rid=7f84d8972a7698e69bcbf171daa36bc6"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Cookie jar shared by all requests.
+//!
+//! Holds the `CONSENT` cookie of `recommended_cookies` plus cookies
+//! imported from a Netscape `cookies.txt`, as exported by browser
+//! extensions, for logged-in sessions. Cookie values are never logged.
+
+use crate::error::{MusicFreeError, Result};
+use reqwest::cookie::{CookieStore, Jar};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use url::Url;
+
+/// The jar used by `get_http_client`
+pub fn cookie_jar() -> Arc<Jar> {
+    static JAR: OnceLock<Arc<Jar>> = OnceLock::new();
+    JAR.get_or_init(|| Arc::new(recommended_cookies())).clone()
+}
+
+pub fn recommended_cookies() -> Jar {
+    let cookie =
+        "CONSENT=YES+; Path=/; Domain=youtube.com; Secure; Expires=Fri, 01 Jan 2038 00:00:00 GMT;";
+    let url = "https://youtube.com".parse().unwrap();
+
+    let jar = Jar::default();
+    jar.add_cookie_str(cookie, &url);
+    jar
+}
+
+/// A cookie of a `cookies.txt`
+#[derive(Clone, PartialEq, Eq)]
+pub struct NetscapeCookie {
+    pub domain: String,
+    /// Also sent to subdomains of `domain`
+    pub include_subdomains: bool,
+    pub path: String,
+    pub secure: bool,
+    /// Unix time, `None` for session cookies
+    pub expires: Option<i64>,
+    pub name: String,
+    pub value: String,
+}
+
+// Keeps values out of debug output
+impl std::fmt::Debug for NetscapeCookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetscapeCookie")
+            .field("domain", &self.domain)
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NetscapeCookie {
+    /// URL the cookie is set from
+    fn url(&self) -> Option<Url> {
+        let host = self.domain.trim_start_matches('.');
+        Url::parse(&format!("https://{host}{}", self.path)).ok()
+    }
+
+    /// `Set-Cookie` form of the cookie
+    fn set_cookie(&self, now: i64) -> String {
+        let mut cookie = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.include_subdomains {
+            cookie.push_str(&format!("; Domain={}", self.domain.trim_start_matches('.')));
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        if let Some(expires) = self.expires {
+            cookie.push_str(&format!("; Max-Age={}", expires - now));
+        }
+        cookie
+    }
+}
+
+/// Parse the Netscape `cookies.txt` format
+///
+/// Lines are `domain, include subdomains, path, secure, expires, name,
+/// value` separated by tabs. `#HttpOnly_` prefixed domains are cookies,
+/// other `#` lines comments.
+pub fn parse_cookies_txt(content: &str) -> Result<Vec<NetscapeCookie>> {
+    let mut cookies = vec![];
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [
+            domain,
+            include_subdomains,
+            path,
+            secure,
+            expires,
+            name,
+            value,
+        ] = fields[..]
+        else {
+            return Err(MusicFreeError::ParseError(format!(
+                "Invalid cookies.txt line {}: expected 7 tab separated fields",
+                index + 1
+            )));
+        };
+        let expires: i64 = expires.parse().map_err(|_| {
+            MusicFreeError::ParseError(format!(
+                "Invalid cookies.txt line {}: bad expiry {expires:?}",
+                index + 1
+            ))
+        })?;
+        cookies.push(NetscapeCookie {
+            domain: domain.to_string(),
+            include_subdomains: include_subdomains.eq_ignore_ascii_case("TRUE"),
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            expires: (expires > 0).then_some(expires),
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(cookies)
+}
+
+/// Import a `cookies.txt` into the shared jar, returning the number of
+/// cookies added. Expired cookies are skipped.
+pub fn load_cookies_txt(path: impl AsRef<Path>) -> Result<usize> {
+    let content = std::fs::read_to_string(path)?;
+    let now = chrono::Utc::now().timestamp();
+    let jar = cookie_jar();
+    let mut count = 0;
+    for cookie in parse_cookies_txt(&content)? {
+        if cookie.expires.is_some_and(|expires| expires <= now) {
+            continue;
+        }
+        if let Some(url) = cookie.url() {
+            jar.add_cookie_str(&cookie.set_cookie(now), &url);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Value of the cookie `name` sent to `url`
+pub fn cookie_value(url: &str, name: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let header = cookie_jar().cookies(&url)?;
+    header.to_str().ok()?.split("; ").find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == name).then(|| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cookies_txt() {
+        let content = "# Netscape HTTP Cookie File\r\n\
+            # This is a generated file! Do not edit.\r\n\
+            \r\n\
+            .youtube.com\tTRUE\t/\tTRUE\t1893456000\tSAPISID\tabc/def\r\n\
+            #HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t0\tSID\tg.a000\r\n\
+            www.bilibili.com\tFALSE\t\tFALSE\t1893456000\tbuvid3\tx=y\n";
+        let cookies = parse_cookies_txt(content).unwrap();
+        assert_eq!(
+            cookies,
+            [
+                NetscapeCookie {
+                    domain: ".youtube.com".to_string(),
+                    include_subdomains: true,
+                    path: "/".to_string(),
+                    secure: true,
+                    expires: Some(1893456000),
+                    name: "SAPISID".to_string(),
+                    value: "abc/def".to_string(),
+                },
+                // HttpOnly cookie with a session expiry
+                NetscapeCookie {
+                    domain: ".youtube.com".to_string(),
+                    include_subdomains: true,
+                    path: "/".to_string(),
+                    secure: true,
+                    expires: None,
+                    name: "SID".to_string(),
+                    value: "g.a000".to_string(),
+                },
+                NetscapeCookie {
+                    domain: "www.bilibili.com".to_string(),
+                    include_subdomains: false,
+                    path: "/".to_string(),
+                    secure: false,
+                    expires: Some(1893456000),
+                    name: "buvid3".to_string(),
+                    value: "x=y".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let missing_field = ".youtube.com\tTRUE\t/\tTRUE\t0\tSID\n";
+        let error = parse_cookies_txt(missing_field).unwrap_err().to_string();
+        assert!(error.contains("line 1"), "{error}");
+
+        let bad_expiry = "# comment\n.youtube.com\tTRUE\t/\tTRUE\tsoon\tSID\tvalue\n";
+        let error = parse_cookies_txt(bad_expiry).unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{error}");
+    }
+
+    #[test]
+    fn builds_set_cookie_strings() {
+        let cookie = NetscapeCookie {
+            domain: ".youtube.com".to_string(),
+            include_subdomains: true,
+            path: "/".to_string(),
+            secure: true,
+            expires: Some(1_000_060),
+            name: "SAPISID".to_string(),
+            value: "secret".to_string(),
+        };
+        assert_eq!(
+            cookie.set_cookie(1_000_000),
+            "SAPISID=secret; Path=/; Domain=youtube.com; Secure; Max-Age=60"
+        );
+        assert_eq!(
+            cookie.url().map(String::from).as_deref(),
+            Some("https://youtube.com/")
+        );
+        // Values never reach debug output
+        assert!(!format!("{cookie:?}").contains("secret"));
+    }
+}
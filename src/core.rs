@@ -1,6 +1,7 @@
 use crate::FileExtractor;
 use crate::download::download_binary;
-use crate::error::Result;
+use crate::error::{MusicFreeError, Result};
+use crate::subtitle::SubtitleLine;
 
 #[cfg(feature = "youtube")]
 use crate::youtube::YoutubeExtractor;
@@ -9,6 +10,8 @@ use crate::youtube::YoutubeExtractor;
 use crate::BilibiliExtractor;
 use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
 pub use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -45,6 +48,9 @@ pub enum AudioFormat {
     Ogg,
     Mp4,
     Webm,
+    Flv,
+    /// MPEG-TS, HLS live recordings
+    Ts,
 }
 
 impl AudioFormat {
@@ -58,6 +64,8 @@ impl AudioFormat {
             AudioFormat::Ogg => ".ogg",
             AudioFormat::Mp4 => ".mp4",
             AudioFormat::Webm => ".webm",
+            AudioFormat::Flv => ".flv",
+            AudioFormat::Ts => ".ts",
         }
     }
 
@@ -67,6 +75,35 @@ impl AudioFormat {
         };
         Self::Mp4
     }
+
+    /// Container of downloaded data by its leading bytes
+    pub fn from_signature(data: &[u8]) -> Option<Self> {
+        let format = match data {
+            _ if data.get(4..8) == Some(b"ftyp".as_slice()) => Self::M4A,
+            [0x1A, 0x45, 0xDF, 0xA3, ..] => Self::Webm,
+            _ if data.starts_with(b"fLaC") => Self::Flac,
+            _ if data.starts_with(b"OggS") => Self::Ogg,
+            _ if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WAVE".as_slice()) => {
+                Self::Wav
+            }
+            _ if data.starts_with(b"FLV") => Self::Flv,
+            _ if data.starts_with(b"ID3") => Self::Mp3,
+            // ADTS and MPEG audio share the frame sync, ADTS has layer 0
+            [0xFF, b, ..] if b & 0xF6 == 0xF0 => Self::AAC,
+            [0xFF, b, ..] if b & 0xE0 == 0xE0 => Self::Mp3,
+            [0x47, ..] if data.get(188) == Some(&0x47) => Self::Ts,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Whether both formats are stored in the same container
+    pub fn same_container(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::M4A | Self::Mp4, Self::M4A | Self::Mp4)
+        ) || self == other
+    }
 }
 /// Audio resource representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +117,26 @@ pub struct Audio {
     pub duration: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub format: Option<AudioFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    /// Track number within `album`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lyric_url: Option<String>,
+    /// Timed subtitles (CC), see `subtitle::download_lyrics`. Platforms
+    /// that look them up on demand leave this empty, see
+    /// `Extractor::get_subtitles`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtitles: Vec<Subtitle>,
+    /// Where the platform finds the media, bypassing `download_url` parsing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locator: Option<Locator>,
+    /// Live stream: use `Extractor::record` instead of `download`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub live: bool,
     pub platform: Platform,
 }
 
@@ -93,6 +150,13 @@ impl Audio {
             cover: None,
             duration: None,
             format: None,
+            artist: None,
+            album: None,
+            track: None,
+            lyric_url: None,
+            subtitles: Vec::new(),
+            locator: None,
+            live: false,
             platform,
         }
     }
@@ -114,6 +178,79 @@ impl Audio {
         self.duration = Some(duration);
         self
     }
+
+    /// Set artist name
+    pub fn with_artist(mut self, artist: String) -> Self {
+        self.artist = Some(artist);
+        self
+    }
+
+    /// Set album title
+    pub fn with_album(mut self, album: String) -> Self {
+        self.album = Some(album);
+        self
+    }
+
+    /// Set track number within the album
+    pub fn with_track(mut self, track: u32) -> Self {
+        self.track = Some(track);
+        self
+    }
+
+    /// Set lyric (LRC) URL
+    pub fn with_lyric_url(mut self, lyric_url: String) -> Self {
+        self.lyric_url = Some(lyric_url);
+        self
+    }
+
+    /// Set available subtitles
+    pub fn with_subtitles(mut self, subtitles: Vec<Subtitle>) -> Self {
+        self.subtitles = subtitles;
+        self
+    }
+
+    /// Set platform locator
+    pub fn with_locator(mut self, locator: Locator) -> Self {
+        self.locator = Some(locator);
+        self
+    }
+
+    /// Mark as live stream
+    pub fn with_live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+}
+
+/// Platform specific address of a media stream
+///
+/// Produced by `extract` and consumed by `Extractor::download_audio`; treat
+/// it as opaque. It survives serialization so stored playlists can still be
+/// downloaded without resolving their URLs again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Locator {
+    /// A single page of a Bilibili video
+    Bilibili { bvid: String, cid: u64 },
+    /// A YouTube video, optionally pinned to one format
+    Youtube {
+        video_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        itag: Option<u64>,
+    },
+}
+
+/// A subtitle track of an audio, fetched with `Extractor::download_subtitle`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Subtitle {
+    /// Language code, e.g. `zh-CN`, `ai-zh` or `en`
+    pub lang: String,
+    /// Human readable language name
+    pub name: String,
+    pub url: String,
+    /// Whether `Extractor::translate_subtitle` can translate the track
+    #[serde(default)]
+    pub translatable: bool,
 }
 
 /// Playlist representation
@@ -129,6 +266,10 @@ pub struct Playlist {
     pub audios: Vec<Audio>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cover: Option<String>,
+    /// Nested playlists, e.g. the playlists tab of a channel. Their audios
+    /// are not extracted, download them through `download_url`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub playlists: Vec<Playlist>,
     pub platform: Platform,
 }
 
@@ -141,11 +282,27 @@ impl Playlist {
             title,
             audios: Vec::new(),
             cover: None,
+            playlists: Vec::new(),
             platform,
         }
     }
 }
 
+/// A parsed search input, e.g. `ytsearch:10:query`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub query: String,
+    /// Maximum number of results
+    pub limit: usize,
+}
+
+/// Options for recording live streams
+#[derive(Debug, Clone, Default)]
+pub struct RecordOptions {
+    /// Stop after this long, `None` records until the stream ends
+    pub duration: Option<Duration>,
+}
+
 #[derive(EnumIter, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Copy, Default)]
 pub enum Quality {
     Low,
@@ -172,11 +329,54 @@ pub trait Extractor: Send + Sync {
         Ok(binary)
     }
 
+    /// Download an extracted audio
+    /// Uses `audio.locator` when the platform supports it, `download_url` otherwise
+    async fn download_audio(&self, audio: &Audio) -> Result<Vec<u8>> {
+        self.download(&audio.download_url).await
+    }
+
     async fn download_cover(&self, url: &str) -> Result<Vec<u8>> {
         let binary = download_binary(url, HeaderMap::new()).await?;
         Ok(binary)
     }
 
+    /// Record a live stream into `writer` until it ends or `options.duration` elapses
+    /// Returns the number of bytes written
+    async fn record(
+        &self,
+        url: &str,
+        _options: &RecordOptions,
+        _writer: &mut (dyn Write + Send),
+    ) -> Result<u64> {
+        Err(MusicFreeError::PlatformNotSupported(format!(
+            "Live recording is not supported for: {}",
+            url
+        )))
+    }
+
+    /// Subtitle tracks of an audio, `audio.subtitles` unless the platform
+    /// looks them up on demand
+    async fn get_subtitles(&self, audio: &Audio) -> Result<Vec<Subtitle>> {
+        Ok(audio.subtitles.clone())
+    }
+
+    /// Download and parse a subtitle track into timed lines
+    async fn download_subtitle(&self, subtitle: &Subtitle) -> Result<Vec<SubtitleLine>> {
+        Err(MusicFreeError::PlatformNotSupported(format!(
+            "Subtitles are not supported for: {}",
+            subtitle.url
+        )))
+    }
+
+    /// A machine translation of a subtitle track into `lang`, an error when
+    /// the platform or the track can't be translated
+    fn translate_subtitle(&self, subtitle: &Subtitle, _lang: &str) -> Result<Subtitle> {
+        Err(MusicFreeError::PlatformNotSupported(format!(
+            "Subtitle translation is not supported for: {}",
+            subtitle.url
+        )))
+    }
+
     /// Get platform identifier
     fn platform(&self) -> Platform;
 }
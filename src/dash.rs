@@ -0,0 +1,587 @@
+//! Minimal DASH (MPD) manifest support for live recording.
+//!
+//! Only what audio recording needs is parsed: audio representations with
+//! their `BaseURL`, `SegmentList` segments and initialization. Dynamic
+//! manifests are refreshed like an HLS playlist, static ones are downloaded
+//! once. Representations without segments are a single file.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use url::Url;
+
+use crate::download::{
+    download_binary_chunked, download_single_chunk, download_text, record_stream,
+};
+use crate::error::{MusicFreeError, Result};
+
+/// Lower bound of the manifest refresh interval
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Refresh interval of dynamic manifests without `minimumUpdatePeriod`
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A representation of an adaptation set
+#[derive(Debug, Clone, Default)]
+pub struct Representation {
+    pub id: String,
+    pub mime_type: Option<String>,
+    pub bandwidth: u64,
+    /// Absolute `BaseURL`
+    pub base_url: String,
+    /// Absolute initialization segment URL
+    pub initialization: Option<String>,
+    /// Sequence numbers and absolute URLs of the segments
+    pub segments: Vec<(u64, String)>,
+}
+
+impl Representation {
+    pub fn is_audio(&self) -> bool {
+        self.mime_type
+            .as_deref()
+            .is_some_and(|mime| mime.starts_with("audio/"))
+    }
+}
+
+/// A parsed MPD manifest
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    /// `type="dynamic"`, segments are added while the stream is live
+    pub dynamic: bool,
+    /// `minimumUpdatePeriod`
+    pub minimum_update_period: Option<Duration>,
+    pub representations: Vec<Representation>,
+}
+
+impl Manifest {
+    /// The audio representation with the highest bandwidth
+    pub fn best_audio(&self) -> Option<&Representation> {
+        self.representations
+            .iter()
+            .filter(|r| r.is_audio())
+            .max_by_key(|r| r.bandwidth)
+    }
+}
+
+/// A start, end or empty element tag
+struct Tag<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    end: bool,
+    empty: bool,
+    /// Text up to the next tag
+    text: &'a str,
+}
+
+impl Tag<'_> {
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Element name without namespace prefix
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn parse_tag(content: &str) -> (&str, Vec<(&str, String)>) {
+    let content = content.trim();
+    let (name, mut rest) = content
+        .split_once(|c: char| c.is_ascii_whitespace())
+        .unwrap_or((content, ""));
+    let mut attributes = vec![];
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some((value, next)) = value[1..].split_once(quote) else {
+            break;
+        };
+        attributes.push((local_name(key.trim()), unescape(value)));
+        rest = next;
+    }
+    (local_name(name), attributes)
+}
+
+/// Split XML into tags, skipping declarations and comments
+fn tags(xml: &str) -> Vec<Tag<'_>> {
+    let mut tags = vec![];
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, next)| next);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let content = &rest[1..end];
+        rest = &rest[end + 1..];
+        if content.starts_with(['?', '!']) {
+            continue;
+        }
+
+        let end_tag = content.starts_with('/');
+        let empty = content.ends_with('/');
+        let content = content.trim_start_matches('/').trim_end_matches('/');
+        let (name, attributes) = parse_tag(content);
+        tags.push(Tag {
+            name,
+            attributes,
+            end: end_tag,
+            empty,
+            text: &rest[..rest.find('<').unwrap_or(rest.len())],
+        });
+    }
+    tags
+}
+
+/// Parse an ISO 8601 duration such as `PT5S` or `PT1M30.5S`
+fn parse_duration(s: &str) -> Option<Duration> {
+    let time = s.strip_prefix("PT")?;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for c in time.chars() {
+        let unit = match c {
+            'H' => 3600.0,
+            'M' => 60.0,
+            'S' => 1.0,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        seconds += number.parse::<f64>().ok()? * unit;
+        number.clear();
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Sequence number of a YouTube `sq/<n>` segment path
+fn youtube_sequence(media: &str) -> Option<u64> {
+    let start = media.find("sq/")? + "sq/".len();
+    media[start..].split('/').next()?.parse().ok()
+}
+
+fn resolve(base: &str, uri: &str) -> Result<String> {
+    let base = Url::parse(base)
+        .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid DASH URL {base}: {e}")))?;
+    base.join(uri.trim())
+        .map(|u| u.to_string())
+        .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid DASH segment {uri}: {e}")))
+}
+
+/// Representation being parsed, URLs are resolved once its `BaseURL` is known
+#[derive(Default)]
+struct Pending {
+    representation: Representation,
+    start_number: u64,
+    initialization: Option<String>,
+    media: Vec<String>,
+}
+
+/// Parse an MPD manifest, resolving URLs against `base`
+pub fn parse_mpd(xml: &str, base: &Url) -> Result<Manifest> {
+    let mut manifest = Manifest::default();
+    // `BaseURL` of MPD, Period, AdaptationSet and Representation
+    let mut bases = vec![base.to_string()];
+    // `mimeType` inherited from the adaptation set
+    let mut set_mime_type = None;
+    let mut pending: Option<Pending> = None;
+
+    for tag in tags(xml) {
+        match (tag.name, tag.end) {
+            ("MPD", false) => {
+                manifest.dynamic = tag.attribute("type") == Some("dynamic");
+                manifest.minimum_update_period = tag
+                    .attribute("minimumUpdatePeriod")
+                    .and_then(parse_duration);
+            }
+            ("Period" | "AdaptationSet" | "Representation", false) if !tag.empty => {
+                let parent = bases.last().cloned().unwrap_or_default();
+                bases.push(parent);
+            }
+            ("Period" | "AdaptationSet", true) => {
+                bases.pop();
+            }
+            _ => {}
+        }
+
+        match (tag.name, tag.end) {
+            ("BaseURL", false) => {
+                let text = unescape(tag.text.trim());
+                if let Some(current) = bases.last_mut() {
+                    *current = resolve(current, &text)?;
+                }
+            }
+            ("AdaptationSet", false) => {
+                set_mime_type = tag.attribute("mimeType").map(str::to_string);
+            }
+            ("Representation", false) => {
+                pending = Some(Pending {
+                    representation: Representation {
+                        id: tag.attribute("id").unwrap_or_default().to_string(),
+                        mime_type: tag
+                            .attribute("mimeType")
+                            .map(str::to_string)
+                            .or_else(|| set_mime_type.clone()),
+                        bandwidth: tag
+                            .attribute("bandwidth")
+                            .and_then(|b| b.parse().ok())
+                            .unwrap_or(0),
+                        ..Default::default()
+                    },
+                    start_number: 1,
+                    ..Default::default()
+                });
+            }
+            ("SegmentList", false) => {
+                if let Some(pending) = &mut pending {
+                    pending.start_number = tag
+                        .attribute("startNumber")
+                        .and_then(|n| n.parse().ok())
+                        .unwrap_or(1);
+                }
+            }
+            ("Initialization", false) => {
+                if let Some(pending) = &mut pending {
+                    pending.initialization = tag.attribute("sourceURL").map(str::to_string);
+                }
+            }
+            ("SegmentURL", false) => {
+                if let Some(pending) = &mut pending
+                    && let Some(media) = tag.attribute("media")
+                {
+                    pending.media.push(media.to_string());
+                }
+            }
+            ("Representation", true) => {
+                let base_url = bases.pop().unwrap_or_default();
+                if let Some(pending) = pending.take() {
+                    manifest.representations.push(finish(pending, base_url)?);
+                }
+            }
+            _ => {}
+        }
+
+        // `<Representation ... />` is opened and closed at once
+        if tag.name == "Representation"
+            && tag.empty
+            && let Some(pending) = pending.take()
+        {
+            let base_url = bases.last().cloned().unwrap_or_default();
+            manifest.representations.push(finish(pending, base_url)?);
+        }
+    }
+    Ok(manifest)
+}
+
+fn finish(pending: Pending, base_url: String) -> Result<Representation> {
+    let mut representation = pending.representation;
+    representation.initialization = pending
+        .initialization
+        .map(|uri| resolve(&base_url, &uri))
+        .transpose()?;
+    for (index, media) in pending.media.iter().enumerate() {
+        let sequence = youtube_sequence(media).unwrap_or(pending.start_number + index as u64);
+        representation
+            .segments
+            .push((sequence, resolve(&base_url, media)?));
+    }
+    representation.base_url = base_url;
+    Ok(representation)
+}
+
+/// Record the best audio representation of a DASH manifest into `writer`
+/// until a static manifest is downloaded, a dynamic one disappears, or
+/// `deadline` passes, also within a single-file representation. Returns the number of bytes written.
+pub async fn record_dash(
+    url: &str,
+    headers: HeaderMap,
+    deadline: Option<Instant>,
+    writer: &mut (dyn Write + Send),
+) -> Result<u64> {
+    let base = Url::parse(url)
+        .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid DASH URL {url}: {e}")))?;
+    let is_over = || deadline.is_some_and(|d| Instant::now() >= d);
+
+    let mut written = 0;
+    let mut last_sequence = None;
+    let mut representation_id = None;
+    let mut wrote_init = false;
+
+    while !is_over() {
+        let text = match download_text(url, headers.clone()).await {
+            Ok(text) => text,
+            // Live manifests are removed once the stream is over
+            Err(MusicFreeError::HttpError { status: 404, .. }) if written > 0 => break,
+            Err(e) => return Err(e),
+        };
+        let manifest = parse_mpd(&text, &base)?;
+
+        // Stay on the first pick, segments of other representations don't
+        // concatenate
+        let representation = match &representation_id {
+            Some(id) => manifest.representations.iter().find(|r| &r.id == id),
+            None => manifest.best_audio(),
+        }
+        .ok_or(MusicFreeError::AudioNotFound)?;
+        representation_id = Some(representation.id.clone());
+
+        #[cfg(debug_assertions)]
+        if !wrote_init {
+            eprintln!(
+                "[debug] DASH representation {} ({:?}, {} bps)",
+                representation.id, representation.mime_type, representation.bandwidth
+            );
+        }
+
+        // A single file, streamed so that the deadline still applies
+        if representation.segments.is_empty() {
+            written += match deadline {
+                Some(_) => {
+                    record_stream(&representation.base_url, headers.clone(), deadline, writer)
+                        .await?
+                }
+                None => {
+                    let data =
+                        download_binary_chunked(&representation.base_url, headers.clone()).await?;
+                    writer.write_all(&data)?;
+                    data.len() as u64
+                }
+            };
+            break;
+        }
+
+        if !wrote_init && let Some(init) = &representation.initialization {
+            let data = download_single_chunk(init, &headers).await?;
+            writer.write_all(&data)?;
+            written += data.len() as u64;
+        }
+        wrote_init = true;
+
+        for (sequence, segment) in &representation.segments {
+            if last_sequence.is_some_and(|last| *sequence <= last) {
+                continue;
+            }
+            if is_over() {
+                break;
+            }
+
+            let data = download_single_chunk(segment, &headers).await?;
+            writer.write_all(&data)?;
+            written += data.len() as u64;
+            last_sequence = Some(*sequence);
+        }
+
+        if !manifest.dynamic {
+            break;
+        }
+
+        let interval = manifest
+            .minimum_update_period
+            .unwrap_or(DEFAULT_POLL_INTERVAL);
+        tokio::time::sleep(interval.max(MIN_POLL_INTERVAL)).await;
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A YouTube live manifest, trimmed to one audio and one video
+    /// representation
+    const YOUTUBE_LIVE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns="urn:mpeg:DASH:schema:MPD:2011" xmlns:yt="http://youtube.com/yt/2012/10/10" xsi:schemaLocation="urn:mpeg:DASH:schema:MPD:2011 DASH-MPD.xsd" minBufferTime="PT1.500S" profiles="urn:mpeg:dash:profile:isoff-live:2011" type="dynamic" availabilityStartTime="2024-05-01T12:00:00" timeShiftBufferDepth="PT7200.000S" minimumUpdatePeriod="PT5.000S" yt:earliestMediaSequence="0" yt:mpdRequestTime="2024-05-01T13:00:00.123" yt:mpdResponseTime="2024-05-01T13:00:00.125">
+<Period start="PT0.000S" yt:segmentIngestTime="2024-05-01T12:59:55.000">
+<SegmentList presentationTimeOffset="0" startNumber="718" timescale="1000">
+<SegmentTimeline><S d="5000" r="2"/></SegmentTimeline>
+</SegmentList>
+<AdaptationSet id="0" mimeType="audio/mp4" subsegmentAlignment="true">
+<Role schemeIdUri="urn:mpeg:DASH:role:2011" value="main"/>
+<Representation id="140" codecs="mp4a.40.2" audioSamplingRate="48000" startWithSAP="1" bandwidth="144000">
+<AudioChannelConfiguration schemeIdUri="urn:mpeg:dash:23003:3:audio_channel_configuration:2011" value="2"/>
+<BaseURL>https://rr3---sn-a5mekn6d.googlevideo.com/videoplayback/expire/1714590000/ei/abc/id/jfKfPfyJRdk.2/itag/140/source/yt_live_broadcast/live/1/sparams/expire%2Cei%2Cid%2Citag%2Csource%2Clive/sig/xyz/</BaseURL>
+<SegmentList>
+<SegmentURL media="sq/718/lmt/1714564800000718"/>
+<SegmentURL media="sq/719/lmt/1714564800000719"/>
+<SegmentURL media="sq/720/lmt/1714564800000720"/>
+</SegmentList>
+</Representation>
+</AdaptationSet>
+<AdaptationSet id="1" mimeType="video/mp4" subsegmentAlignment="true">
+<Role schemeIdUri="urn:mpeg:DASH:role:2011" value="main"/>
+<Representation id="133" codecs="avc1.4d4015" width="426" height="240" startWithSAP="1" maxPlayoutRate="1" bandwidth="258000" frameRate="30">
+<BaseURL>https://rr3---sn-a5mekn6d.googlevideo.com/videoplayback/expire/1714590000/ei/abc/id/jfKfPfyJRdk.2/itag/133/source/yt_live_broadcast/live/1/sig/xyz/</BaseURL>
+<SegmentList>
+<SegmentURL media="sq/718/lmt/1714564800000718"/>
+</SegmentList>
+</Representation>
+</AdaptationSet>
+</Period>
+</MPD>
+"#;
+
+    /// An on-demand manifest, one file per representation
+    const STATIC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Generated <Representation id="ignored"/> -->
+<mpd:MPD xmlns:mpd="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT3M32.5S">
+<mpd:BaseURL>https://cdn.example.com/media/</mpd:BaseURL>
+<mpd:Period>
+<mpd:AdaptationSet mimeType="audio/webm" lang="en">
+<mpd:Representation id="251" codecs="opus" bandwidth="160000">
+<mpd:BaseURL>251.webm?range=all&amp;sig=1</mpd:BaseURL>
+<mpd:SegmentBase indexRange="258-1023"><mpd:Initialization range="0-257"/></mpd:SegmentBase>
+</mpd:Representation>
+<mpd:Representation id="250" codecs="opus" bandwidth="70000">
+<mpd:BaseURL>250.webm</mpd:BaseURL>
+</mpd:Representation>
+</mpd:AdaptationSet>
+<mpd:AdaptationSet>
+<mpd:Representation id="18" mimeType="video/mp4" bandwidth="500000"/>
+</mpd:AdaptationSet>
+</mpd:Period>
+</mpd:MPD>
+"#;
+
+    fn base() -> Url {
+        Url::parse(
+            "https://manifest.googlevideo.com/api/manifest/dash/id/1/source/yt_live_broadcast",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_youtube_live_manifest() {
+        let manifest = parse_mpd(YOUTUBE_LIVE, &base()).unwrap();
+        assert!(manifest.dynamic);
+        assert_eq!(manifest.minimum_update_period, Some(Duration::from_secs(5)));
+        assert_eq!(manifest.representations.len(), 2);
+
+        let audio = manifest.best_audio().unwrap();
+        assert_eq!(audio.id, "140");
+        assert_eq!(audio.mime_type.as_deref(), Some("audio/mp4"));
+        assert_eq!(audio.bandwidth, 144_000);
+        assert_eq!(audio.initialization, None);
+        let sequences: Vec<u64> = audio.segments.iter().map(|(sq, _)| *sq).collect();
+        assert_eq!(sequences, [718, 719, 720]);
+        assert_eq!(
+            audio.segments[0].1,
+            format!("{}sq/718/lmt/1714564800000718", audio.base_url)
+        );
+        assert!(audio.base_url.contains("/itag/140/"));
+
+        let video = &manifest.representations[1];
+        assert_eq!(video.id, "133");
+        assert!(!video.is_audio());
+    }
+
+    #[test]
+    fn parses_static_manifest_with_nested_base_urls() {
+        let manifest = parse_mpd(STATIC, &base()).unwrap();
+        assert!(!manifest.dynamic);
+        assert_eq!(manifest.minimum_update_period, None);
+
+        let ids: Vec<&str> = manifest
+            .representations
+            .iter()
+            .map(|r| r.id.as_str())
+            .collect();
+        assert_eq!(ids, ["251", "250", "18"]);
+
+        let audio = manifest.best_audio().unwrap();
+        assert_eq!(audio.id, "251");
+        assert!(audio.segments.is_empty());
+        assert_eq!(
+            audio.base_url,
+            "https://cdn.example.com/media/251.webm?range=all&sig=1"
+        );
+        assert_eq!(
+            manifest.representations[1].base_url,
+            "https://cdn.example.com/media/250.webm"
+        );
+        // The self-closing representation inherits the period's BaseURL
+        assert_eq!(
+            manifest.representations[2].base_url,
+            "https://cdn.example.com/media/"
+        );
+    }
+
+    #[test]
+    fn numbers_segments_from_start_number() {
+        let xml = r#"<MPD type="dynamic">
+<AdaptationSet mimeType="audio/mp4">
+<Representation id="a" bandwidth="96000">
+<SegmentList startNumber="7">
+<Initialization sourceURL="init.mp4"/>
+<SegmentURL media="seg-7.m4s"/>
+<SegmentURL media="seg-8.m4s"/>
+</SegmentList>
+</Representation>
+</AdaptationSet>
+</MPD>"#;
+        let base = Url::parse("https://live.example.com/stream/manifest.mpd").unwrap();
+        let manifest = parse_mpd(xml, &base).unwrap();
+        let audio = manifest.best_audio().unwrap();
+        assert_eq!(
+            audio.initialization.as_deref(),
+            Some("https://live.example.com/stream/init.mp4")
+        );
+        assert_eq!(
+            audio.segments,
+            [
+                (7, "https://live.example.com/stream/seg-7.m4s".to_string()),
+                (8, "https://live.example.com/stream/seg-8.m4s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_tags_and_attributes() {
+        let tags =
+            tags(r#"<?xml version="1.0"?><a x="1" y='two &amp; three'>text<b/></a><!-- <c/> -->"#);
+        let names: Vec<(&str, bool, bool)> =
+            tags.iter().map(|t| (t.name, t.end, t.empty)).collect();
+        assert_eq!(
+            names,
+            [("a", false, false), ("b", false, true), ("a", true, false)]
+        );
+        assert_eq!(tags[0].attribute("x"), Some("1"));
+        assert_eq!(tags[0].attribute("y"), Some("two & three"));
+        assert_eq!(tags[0].text, "text");
+
+        let (name, attributes) = parse_tag(r#"yt:Tag  yt:key = "v" broken=unquoted"#);
+        assert_eq!(name, "Tag");
+        assert_eq!(attributes, [("key", "v".to_string())]);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT5S"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("PT5.000S"), Some(Duration::from_secs(5)));
+        assert_eq!(
+            parse_duration("PT1M30.5S"),
+            Some(Duration::from_secs_f64(90.5))
+        );
+        assert_eq!(parse_duration("PT2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("P1D"), None);
+        assert_eq!(parse_duration("PTxS"), None);
+    }
+}
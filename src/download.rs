@@ -5,38 +5,52 @@ use reqwest::{
     header::{HeaderMap, HeaderValue, RANGE},
 };
 use serde::{Serialize, de::DeserializeOwned};
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+use std::io::Write;
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+use std::time::Instant;
 use std::{sync::OnceLock, time::Duration};
 
+use crate::cookies::cookie_jar;
 use crate::error::{MusicFreeError, Result};
 
 pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+/// Give up on a live stream when no data arrives for this long.
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+pub(crate) const STREAM_STALL_TIMEOUT: Duration = Duration::from_secs(30);
 pub(crate) const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36 Edg/143.0.0.0";
 
 pub fn get_http_client() -> &'static Client {
     static CLIENT: OnceLock<Client> = OnceLock::new();
-    let cookie_jar = recommended_cookies();
 
     CLIENT.get_or_init(|| {
         reqwest::Client::builder()
             .timeout(DEFAULT_TIMEOUT)
             .connect_timeout(DEFAULT_TIMEOUT)
             .tcp_keepalive(Duration::from_secs(60))
-            .cookie_provider(std::sync::Arc::new(cookie_jar))
-            .cookie_store(true)
+            // `cookie_store(true)` would replace the jar with an empty one
+            .cookie_provider(cookie_jar())
             .http1_only()
             .build()
             .expect("Failed to create HTTP client")
     })
 }
 
-pub fn recommended_cookies() -> reqwest::cookie::Jar {
-    let cookie =
-        "CONSENT=YES+; Path=/; Domain=youtube.com; Secure; Expires=Fri, 01 Jan 2038 00:00:00 GMT;";
-    let url = "https://youtube.com".parse().unwrap();
+/// Client for long-lived streaming responses (live recording).
+/// Unlike `get_http_client` it has no total request timeout; stalls are
+/// detected per chunk with `STREAM_STALL_TIMEOUT`.
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+pub fn get_stream_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
 
-    let jar = reqwest::cookie::Jar::default();
-    jar.add_cookie_str(cookie, &url);
-    jar
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(DEFAULT_TIMEOUT)
+            .tcp_keepalive(Duration::from_secs(60))
+            .http1_only()
+            .build()
+            .expect("Failed to create HTTP client")
+    })
 }
 
 /// Get default headers for API/page requests.
@@ -165,7 +179,7 @@ fn parse_content_range(content_range: &str) -> Option<usize> {
 
 /// Retry a single chunk download with exponential backoff.
 /// Only retries on 5xx server errors and network errors (like yt-dlp).
-async fn download_single_chunk(url: &str, headers: &HeaderMap) -> Result<Vec<u8>> {
+pub(crate) async fn download_single_chunk(url: &str, headers: &HeaderMap) -> Result<Vec<u8>> {
     let mut last_error = None;
 
     for attempt in 0..=MAX_RETRIES {
@@ -258,13 +272,19 @@ pub async fn download_binary_chunked(url: &str, headers: HeaderMap) -> Result<Ve
 
     #[cfg(debug_assertions)]
     {
-        eprintln!("[debug] download_binary_chunked: url={url}");
+        eprintln!(
+            "[debug] download_binary_chunked: url={}",
+            crate::utils::strip_query(url)
+        );
+        use reqwest::header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION};
         for (key, val) in download_headers.iter() {
-            eprintln!(
-                "[debug]   header: {} = {}",
-                key,
+            // Credentials stay out of the log
+            let val = if [COOKIE, AUTHORIZATION, PROXY_AUTHORIZATION].contains(key) {
+                "<redacted>"
+            } else {
                 val.to_str().unwrap_or("<binary>")
-            );
+            };
+            eprintln!("[debug]   header: {} = {}", key, val);
         }
     }
 
@@ -381,6 +401,52 @@ pub async fn download_binary_chunked(url: &str, headers: HeaderMap) -> Result<Ve
     }
 }
 
+/// Stream a response body into `writer` until the server closes it,
+/// `deadline` passes, or the stream stalls for `STREAM_STALL_TIMEOUT`.
+/// Returns the number of bytes written.
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+pub async fn record_stream(
+    url: &str,
+    headers: HeaderMap,
+    deadline: Option<Instant>,
+    writer: &mut (dyn Write + Send),
+) -> Result<u64> {
+    let client = get_stream_client();
+    let mut response = execute_download_request(client, url, headers).await?;
+    let mut written = 0;
+
+    loop {
+        let wait = match deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .min(STREAM_STALL_TIMEOUT),
+            None => STREAM_STALL_TIMEOUT,
+        };
+        if wait.is_zero() {
+            break;
+        }
+
+        match tokio::time::timeout(wait, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => {
+                writer.write_all(&chunk)?;
+                written += chunk.len() as u64;
+            }
+            // Server closed the stream
+            Ok(Ok(None)) => break,
+            Ok(Err(e)) => return Err(MusicFreeError::NetworkError(e)),
+            // Deadline reached or stream stalled
+            Err(_) => {
+                #[cfg(debug_assertions)]
+                eprintln!("[debug] record_stream: no data within {wait:?}, stopping");
+                break;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
 /// Get HTTP response from URL with custom headers
 pub async fn get_response(url: &str, headers: HeaderMap) -> Result<reqwest::Response> {
     let client = get_http_client();
@@ -29,6 +29,24 @@ pub enum MusicFreeError {
     #[error("Video not found or unavailable")]
     VideoNotFound,
 
+    #[error("Premium membership required: {0}")]
+    PremiumRequired(String),
+
+    #[error("Login required: {0}")]
+    LoginRequired(String),
+
+    #[error("Age restricted: {0}")]
+    AgeRestricted(String),
+
+    #[error("Not available in this region: {0}")]
+    GeoBlocked(String),
+
+    #[error("Private video: {0}")]
+    Private(String),
+
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -74,6 +92,9 @@ pub enum MusicFreeError {
     #[error("Config parse error: {0}")]
     ConfigParseError(String),
 
+    #[error("Invalid format selector: {0}")]
+    InvalidFormatSelector(String),
+
     #[error("Regex error: {0}")]
     RegexError(#[from] regex::Error),
 }
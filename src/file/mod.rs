@@ -42,6 +42,7 @@ impl Extractor for FileExtractor {
             title: None,
             audios: vec![audio],
             cover: None,
+            playlists: Vec::new(),
             platform: Platform::File,
         };
 
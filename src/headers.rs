@@ -2,7 +2,7 @@
 //!
 //! Provides a `download_headers()` base function plus `with_*()` modifiers
 //! that build browser-like HTTP headers for binary download requests.
-//! Used by YouTube (android/web) and Bilibili download modules.
+//! Used by YouTube (client/web) and Bilibili download modules.
 
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 
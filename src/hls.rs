@@ -0,0 +1,483 @@
+//! Minimal HLS (m3u8) media playlist support for live recording.
+//!
+//! The playlist is polled, new segments are appended to the writer in
+//! media-sequence order, and an `EXT-X-MAP` init segment (fMP4) is written
+//! once up front. Master playlists are resolved to their audio rendition
+//! first. When every variant muxes the audio with video, the smallest
+//! variant is recorded and its AAC is demuxed from MPEG-TS into ADTS.
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use url::Url;
+
+use crate::download::{download_single_chunk, download_text};
+use crate::error::{MusicFreeError, Result};
+use crate::ts::AdtsDemuxer;
+
+/// Lower bound of the playlist polling interval
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A parsed HLS media playlist
+#[derive(Debug, Clone, Default)]
+pub struct MediaPlaylist {
+    /// `EXT-X-MEDIA-SEQUENCE` of the first segment
+    pub media_sequence: u64,
+    /// `EXT-X-TARGETDURATION` in seconds
+    pub target_duration: f64,
+    /// `EXT-X-MAP` init segment URL (fMP4)
+    pub map: Option<String>,
+    /// Absolute segment URLs
+    pub segments: Vec<String>,
+    /// `EXT-X-ENDLIST` present: no more segments will be added
+    pub ended: bool,
+}
+
+/// A variant stream (`EXT-X-STREAM-INF`) of a master playlist
+#[derive(Debug, Clone, Default)]
+pub struct Variant {
+    pub uri: String,
+    pub bandwidth: u64,
+    pub codecs: Option<String>,
+}
+
+/// An audio rendition (`EXT-X-MEDIA:TYPE=AUDIO`) of a master playlist
+#[derive(Debug, Clone, Default)]
+pub struct AudioRendition {
+    /// Missing when the audio is muxed into the variants
+    pub uri: Option<String>,
+    pub default: bool,
+}
+
+/// A parsed HLS master playlist
+#[derive(Debug, Clone, Default)]
+pub struct MasterPlaylist {
+    pub variants: Vec<Variant>,
+    pub audio: Vec<AudioRendition>,
+}
+
+fn resolve(base: &Url, uri: &str) -> Result<String> {
+    base.join(uri)
+        .map(|u| u.to_string())
+        .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid HLS URI {uri}: {e}")))
+}
+
+/// Parse an attribute list, e.g. `BANDWIDTH=1280000,CODECS="avc1,mp4a"`
+fn parse_attributes(list: &str) -> Vec<(&str, &str)> {
+    let mut attributes = vec![];
+    let mut rest = list;
+    while let Some((key, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, next)) => (value, next),
+                None => (quoted, ""),
+            },
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        attributes.push((key.trim(), value));
+        rest = next.trim_start_matches(',');
+    }
+    attributes
+}
+
+fn attribute<'a>(attributes: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// Whether a playlist is a master playlist listing variants
+pub fn is_master_playlist(text: &str) -> bool {
+    text.contains("#EXT-X-STREAM-INF:")
+}
+
+/// Parse an HLS master playlist, resolving URIs against `base`
+pub fn parse_master_playlist(text: &str, base: &Url) -> Result<MasterPlaylist> {
+    let mut playlist = MasterPlaylist::default();
+    let mut pending: Option<Variant> = None;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            let attributes = parse_attributes(list);
+            pending = Some(Variant {
+                uri: String::new(),
+                bandwidth: attribute(&attributes, "BANDWIDTH")
+                    .and_then(|b| b.parse().ok())
+                    .unwrap_or(0),
+                codecs: attribute(&attributes, "CODECS").map(str::to_string),
+            });
+        } else if let Some(list) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attributes = parse_attributes(list);
+            if attribute(&attributes, "TYPE") != Some("AUDIO") {
+                continue;
+            }
+            playlist.audio.push(AudioRendition {
+                uri: attribute(&attributes, "URI")
+                    .map(|uri| resolve(base, uri))
+                    .transpose()?,
+                default: attribute(&attributes, "DEFAULT") == Some("YES"),
+            });
+        } else if !line.starts_with('#')
+            && let Some(mut variant) = pending.take()
+        {
+            variant.uri = resolve(base, line)?;
+            playlist.variants.push(variant);
+        }
+    }
+    Ok(playlist)
+}
+
+/// Media playlist carrying only the audio of a master playlist
+///
+/// A separate audio rendition is preferred, the default one first, then
+/// the best audio-only variant. `None` when every variant muxes the audio
+/// with video.
+pub fn select_audio_playlist(master: &MasterPlaylist) -> Option<String> {
+    let rendition = master
+        .audio
+        .iter()
+        .filter(|r| r.uri.is_some())
+        .max_by_key(|r| r.default);
+    if let Some(uri) = rendition.and_then(|r| r.uri.clone()) {
+        return Some(uri);
+    }
+
+    const VIDEO_CODECS: [&str; 6] = ["avc", "hvc", "hev", "vp8", "vp9", "av01"];
+    let audio_only = |v: &&Variant| {
+        v.codecs
+            .as_deref()
+            .is_some_and(|codecs| !VIDEO_CODECS.iter().any(|c| codecs.contains(c)))
+    };
+    master
+        .variants
+        .iter()
+        .filter(audio_only)
+        .max_by_key(|v| v.bandwidth)
+        .map(|v| v.uri.clone())
+}
+
+/// Smallest variant muxing AAC with video, for masters without an
+/// audio-only playlist
+pub fn select_muxed_playlist(master: &MasterPlaylist) -> Option<String> {
+    master
+        .variants
+        .iter()
+        .filter(|v| v.codecs.as_deref().is_some_and(|c| c.contains("mp4a")))
+        .min_by_key(|v| v.bandwidth)
+        .map(|v| v.uri.clone())
+}
+
+/// Resolve a master playlist URL to the media playlist of its audio, and
+/// whether that playlist muxes the audio with video. Media playlist URLs
+/// are returned as is
+async fn media_playlist_url(url: &str, base: &Url, headers: &HeaderMap) -> Result<(String, bool)> {
+    let text = download_text(url, headers.clone()).await?;
+    if !is_master_playlist(&text) {
+        return Ok((url.to_string(), false));
+    }
+    let master = parse_master_playlist(&text, base)?;
+    let media = match select_audio_playlist(&master) {
+        Some(media) => (media, false),
+        None => {
+            let media = select_muxed_playlist(&master).ok_or_else(|| {
+                MusicFreeError::ExtractionFailed("HLS master playlist has no AAC audio".to_string())
+            })?;
+            (media, true)
+        }
+    };
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] HLS master playlist -> {} (muxed={})",
+        media.0, media.1
+    );
+
+    Ok(media)
+}
+
+/// Parse an HLS media playlist, resolving URIs against `base`
+pub fn parse_media_playlist(text: &str, base: &Url) -> Result<MediaPlaylist> {
+    if !text.trim_start().starts_with("#EXTM3U") {
+        return Err(MusicFreeError::ParseError(
+            "Not an HLS playlist (missing #EXTM3U)".to_string(),
+        ));
+    }
+
+    let mut playlist = MediaPlaylist::default();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(v) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            playlist.media_sequence = v.trim().parse().unwrap_or(0);
+        } else if let Some(v) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            playlist.target_duration = v.trim().parse().unwrap_or(0.0);
+        } else if let Some(v) = line.strip_prefix("#EXT-X-MAP:") {
+            let uri = v
+                .split(',')
+                .find_map(|attr| attr.trim().strip_prefix("URI="))
+                .map(|uri| uri.trim_matches('"'));
+            if let Some(uri) = uri {
+                playlist.map = Some(resolve(base, uri)?);
+            }
+        } else if line == "#EXT-X-ENDLIST" {
+            playlist.ended = true;
+        } else if !line.starts_with('#') {
+            playlist.segments.push(resolve(base, line)?);
+        }
+    }
+    Ok(playlist)
+}
+
+/// Record a live HLS playlist into `writer` until the playlist ends,
+/// disappears, or `deadline` passes. Returns the number of bytes written.
+///
+/// Master playlists are recorded from their audio rendition, see
+/// `select_audio_playlist`, or as ADTS demuxed from their smallest muxed
+/// MPEG-TS variant, see `select_muxed_playlist`.
+pub async fn record_hls(
+    url: &str,
+    headers: HeaderMap,
+    deadline: Option<Instant>,
+    writer: &mut (dyn Write + Send),
+) -> Result<u64> {
+    let parse_url = |url: &str| {
+        Url::parse(url)
+            .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid HLS URL {url}: {e}")))
+    };
+    let (url, muxed) = &media_playlist_url(url, &parse_url(url)?, &headers).await?;
+    let base = parse_url(url)?;
+    let mut demuxer = muxed.then(AdtsDemuxer::new);
+    let is_over = || deadline.is_some_and(|d| Instant::now() >= d);
+
+    let mut written = 0;
+    let mut last_sequence = None;
+    let mut wrote_map = false;
+
+    while !is_over() {
+        let text = match download_text(url, headers.clone()).await {
+            Ok(text) => text,
+            // Live playlists are removed once the stream is over
+            Err(MusicFreeError::HttpError { status: 404, .. }) if written > 0 => break,
+            Err(e) => return Err(e),
+        };
+        let playlist = parse_media_playlist(&text, &base)?;
+
+        if demuxer.is_some() && playlist.map.is_some() {
+            return Err(MusicFreeError::ExtractionFailed(
+                "Muxed fMP4 HLS variants are not supported".to_string(),
+            ));
+        }
+        if !wrote_map && let Some(map) = &playlist.map {
+            let data = download_single_chunk(map, &headers).await?;
+            writer.write_all(&data)?;
+            written += data.len() as u64;
+            wrote_map = true;
+        }
+
+        for (index, segment) in playlist.segments.iter().enumerate() {
+            let sequence = playlist.media_sequence + index as u64;
+            if last_sequence.is_some_and(|last| sequence <= last) {
+                continue;
+            }
+            if is_over() {
+                break;
+            }
+
+            let mut data = download_single_chunk(segment, &headers).await?;
+            if let Some(demuxer) = &mut demuxer {
+                data = demuxer.push(&data)?;
+            }
+            writer.write_all(&data)?;
+            written += data.len() as u64;
+            last_sequence = Some(sequence);
+        }
+
+        if playlist.ended {
+            break;
+        }
+
+        // A negative or NaN target duration polls at the minimum interval
+        let interval = Duration::try_from_secs_f64(playlist.target_duration / 2.0)
+            .unwrap_or(MIN_POLL_INTERVAL);
+        tokio::time::sleep(interval.max(MIN_POLL_INTERVAL)).await;
+    }
+
+    if let Some(demuxer) = &mut demuxer {
+        let data = demuxer.finish();
+        writer.write_all(&data)?;
+        written += data.len() as u64;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A YouTube live master playlist, the variants mux AAC with H.264
+    const YOUTUBE_MASTER: &str = r#"#EXTM3U
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-STREAM-INF:BANDWIDTH=290288,CODECS="mp4a.40.5,avc1.42c00b",RESOLUTION=256x144,FRAME-RATE=15,VIDEO-RANGE=SDR,CLOSED-CAPTIONS=NONE
+https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1714590000/id/jfKfPfyJRdk.2/itag/91/source/yt_live_broadcast/playlist/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=546239,CODECS="mp4a.40.5,avc1.4d4015",RESOLUTION=426x240,FRAME-RATE=30,VIDEO-RANGE=SDR,CLOSED-CAPTIONS=NONE
+https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1714590000/id/jfKfPfyJRdk.2/itag/92/source/yt_live_broadcast/playlist/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=1209199,CODECS="mp4a.40.2,avc1.4d401e",RESOLUTION=640x360,FRAME-RATE=30,VIDEO-RANGE=SDR,CLOSED-CAPTIONS=NONE
+https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1714590000/id/jfKfPfyJRdk.2/itag/93/source/yt_live_broadcast/playlist/index.m3u8
+"#;
+
+    /// A master playlist with separate audio renditions, as in Apple's
+    /// examples
+    const RENDITIONS: &str = r#"#EXTM3U
+#EXT-X-VERSION:6
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID="aud1",LANGUAGE="es",NAME="Español",AUTOSELECT=YES,DEFAULT=NO,CHANNELS="2",URI="a2/prog_index.m3u8"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID="aud1",LANGUAGE="en",NAME="English",AUTOSELECT=YES,DEFAULT=YES,CHANNELS="2",URI="a1/prog_index.m3u8"
+#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID="sub1",LANGUAGE="en",NAME="English",DEFAULT=YES,URI="s1/en/prog_index.m3u8"
+#EXT-X-STREAM-INF:AVERAGE-BANDWIDTH=2168183,BANDWIDTH=2177116,CODECS="avc1.640020,mp4a.40.2",RESOLUTION=960x540,FRAME-RATE=60.000,AUDIO="aud1",SUBTITLES="sub1"
+v5/prog_index.m3u8
+"#;
+
+    fn base() -> Url {
+        Url::parse("https://live.example.com/stream/master.m3u8").unwrap()
+    }
+
+    #[test]
+    fn parses_quoted_attribute_lists() {
+        let attributes = parse_attributes(
+            r#"BANDWIDTH=1280000,CODECS="avc1.4d401e,mp4a.40.2",NAME="a=b",DEFAULT=YES"#,
+        );
+        assert_eq!(
+            attributes,
+            [
+                ("BANDWIDTH", "1280000"),
+                ("CODECS", "avc1.4d401e,mp4a.40.2"),
+                ("NAME", "a=b"),
+                ("DEFAULT", "YES"),
+            ]
+        );
+        assert_eq!(
+            parse_attributes(r#"URI="unterminated"#),
+            [("URI", "unterminated")]
+        );
+        assert!(parse_attributes("").is_empty());
+    }
+
+    #[test]
+    fn parses_youtube_master_playlist() {
+        assert!(is_master_playlist(YOUTUBE_MASTER));
+        let master = parse_master_playlist(YOUTUBE_MASTER, &base()).unwrap();
+        assert!(master.audio.is_empty());
+        let variants: Vec<(u64, &str)> = master
+            .variants
+            .iter()
+            .map(|v| (v.bandwidth, v.codecs.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            variants,
+            [
+                (290_288, "mp4a.40.5,avc1.42c00b"),
+                (546_239, "mp4a.40.5,avc1.4d4015"),
+                (1_209_199, "mp4a.40.2,avc1.4d401e"),
+            ]
+        );
+        assert!(master.variants[0].uri.contains("/itag/91/"));
+    }
+
+    #[test]
+    fn parses_audio_renditions() {
+        let master = parse_master_playlist(RENDITIONS, &base()).unwrap();
+        let renditions: Vec<(Option<&str>, bool)> = master
+            .audio
+            .iter()
+            .map(|r| (r.uri.as_deref(), r.default))
+            .collect();
+        assert_eq!(
+            renditions,
+            [
+                (
+                    Some("https://live.example.com/stream/a2/prog_index.m3u8"),
+                    false
+                ),
+                (
+                    Some("https://live.example.com/stream/a1/prog_index.m3u8"),
+                    true
+                ),
+            ]
+        );
+        assert_eq!(
+            master.variants[0].uri,
+            "https://live.example.com/stream/v5/prog_index.m3u8"
+        );
+    }
+
+    #[test]
+    fn selects_the_default_audio_rendition() {
+        let master = parse_master_playlist(RENDITIONS, &base()).unwrap();
+        assert_eq!(
+            select_audio_playlist(&master).as_deref(),
+            Some("https://live.example.com/stream/a1/prog_index.m3u8")
+        );
+    }
+
+    #[test]
+    fn selects_the_best_audio_only_variant() {
+        let text = r#"#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=800000,CODECS="avc1.4d401e,mp4a.40.2"
+video.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS="mp4a.40.5"
+audio/low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=128000,CODECS="mp4a.40.2"
+audio/high.m3u8
+"#;
+        let master = parse_master_playlist(text, &base()).unwrap();
+        assert_eq!(
+            select_audio_playlist(&master).as_deref(),
+            Some("https://live.example.com/stream/audio/high.m3u8")
+        );
+    }
+
+    #[test]
+    fn records_the_smallest_muxed_variant() {
+        let master = parse_master_playlist(YOUTUBE_MASTER, &base()).unwrap();
+        assert_eq!(select_audio_playlist(&master), None);
+        assert!(select_muxed_playlist(&master).is_some_and(|uri| uri.contains("/itag/91/")));
+
+        let renditions = parse_master_playlist(RENDITIONS, &base()).unwrap();
+        assert_eq!(
+            select_muxed_playlist(&renditions).as_deref(),
+            Some("https://live.example.com/stream/v5/prog_index.m3u8")
+        );
+    }
+
+    #[test]
+    fn parses_media_playlist() {
+        let text = r#"#EXTM3U
+#EXT-X-VERSION:7
+#EXT-X-TARGETDURATION:6
+#EXT-X-MEDIA-SEQUENCE:2680
+#EXT-X-MAP:URI="init.mp4"
+#EXTINF:6.000,
+seg2680.m4s
+#EXTINF:5.995,
+https://cdn.example.com/seg2681.m4s
+#EXT-X-ENDLIST
+"#;
+        assert!(!is_master_playlist(text));
+        let playlist = parse_media_playlist(text, &base()).unwrap();
+        assert_eq!(playlist.media_sequence, 2680);
+        assert_eq!(playlist.target_duration, 6.0);
+        assert_eq!(
+            playlist.map.as_deref(),
+            Some("https://live.example.com/stream/init.mp4")
+        );
+        assert_eq!(
+            playlist.segments,
+            [
+                "https://live.example.com/stream/seg2680.m4s",
+                "https://cdn.example.com/seg2681.m4s",
+            ]
+        );
+        assert!(playlist.ended);
+    }
+
+    #[test]
+    fn rejects_non_playlists() {
+        assert!(parse_media_playlist("<html></html>", &base()).is_err());
+    }
+}
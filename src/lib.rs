@@ -1,8 +1,16 @@
+pub mod cookies;
 pub mod core;
+#[cfg(feature = "youtube")]
+mod dash;
 mod download;
 pub mod error;
 pub mod file;
 pub mod headers;
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+mod hls;
+pub mod subtitle;
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+mod ts;
 
 #[cfg(feature = "bilibili")]
 pub mod bilibili;
@@ -1,4 +1,5 @@
 use clap::Parser;
+use musicfree::core::IntoEnumIterator;
 use musicfree::extract;
 use std::fs;
 use std::path::Path;
@@ -22,11 +23,18 @@ const VERSION: &str = const_str::concat!(CARGO_PKG_VERSION, " ", GIT_HASH);
       musicfree -d ./music https://example.com/video     # Download to directory\n\
       musicfree -o song.mp3 https://example.com/video    # Custom filename\n\
       musicfree -c https://example.com/video            # Download audio + cover\n\
-      musicfree -c --cover-dir ./covers https://example.com/video  # Custom cover dir"
+      musicfree -c --cover-dir ./covers https://example.com/video  # Custom cover dir\n\
+      musicfree --lyrics --lyrics-lang zh,en https://example.com/video  # Save lyrics as .lrc\n\
+      musicfree --lyrics --lyrics-format srt https://example.com/video  # Save subtitles as .srt\n\
+      musicfree \"bilisearch:5:keyword\"                # Download top 5 Bilibili search results\n\
+      musicfree \"ytsearch:5:keyword\"                  # Download top 5 YouTube search results\n\
+      musicfree --record-duration 600 https://live.bilibili.com/123  # Record 10 min of a live stream"
 )]
 struct Args {
     /// URL to extract audio from
-    #[arg(help = "URL to extract audio from (supports Bilibili, YouTube)")]
+    #[arg(
+        help = "URL to extract audio from (supports Bilibili, YouTube), or a search such as ytsearch:[N:]keyword"
+    )]
     url: String,
 
     /// Download to specified directory
@@ -91,6 +99,131 @@ struct Args {
         help = "Select specific items from playlist to download (e.g., \"1,3,5\" or \"2-4\" or \"1,3-5,7\")"
     )]
     playlist_items: Option<String>,
+
+    /// Stop fetching playlists after this many items
+    #[cfg(any(feature = "youtube", feature = "bilibili"))]
+    #[arg(
+        long = "playlist-limit",
+        help = "Stop fetching YouTube playlists and Bilibili uploader, series and collection lists after this many items (default: whole playlist)"
+    )]
+    playlist_limit: Option<usize>,
+
+    /// Number of videos to take from a YouTube Mix (RD...) playlist
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "mix-limit",
+        help = "Number of videos to take from a YouTube Mix (RD...) playlist (default: 50)"
+    )]
+    mix_limit: Option<usize>,
+
+    /// Stop recording live streams after this many seconds
+    #[arg(
+        long = "record-duration",
+        help = "Stop recording live streams after this many seconds (default: until the stream ends)"
+    )]
+    record_duration: Option<u64>,
+
+    /// Save lyrics or subtitles next to the audio
+    #[arg(
+        long = "lyrics",
+        help = "Save lyrics or subtitles next to the audio (.lrc, see --lyrics-format)"
+    )]
+    lyrics: bool,
+
+    /// Preferred subtitle languages, in order (e.g., "zh-CN,en")
+    #[arg(
+        long = "lyrics-lang",
+        help = "Preferred subtitle languages, in order (e.g., \"zh-CN,en\"; default: first available; YouTube translates when none matches)"
+    )]
+    lyrics_lang: Option<String>,
+
+    /// Lyrics file format
+    #[arg(
+        long = "lyrics-format",
+        value_parser = ["lrc", "srt"],
+        default_value = "lrc",
+        help = "Lyrics file format: lrc, or srt for timed subtitles"
+    )]
+    lyrics_format: String,
+
+    /// Innertube clients to try in order when downloading from YouTube
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "youtube-clients",
+        value_delimiter = ',',
+        help = "YouTube clients to try in order (e.g., \"ios,android_vr\"; available: android_vr, ios, tv_embedded, web_safari, mweb, web_music)"
+    )]
+    youtube_clients: Vec<String>,
+
+    /// Directory caching YouTube player.js files
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "player-cache-dir",
+        help = "Directory caching YouTube player.js files (default: musicfree/player in the user cache directory)"
+    )]
+    player_cache_dir: Option<std::path::PathBuf>,
+
+    /// PO token sent with YouTube requests
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "po-token",
+        conflicts_with_all = ["po_token_file", "po_token_command"],
+        help = "PO token sent with YouTube player requests and stream URLs"
+    )]
+    po_token: Option<String>,
+
+    /// File holding the PO token, re-read for every request
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "po-token-file",
+        conflicts_with = "po_token_command",
+        help = "File holding the YouTube PO token, re-read for every request"
+    )]
+    po_token_file: Option<std::path::PathBuf>,
+
+    /// Command printing a PO token
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "po-token-command",
+        help = "Command printing a YouTube PO token on stdout, quoted like a shell command (request in MUSICFREE_POT_* environment variables)"
+    )]
+    po_token_command: Option<String>,
+
+    /// Netscape cookies.txt with the cookies of a logged-in session
+    #[arg(
+        long = "cookies",
+        help = "Netscape cookies.txt to send, e.g. a logged-in YouTube session for age-restricted videos"
+    )]
+    cookies: Option<std::path::PathBuf>,
+
+    /// YouTube audio format selector
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "youtube-format",
+        help = "YouTube audio format selector (e.g., \"bestaudio[acodec=opus]/140\", \"lang=ja\", \"abr<=128\"; default: 140/bestaudio/best)"
+    )]
+    youtube_format: Option<musicfree::youtube::FormatSelector>,
+
+    /// YouTube audio quality, picks the default format selector
+    #[cfg(feature = "youtube")]
+    #[arg(
+        long = "youtube-quality",
+        value_parser = ["low", "standard", "high", "super"],
+        conflicts_with = "youtube_format",
+        help = "YouTube audio quality when no --youtube-format is given: low, standard, high (AAC) or super (best, usually Opus)"
+    )]
+    youtube_quality: Option<String>,
+}
+
+#[cfg(feature = "youtube")]
+fn parse_quality(quality: &str) -> Option<musicfree::core::Quality> {
+    match quality {
+        "low" => Some(musicfree::core::Quality::Low),
+        "standard" => Some(musicfree::core::Quality::Standard),
+        "high" => Some(musicfree::core::Quality::High),
+        "super" => Some(musicfree::core::Quality::Super),
+        _ => None,
+    }
 }
 
 fn parse_format(format_str: &str) -> Option<musicfree::core::AudioFormat> {
@@ -139,6 +272,23 @@ fn list_available_formats(audios: &[musicfree::core::Audio]) {
     }
 }
 
+/// List nested playlists, e.g. a channel's playlists tab
+fn list_nested_playlists(playlists: &[musicfree::core::Playlist]) {
+    println!("Playlists:");
+    println!();
+
+    for (index, playlist) in playlists.iter().enumerate() {
+        println!(
+            "[{}]: {}",
+            index + 1,
+            playlist.title.as_deref().unwrap_or("Unknown")
+        );
+        if let Some(url) = &playlist.download_url {
+            println!("  URL: {}", url);
+        }
+    }
+}
+
 fn format_duration(seconds: u64) -> String {
     let minutes = seconds / 60;
     let secs = seconds % 60;
@@ -295,10 +445,39 @@ fn display_audio_info(
         println!("    Format: {}", format_display_format(&audio.format));
         println!("    URL: {}", audio.download_url);
 
+        if audio.live {
+            println!("    Live: yes");
+        }
+
+        if let Some(artist) = &audio.artist {
+            println!("    Artist: {}", artist);
+        }
+
+        if let Some(album) = &audio.album {
+            println!("    Album: {}", album);
+        }
+
+        if let Some(track) = audio.track {
+            println!("    Track: {}", track);
+        }
+
         if let Some(duration) = audio.duration {
             println!("    Duration: {}", format_duration(duration));
         }
 
+        if let Some(lyric_url) = &audio.lyric_url {
+            println!("    Lyrics: {}", lyric_url);
+        }
+
+        if !audio.subtitles.is_empty() {
+            let subtitles: Vec<_> = audio
+                .subtitles
+                .iter()
+                .map(|s| format!("{} ({})", s.lang, s.name))
+                .collect();
+            println!("    Subtitles: {}", subtitles.join(", "));
+        }
+
         if let Some(cover_url) = &audio.cover {
             println!("    Cover: {}", cover_url);
         }
@@ -308,6 +487,18 @@ fn display_audio_info(
 }
 
 fn get_filename(audio: &musicfree::core::Audio, output_name: &Option<String>) -> String {
+    get_filename_as(audio, audio.format.as_ref(), output_name)
+}
+
+/// File name of `audio` saved as `format`, MP3 when the format is unknown
+fn get_filename_as(
+    audio: &musicfree::core::Audio,
+    format: Option<&musicfree::core::AudioFormat>,
+    output_name: &Option<String>,
+) -> String {
+    let extension = format
+        .unwrap_or(&musicfree::core::AudioFormat::Mp3)
+        .extension();
     if let Some(name) = output_name {
         // If output name is provided, use it without changing extension
         let base_name = Path::new(name)
@@ -315,21 +506,43 @@ fn get_filename(audio: &musicfree::core::Audio, output_name: &Option<String>) ->
             .and_then(|s| s.to_str())
             .unwrap_or("audio");
 
-        let extension = audio
-            .format
-            .as_ref()
-            .unwrap_or(&musicfree::core::AudioFormat::Mp3)
-            .extension();
-
         format!("{}{}", base_name, extension)
     } else {
         // Use sanitized title + extension
-        sanitize_filename::sanitize(&audio.title)
-            + audio
-                .format
-                .as_ref()
-                .unwrap_or(&musicfree::core::AudioFormat::Mp3)
-                .extension()
+        sanitize_filename::sanitize(&audio.title) + extension
+    }
+}
+
+/// Format a download is saved as: the container found in the data, named
+/// as the extractor announced it when both agree
+fn saved_format(
+    audio: &musicfree::core::Audio,
+    data: &[u8],
+) -> Option<musicfree::core::AudioFormat> {
+    match (
+        musicfree::core::AudioFormat::from_signature(data),
+        &audio.format,
+    ) {
+        (Some(found), Some(format)) if found.same_container(format) => Some(format.clone()),
+        (Some(found), _) => Some(found),
+        (None, format) => format.clone(),
+    }
+}
+
+/// Saved file of `audio` in `dir`, entries without a known format may have
+/// been saved with any extension
+fn existing_file(
+    audio: &musicfree::core::Audio,
+    dir: &Path,
+    output_name: &Option<String>,
+) -> Option<std::path::PathBuf> {
+    if audio.format.is_some() {
+        let path = dir.join(get_filename(audio, output_name));
+        path.exists().then_some(path)
+    } else {
+        musicfree::core::AudioFormat::iter()
+            .map(|format| dir.join(get_filename_as(audio, Some(&format), output_name)))
+            .find(|path| path.exists())
     }
 }
 
@@ -337,36 +550,68 @@ async fn download_audio(
     audio: &musicfree::core::Audio,
     output_dir: &Option<String>,
     output_name: &Option<String>,
+    record_options: &musicfree::core::RecordOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let filename = get_filename(audio, output_name);
-    let base_path = if let Some(dir) = output_dir {
+    let dir = if let Some(dir) = output_dir {
         // Create directory if it doesn't exist
         fs::create_dir_all(dir)?;
-        Path::new(dir).join(&filename)
+        Path::new(dir)
     } else {
-        Path::new(".").join(&filename)
+        Path::new(".")
     };
+    let base_path = dir.join(&filename);
 
-    // Check if file already exists
-    if base_path.exists() {
-        println!("⏭ File already exists, skipping: {}", base_path.display());
+    if let Some(path) = existing_file(audio, dir, output_name) {
+        println!("⏭ File already exists, skipping: {}", path.display());
         return Ok(());
     }
 
-    // Find appropriate extractor and download binary data
-    match audio
-        .platform
-        .extractor()
-        .download(&audio.download_url)
-        .await
-    {
-        Ok(bin) => match fs::write(&base_path, bin) {
-            Ok(_) => println!("✓ Saved to: {}", base_path.display()),
+    // Live streams are written to a .part file while recording, renamed
+    // once the recording ends so a failed one is not taken as saved
+    if audio.live {
+        println!("Recording live stream...");
+        let mut part_path = base_path.clone().into_os_string();
+        part_path.push(".part");
+        let part_path = std::path::PathBuf::from(part_path);
+        let mut file = fs::File::create(&part_path)?;
+        match audio
+            .platform
+            .extractor()
+            .record(&audio.download_url, record_options, &mut file)
+            .await
+        {
+            Ok(size) => {
+                drop(file);
+                fs::rename(&part_path, &base_path)?;
+                println!("✓ Recorded {} bytes to: {}", size, base_path.display());
+            }
             Err(e) => {
-                eprintln!("✗ Error saving file: {}", e);
+                eprintln!(
+                    "✗ Recording failed: {:?}, partial recording kept at: {}",
+                    e,
+                    part_path.display()
+                );
                 return Err(e.into());
             }
-        },
+        }
+        return Ok(());
+    }
+
+    // Find appropriate extractor and download binary data
+    match audio.platform.extractor().download_audio(audio).await {
+        Ok(bin) => {
+            // Playlist entries don't know the format the extractor picks
+            let format = saved_format(audio, &bin);
+            let path = dir.join(get_filename_as(audio, format.as_ref(), output_name));
+            match fs::write(&path, bin) {
+                Ok(_) => println!("✓ Saved to: {}", path.display()),
+                Err(e) => {
+                    eprintln!("✗ Error saving file: {}", e);
+                    return Err(e.into());
+                }
+            }
+        }
         Err(e) => {
             eprintln!("✗ No binary data available for download: {:?}", e);
             return Err(e.into());
@@ -438,10 +683,165 @@ async fn download_cover(
     Ok(())
 }
 
+async fn download_lyrics(
+    audio: &musicfree::core::Audio,
+    output_dir: &Option<String>,
+    output_name: &Option<String>,
+    langs: &[String],
+    lyrics_format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let filename = Path::new(&get_filename(audio, output_name)).with_extension(lyrics_format);
+    let base_path = if let Some(dir) = output_dir {
+        fs::create_dir_all(dir)?;
+        Path::new(dir).join(&filename)
+    } else {
+        Path::new(".").join(&filename)
+    };
+
+    if base_path.exists() {
+        println!(
+            "⏭ Lyrics file already exists, skipping: {}",
+            base_path.display()
+        );
+        return Ok(());
+    }
+
+    let lyrics = match lyrics_format {
+        "srt" => musicfree::subtitle::download_srt(audio, langs).await,
+        _ => musicfree::subtitle::download_lyrics(audio, langs).await,
+    };
+    match lyrics {
+        Ok(Some(lrc)) => match fs::write(&base_path, lrc) {
+            Ok(_) => println!("✓ Lyrics saved to: {}", base_path.display()),
+            Err(e) => {
+                eprintln!("✗ Error saving lyrics: {}", e);
+                return Err(e.into());
+            }
+        },
+        Ok(None) => println!("✗ No lyrics available for this audio"),
+        Err(e) => {
+            eprintln!("✗ Failed to download lyrics: {:?}", e);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of YouTube videos whose stream URLs are resolved together, small
+/// enough that the URLs don't expire before being downloaded
+#[cfg(feature = "ytdlp-ejs")]
+const YOUTUBE_RESOLVE_BATCH: usize = 50;
+
+/// Resolve the stream URLs of the YouTube videos of `audios` together, so
+/// their challenges are solved once per player instead of once per video.
+/// Videos already saved are left out
+#[cfg(feature = "ytdlp-ejs")]
+async fn resolve_youtube_urls(
+    audios: &[musicfree::core::Audio],
+    output_dir: &Option<String>,
+    output_name: &Option<String>,
+) {
+    let dir = Path::new(output_dir.as_deref().unwrap_or("."));
+    let mut videos: std::collections::HashMap<Option<u64>, Vec<String>> = Default::default();
+    for audio in audios
+        .iter()
+        .filter(|audio| !audio.live && existing_file(audio, dir, output_name).is_none())
+    {
+        if let Some(musicfree::core::Locator::Youtube { video_id, itag }) = &audio.locator {
+            videos.entry(*itag).or_default().push(video_id.clone());
+        }
+    }
+
+    for (itag, video_ids) in videos {
+        // A single video gains nothing over the regular download path
+        if video_ids.len() < 2 {
+            continue;
+        }
+        match musicfree::youtube::resolve_urls(&video_ids, itag).await {
+            Ok(count) => println!("Resolved {} YouTube stream URL(s)", count),
+            Err(e) => eprintln!("Warning: Failed to resolve YouTube stream URLs: {}", e),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
 
+    if let Some(path) = &args.cookies {
+        match musicfree::cookies::load_cookies_txt(path) {
+            Ok(count) => println!("Loaded {} cookie(s) from {}", count, path.display()),
+            Err(e) => {
+                eprintln!(
+                    "Error: Failed to load cookies from {}: {}",
+                    path.display(),
+                    e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    #[cfg(feature = "bilibili")]
+    musicfree::bilibili::set_config(musicfree::bilibili::BilibiliConfig {
+        playlist_limit: args.playlist_limit,
+    });
+
+    #[cfg(feature = "youtube")]
+    {
+        for name in &args.youtube_clients {
+            match musicfree::youtube::client_profile(name) {
+                None => {
+                    eprintln!("Error: Unknown YouTube client: {}", name);
+                    std::process::exit(1);
+                }
+                Some(profile) if !profile.is_supported() => {
+                    eprintln!(
+                        "Error: YouTube client {} needs player.js, this build has no JS solver",
+                        name
+                    );
+                    std::process::exit(1);
+                }
+                Some(_) => {}
+            }
+        }
+
+        let po_token: Option<std::sync::Arc<dyn musicfree::youtube::PoTokenProvider>> =
+            if let Some(token) = &args.po_token {
+                Some(std::sync::Arc::new(
+                    musicfree::youtube::StaticPoTokenProvider::new(token.clone()),
+                ))
+            } else if let Some(path) = &args.po_token_file {
+                Some(std::sync::Arc::new(
+                    musicfree::youtube::StaticPoTokenProvider::from_file(path.clone()),
+                ))
+            } else if let Some(command) = &args.po_token_command {
+                match musicfree::youtube::CommandPoTokenProvider::from_command_line(command) {
+                    Some(provider) => Some(std::sync::Arc::new(provider)),
+                    None => {
+                        eprintln!("Error: Empty or unbalanced quotes in --po-token-command");
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                None
+            };
+
+        musicfree::youtube::set_config(musicfree::youtube::YoutubeConfig {
+            playlist_limit: args.playlist_limit,
+            mix_limit: args.mix_limit,
+            progress: Some(std::sync::Arc::new(|count| {
+                eprintln!("Fetched {} playlist items...", count)
+            })),
+            clients: args.youtube_clients.clone(),
+            player_cache_dir: args.player_cache_dir.clone(),
+            po_token,
+            format: args.youtube_format.clone(),
+            quality: args.youtube_quality.as_deref().and_then(parse_quality),
+        });
+    }
+
     println!("Extracting audio from: {}", args.url);
 
     // Phase 1: Extract and display audio information
@@ -460,6 +860,11 @@ async fn main() {
     let playlist_download_url = playlist.download_url.clone();
     let mut audios = playlist.audios;
 
+    if audios.is_empty() && !playlist.playlists.is_empty() {
+        list_nested_playlists(&playlist.playlists);
+        return;
+    }
+
     if audios.is_empty() {
         println!("No audio files found.");
         return;
@@ -532,23 +937,60 @@ async fn main() {
     println!("Downloading audio files...");
     println!();
 
+    let record_options = musicfree::core::RecordOptions {
+        duration: args.record_duration.map(std::time::Duration::from_secs),
+    };
+
+    let lyrics_langs: Vec<String> = args
+        .lyrics_lang
+        .as_deref()
+        .map(|langs| {
+            langs
+                .split(',')
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
     let audios_len = audios.len();
-    for (index, audio) in audios.into_iter().enumerate() {
+    for (index, audio) in audios.iter().enumerate() {
+        #[cfg(feature = "ytdlp-ejs")]
+        if index % YOUTUBE_RESOLVE_BATCH == 0 {
+            let end = (index + YOUTUBE_RESOLVE_BATCH).min(audios_len);
+            resolve_youtube_urls(&audios[index..end], &args.output_dir, &args.output_name).await;
+        }
+
         println!("Downloading [{}]: {}", index + 1, audio.title);
 
-        if let Err(e) = download_audio(&audio, &args.output_dir, &args.output_name).await {
+        if let Err(e) =
+            download_audio(audio, &args.output_dir, &args.output_name, &record_options).await
+        {
             eprintln!("Failed to download audio [{}]: {}", index + 1, e);
             std::process::exit(1);
         }
 
         // Download cover if requested and available
         if args.download_cover
-            && let Err(e) = download_cover(&audio, &args.cover_dir, &args.output_name).await
+            && let Err(e) = download_cover(audio, &args.cover_dir, &args.output_name).await
         {
             eprintln!("Failed to download cover for [{}]: {}", index + 1, e);
             // Don't exit on cover download failure, just continue
         }
 
+        if args.lyrics
+            && let Err(e) = download_lyrics(
+                audio,
+                &args.output_dir,
+                &args.output_name,
+                &lyrics_langs,
+                &args.lyrics_format,
+            )
+            .await
+        {
+            eprintln!("Failed to download lyrics for [{}]: {}", index + 1, e);
+        }
+
         if index < audios_len - 1 {
             println!();
         }
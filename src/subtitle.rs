@@ -0,0 +1,226 @@
+//! Timed subtitles and their conversion to LRC lyrics or SRT.
+
+use reqwest::header::HeaderMap;
+
+use crate::core::{Audio, Extractor, Subtitle};
+use crate::download::download_text;
+use crate::error::Result;
+
+/// A single timed subtitle line
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleLine {
+    /// Start time in seconds
+    pub start: f64,
+    /// End time in seconds
+    pub end: f64,
+    pub text: String,
+}
+
+/// Gaps longer than this get an empty LRC line so the previous line is cleared
+const LRC_GAP: f64 = 1.0;
+
+fn lrc_time(seconds: f64) -> String {
+    let centis = (seconds.max(0.0) * 100.0).round() as u64;
+    format!(
+        "[{:02}:{:02}.{:02}]",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Convert subtitle lines to LRC text
+pub fn to_lrc(lines: &[SubtitleLine]) -> String {
+    let mut lrc = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        let text = line.text.replace(['\r', '\n'], " ");
+        lrc.push_str(&format!("{}{}\n", lrc_time(line.start), text.trim()));
+
+        let next_start = lines.get(index + 1).map(|l| l.start);
+        if next_start.is_none_or(|start| start - line.end > LRC_GAP) {
+            lrc.push_str(&format!("{}\n", lrc_time(line.end)));
+        }
+    }
+    lrc
+}
+
+fn srt_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Convert subtitle lines to SRT text
+pub fn to_srt(lines: &[SubtitleLine]) -> String {
+    let mut srt = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        // A blank line ends the cue
+        let text: Vec<&str> = line
+            .text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            srt_time(line.start),
+            srt_time(line.end),
+            text.join("\n")
+        ));
+    }
+    srt
+}
+
+fn lang_matches(lang: &str, wanted: &str) -> bool {
+    let lang = lang.to_lowercase();
+    let wanted = wanted.to_lowercase();
+    // Auto-generated tracks are prefixed, e.g. `ai-zh`
+    let lang = lang.strip_prefix("ai-").unwrap_or(&lang);
+    lang == wanted || lang.starts_with(&format!("{wanted}-"))
+}
+
+/// Pick a subtitle by language preference
+///
+/// Each entry of `langs` is tried in order, an exact code wins over a
+/// prefix match (`zh` matches `zh-CN` and `ai-zh`). With no preference the
+/// first track is returned.
+pub fn select_subtitle<'a>(subtitles: &'a [Subtitle], langs: &[String]) -> Option<&'a Subtitle> {
+    if langs.is_empty() {
+        return subtitles.first();
+    }
+    langs.iter().find_map(|wanted| {
+        subtitles
+            .iter()
+            .find(|s| s.lang.eq_ignore_ascii_case(wanted))
+            .or_else(|| subtitles.iter().find(|s| lang_matches(&s.lang, wanted)))
+    })
+}
+
+/// Subtitle selected by `langs`
+///
+/// When no track matches, the first translatable track is machine
+/// translated into the first preferred language the platform can translate
+/// to.
+fn resolve_subtitle(
+    extractor: &dyn Extractor,
+    subtitles: &[Subtitle],
+    langs: &[String],
+) -> Option<Subtitle> {
+    if let Some(subtitle) = select_subtitle(subtitles, langs) {
+        return Some(subtitle.clone());
+    }
+    let source = subtitles.iter().find(|subtitle| subtitle.translatable)?;
+    langs
+        .iter()
+        .find_map(|lang| extractor.translate_subtitle(source, lang).ok())
+}
+
+/// Download the subtitle of an audio selected by `langs`, see
+/// `resolve_subtitle`. Returns `None` when the audio has no subtitles.
+pub async fn download_subtitle_lines(
+    audio: &Audio,
+    langs: &[String],
+) -> Result<Option<Vec<SubtitleLine>>> {
+    let extractor = audio.platform.extractor();
+    let subtitles = extractor.get_subtitles(audio).await?;
+    let Some(subtitle) = resolve_subtitle(extractor, &subtitles, langs) else {
+        return Ok(None);
+    };
+    let lines = extractor.download_subtitle(&subtitle).await?;
+    Ok(Some(lines))
+}
+
+/// Fetch the lyrics of an audio as LRC text
+///
+/// Uses the LRC file of `lyric_url` when the platform provides one,
+/// otherwise converts the subtitle selected by `langs`.
+/// Returns `None` when the audio has neither.
+pub async fn download_lyrics(audio: &Audio, langs: &[String]) -> Result<Option<String>> {
+    if let Some(lyric_url) = &audio.lyric_url {
+        return download_text(lyric_url, HeaderMap::new()).await.map(Some);
+    }
+
+    let lines = download_subtitle_lines(audio, langs).await?;
+    Ok(lines.map(|lines| to_lrc(&lines)))
+}
+
+/// Fetch the subtitle of an audio selected by `langs` as SRT text
+///
+/// Returns `None` when the audio has no subtitles.
+pub async fn download_srt(audio: &Audio, langs: &[String]) -> Result<Option<String>> {
+    let lines = download_subtitle_lines(audio, langs).await?;
+    Ok(lines.map(|lines| to_srt(&lines)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(start: f64, end: f64, text: &str) -> SubtitleLine {
+        SubtitleLine {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    fn subtitle(lang: &str) -> Subtitle {
+        Subtitle {
+            lang: lang.to_string(),
+            name: lang.to_string(),
+            url: format!("https://example.com/{lang}.json"),
+            translatable: false,
+        }
+    }
+
+    #[test]
+    fn converts_to_srt() {
+        let lines = [
+            line(1.36, 5.44, " We're no strangers "),
+            line(3599.9995, 3725.5, "First line\n\n Second line"),
+        ];
+        assert_eq!(
+            to_srt(&lines),
+            "1\n00:00:01,360 --> 00:00:05,440\nWe're no strangers\n\n\
+             2\n01:00:00,000 --> 01:02:05,500\nFirst line\nSecond line\n\n"
+        );
+        assert_eq!(to_srt(&[]), "");
+    }
+
+    #[test]
+    fn converts_to_lrc_clearing_after_gaps() {
+        let lines = [
+            line(1.36, 5.44, "We're no strangers"),
+            line(5.9, 8.0, "to\nlove"),
+            line(12.0, 61.234, "You know the rules"),
+        ];
+        assert_eq!(
+            to_lrc(&lines),
+            "[00:01.36]We're no strangers\n\
+             [00:05.90]to love\n\
+             [00:08.00]\n\
+             [00:12.00]You know the rules\n\
+             [01:01.23]\n"
+        );
+    }
+
+    #[test]
+    fn selects_subtitles_by_preference() {
+        let subtitles = [subtitle("ai-zh"), subtitle("zh-CN"), subtitle("en")];
+        let select = |langs: &[&str]| {
+            let langs: Vec<String> = langs.iter().map(|l| l.to_string()).collect();
+            select_subtitle(&subtitles, &langs).map(|s| s.lang.as_str())
+        };
+        assert_eq!(select(&[]), Some("ai-zh"));
+        assert_eq!(select(&["zh-cn"]), Some("zh-CN"));
+        assert_eq!(select(&["zh"]), Some("ai-zh"));
+        assert_eq!(select(&["ja", "en"]), Some("en"));
+        assert_eq!(select(&["ja"]), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Pull the AAC track out of MPEG-TS segments as ADTS.
+//!
+//! YouTube live HLS variants mux AAC with H.264 in MPEG-TS. The AAC
+//! elementary stream of a TS (`stream_type` 0x0F) is already ADTS, so the
+//! payloads of its PES packets are written out as they are. The PAT and
+//! PMT are read to find the audio PID, state is kept across segments since
+//! a PES packet may continue in the next one.
+
+use crate::error::{MusicFreeError, Result};
+
+const PACKET_SIZE: usize = 188;
+const SYNC_BYTE: u8 = 0x47;
+const PAT_PID: u16 = 0;
+/// `stream_type` of ADTS AAC in a PMT
+const STREAM_TYPE_ADTS: u8 = 0x0F;
+
+fn invalid(msg: &str) -> MusicFreeError {
+    MusicFreeError::ParseError(format!("Invalid MPEG-TS: {msg}"))
+}
+
+/// Section of a PSI payload, after its pointer field, cut to
+/// `section_length` without the CRC
+fn psi_section(payload: &[u8]) -> Result<&[u8]> {
+    let pointer = *payload.first().ok_or_else(|| invalid("empty PSI"))? as usize;
+    let section = payload
+        .get(1 + pointer..)
+        .filter(|s| s.len() >= 3)
+        .ok_or_else(|| invalid("truncated PSI"))?;
+    let section_length = ((section[1] & 0x0F) as usize) << 8 | section[2] as usize;
+    section
+        .get(..3 + section_length)
+        .and_then(|s| s.get(..s.len().checked_sub(4)?))
+        .ok_or_else(|| invalid("truncated PSI section"))
+}
+
+fn read_pid(b: &[u8]) -> u16 {
+    ((b[0] & 0x1F) as u16) << 8 | b[1] as u16
+}
+
+/// Demuxes the ADTS audio of consecutive TS segments
+#[derive(Debug, Clone, Default)]
+pub struct AdtsDemuxer {
+    pmt_pid: Option<u16>,
+    audio_pid: Option<u16>,
+    /// Payload of the audio PES packet being collected, `None` until the
+    /// first packet starting a PES is seen
+    pes: Option<Vec<u8>>,
+}
+
+impl AdtsDemuxer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Demux a segment, returning the ADTS frames of the PES packets it
+    /// completes
+    pub fn push(&mut self, ts: &[u8]) -> Result<Vec<u8>> {
+        if !ts.len().is_multiple_of(PACKET_SIZE) {
+            return Err(invalid("length is not a multiple of 188"));
+        }
+        let mut out = vec![];
+        for packet in ts.chunks_exact(PACKET_SIZE) {
+            if packet[0] != SYNC_BYTE {
+                return Err(invalid("lost packet sync"));
+            }
+            let unit_start = packet[1] & 0x40 != 0;
+            let pid = read_pid(&packet[1..3]);
+            let adaptation_field_control = (packet[3] >> 4) & 0x03;
+            if adaptation_field_control & 0x01 == 0 {
+                continue;
+            }
+            let start = if adaptation_field_control & 0x02 != 0 {
+                5 + packet[4] as usize
+            } else {
+                4
+            };
+            let Some(payload) = packet.get(start..) else {
+                return Err(invalid("adaptation field overflows the packet"));
+            };
+
+            if pid == PAT_PID && unit_start {
+                self.read_pat(payload)?;
+            } else if Some(pid) == self.pmt_pid && unit_start {
+                self.read_pmt(payload)?;
+            } else if Some(pid) == self.audio_pid {
+                self.read_audio(payload, unit_start, &mut out)?;
+            }
+        }
+        if self.pmt_pid.is_some() && self.audio_pid.is_none() {
+            return Err(MusicFreeError::AudioNotFound);
+        }
+        Ok(out)
+    }
+
+    /// ADTS frames of the last PES packet, once the stream is over
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.pes.take().unwrap_or_default()
+    }
+
+    /// Take the PMT PID of the first program
+    fn read_pat(&mut self, payload: &[u8]) -> Result<()> {
+        let section = psi_section(payload)?;
+        self.pmt_pid = section
+            .get(8..)
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .find(|program| program[0] != 0 || program[1] != 0)
+            .map(|program| read_pid(&program[2..4]));
+        Ok(())
+    }
+
+    /// Take the PID of the first ADTS AAC stream
+    fn read_pmt(&mut self, payload: &[u8]) -> Result<()> {
+        let section = psi_section(payload)?;
+        let program_info_length = section
+            .get(10..12)
+            .map(|b| ((b[0] & 0x0F) as usize) << 8 | b[1] as usize)
+            .ok_or_else(|| invalid("truncated PMT"))?;
+        let mut pos = 12 + program_info_length;
+        while let Some(entry) = section.get(pos..pos + 5) {
+            let es_info_length = ((entry[3] & 0x0F) as usize) << 8 | entry[4] as usize;
+            if entry[0] == STREAM_TYPE_ADTS {
+                self.audio_pid = Some(read_pid(&entry[1..3]));
+                return Ok(());
+            }
+            pos += 5 + es_info_length;
+        }
+        Ok(())
+    }
+
+    fn read_audio(&mut self, payload: &[u8], unit_start: bool, out: &mut Vec<u8>) -> Result<()> {
+        if !unit_start {
+            // Packets before the first PES start belong to a cut one
+            if let Some(pes) = &mut self.pes {
+                pes.extend_from_slice(payload);
+            }
+            return Ok(());
+        }
+        if let Some(pes) = self.pes.take() {
+            out.extend(pes);
+        }
+        if payload.get(..3) != Some(&[0, 0, 1]) {
+            return Err(invalid("missing PES start code"));
+        }
+        let header_data_length = *payload.get(8).ok_or_else(|| invalid("truncated PES"))?;
+        let data = payload
+            .get(9 + header_data_length as usize..)
+            .ok_or_else(|| invalid("truncated PES header"))?;
+        self.pes = Some(data.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PMT_PID: u16 = 0x100;
+    const VIDEO_PID: u16 = 0x101;
+    const AUDIO_PID: u16 = 0x102;
+
+    /// A TS packet, stuffed with an adaptation field to 188 bytes
+    fn packet(pid: u16, unit_start: bool, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![
+            SYNC_BYTE,
+            (unit_start as u8) << 6 | (pid >> 8) as u8,
+            pid as u8,
+        ];
+        let stuffing = PACKET_SIZE - 4 - payload.len();
+        if stuffing == 0 {
+            packet.push(0x10);
+        } else {
+            packet.push(0x30);
+            packet.push(stuffing as u8 - 1);
+            if stuffing > 1 {
+                packet.push(0);
+                packet.resize(4 + stuffing, 0xFF);
+            }
+        }
+        packet.extend_from_slice(payload);
+        assert_eq!(packet.len(), PACKET_SIZE);
+        packet
+    }
+
+    /// A PSI payload: pointer field, section with a dummy CRC
+    fn psi(table_id: u8, body: &[u8]) -> Vec<u8> {
+        let section_length = 5 + body.len() + 4;
+        let mut payload = vec![
+            0,
+            table_id,
+            0xB0 | (section_length >> 8) as u8,
+            section_length as u8,
+            0,
+            1,
+            0xC1,
+            0,
+            0,
+        ];
+        payload.extend_from_slice(body);
+        payload.extend_from_slice(&[0; 4]);
+        payload
+    }
+
+    fn pat() -> Vec<u8> {
+        packet(
+            PAT_PID,
+            true,
+            &psi(0, &[0, 1, 0xE0 | (PMT_PID >> 8) as u8, PMT_PID as u8]),
+        )
+    }
+
+    fn pmt(streams: &[(u8, u16)]) -> Vec<u8> {
+        let mut body = vec![0xE0 | (VIDEO_PID >> 8) as u8, VIDEO_PID as u8, 0xF0, 0];
+        for (stream_type, pid) in streams {
+            body.extend_from_slice(&[*stream_type, 0xE0 | (pid >> 8) as u8, *pid as u8, 0xF0, 0]);
+        }
+        packet(PMT_PID, true, &psi(2, &body))
+    }
+
+    /// Start of a PES packet with a PTS
+    fn pes_start(data: &[u8]) -> Vec<u8> {
+        let mut payload = vec![0, 0, 1, 0xC0, 0, 0, 0x80, 0x80, 5, 0x21, 0, 1, 0, 1];
+        payload.extend_from_slice(data);
+        payload
+    }
+
+    #[test]
+    fn extracts_audio_pes_payloads() {
+        let mut ts = pat();
+        ts.extend(pmt(&[(0x1B, VIDEO_PID), (STREAM_TYPE_ADTS, AUDIO_PID)]));
+        ts.extend(packet(VIDEO_PID, true, &pes_start(b"video")));
+        ts.extend(packet(AUDIO_PID, true, &pes_start(b"adts-1")));
+        ts.extend(packet(AUDIO_PID, false, b"+cont"));
+        ts.extend(packet(AUDIO_PID, true, &pes_start(b"adts-2")));
+
+        let mut demuxer = AdtsDemuxer::new();
+        assert_eq!(demuxer.push(&ts).unwrap(), b"adts-1+cont");
+
+        // The PES packet left open continues in the next segment
+        let mut next = pat();
+        next.extend(packet(AUDIO_PID, false, b"+more"));
+        next.extend(packet(AUDIO_PID, true, &pes_start(b"adts-3")));
+        assert_eq!(demuxer.push(&next).unwrap(), b"adts-2+more");
+        assert_eq!(demuxer.finish(), b"adts-3");
+        assert!(demuxer.finish().is_empty());
+    }
+
+    #[test]
+    fn skips_a_pes_packet_cut_before_the_first_segment() {
+        let mut ts = pat();
+        ts.extend(pmt(&[(STREAM_TYPE_ADTS, AUDIO_PID)]));
+        ts.extend(packet(AUDIO_PID, false, b"cut"));
+        ts.extend(packet(AUDIO_PID, true, &pes_start(b"adts")));
+
+        let mut demuxer = AdtsDemuxer::new();
+        assert!(demuxer.push(&ts).unwrap().is_empty());
+        assert_eq!(demuxer.finish(), b"adts");
+    }
+
+    #[test]
+    fn rejects_streams_without_adts_audio() {
+        let mut ts = pat();
+        ts.extend(pmt(&[(0x1B, VIDEO_PID)]));
+        assert!(matches!(
+            AdtsDemuxer::new().push(&ts),
+            Err(MusicFreeError::AudioNotFound)
+        ));
+
+        let mut unsynced = pat();
+        unsynced[0] = 0;
+        assert!(AdtsDemuxer::new().push(&unsynced).is_err());
+        assert!(AdtsDemuxer::new().push(&[SYNC_BYTE; 100]).is_err());
+    }
+}
@@ -2,3 +2,99 @@ pub fn get_md5(s: &str) -> String {
     let digest = md5::compute(s);
     format!("{:x}", digest)
 }
+
+#[cfg(feature = "youtube")]
+pub fn get_sha1(s: &str) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, s.as_bytes());
+    digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Parse a search input: `<prefix><query>` or `<prefix><limit>:<query>`
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+pub fn parse_search_query(
+    input: &str,
+    prefix: &str,
+    default_limit: usize,
+) -> Option<crate::core::SearchQuery> {
+    let rest = input.strip_prefix(prefix)?;
+    let (limit, query) = match rest.split_once(':') {
+        Some((limit, query)) if !limit.is_empty() && limit.bytes().all(|b| b.is_ascii_digit()) => {
+            (limit.parse().ok()?, query)
+        }
+        _ => (default_limit, rest),
+    };
+
+    let query = query.trim();
+    if query.is_empty() || limit == 0 {
+        return None;
+    }
+    Some(crate::core::SearchQuery {
+        query: query.to_string(),
+        limit,
+    })
+}
+
+/// Parse `m:ss` / `h:mm:ss` lengths shown in lists and search results
+/// into seconds
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+pub fn parse_length(length: &str) -> Option<u64> {
+    length
+        .split(':')
+        .map(|part| part.trim().parse::<u64>().ok())
+        .try_fold(0, |acc, part| Some(acc * 60 + part?))
+}
+
+/// A URL without its query string, for debug output of signed URLs
+#[cfg(debug_assertions)]
+pub fn strip_query(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+#[cfg(test)]
+#[cfg(any(feature = "bilibili", feature = "youtube"))]
+mod tests {
+    use super::*;
+    use crate::core::SearchQuery;
+
+    #[test]
+    fn parses_search_queries() {
+        let query = |query: &str, limit| {
+            Some(SearchQuery {
+                query: query.to_string(),
+                limit,
+            })
+        };
+        let cases = [
+            ("ytsearch:lofi hip hop", query("lofi hip hop", 20)),
+            ("ytsearch:5:lofi", query("lofi", 5)),
+            ("ytsearch: spaced ", query("spaced", 20)),
+            // Only a leading number is a limit
+            ("ytsearch:artist: title", query("artist: title", 20)),
+            ("ytsearch:5:", None),
+            ("ytsearch:0:lofi", None),
+            ("ytsearch:", None),
+            ("bilisearch:lofi", None),
+            ("https://www.youtube.com/results?search_query=lofi", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_search_query(input, "ytsearch:", 20),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_lengths() {
+        assert_eq!(parse_length("0:07"), Some(7));
+        assert_eq!(parse_length("4:13"), Some(253));
+        assert_eq!(parse_length("1:02:03"), Some(3723));
+        assert_eq!(parse_length("LIVE"), None);
+        assert_eq!(parse_length(""), None);
+    }
+}
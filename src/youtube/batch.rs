@@ -0,0 +1,134 @@
+//! Batch resolution of web stream URLs.
+//!
+//! Every EJS invocation starts a JS runtime and parses player.js, however
+//! few values it solves. `resolve_urls` reads the watch pages of many videos
+//! first, then solves the n / signature values of the format each would
+//! download in one invocation per player version. The resolved URLs are kept and
+//! `download_video` tries them before asking the Innertube clients.
+
+use crate::download::download_text;
+use crate::error::Result;
+use crate::youtube::core::{
+    extract_audio_formats_web, parse_player_response_from_html, parse_ytcfg, select_audio_format,
+};
+use crate::youtube::ejs::{format_challenges, solve_batch, solve_format};
+use crate::youtube::player::{PlayerJs, load_player};
+use crate::youtube::potoken::with_gvs_token;
+use crate::youtube::types::Format;
+use crate::youtube::utils::{WEB_USER_AGENT, build_watch_url};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+type ResolvedKey = (String, Option<u64>);
+
+fn resolved() -> &'static Mutex<HashMap<ResolvedKey, String>> {
+    static RESOLVED: OnceLock<Mutex<HashMap<ResolvedKey, String>>> = OnceLock::new();
+    RESOLVED.get_or_init(Default::default)
+}
+
+/// Take the URL resolved by `resolve_urls` for a video, it is only tried once
+pub(crate) fn take_resolved(video_id: &str, itag: Option<u64>) -> Option<String> {
+    resolved()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(video_id.to_string(), itag))
+}
+
+/// A video of a batch
+struct BatchVideo {
+    video_id: String,
+    selected: Format,
+    visitor_data: Option<String>,
+}
+
+/// Videos sharing a player
+struct PlayerBatch {
+    player: PlayerJs,
+    videos: Vec<BatchVideo>,
+}
+
+/// Read the watch page of a video, returning its player, the format to
+/// download and visitor data
+async fn fetch_format(
+    video_id: &str,
+    itag: Option<u64>,
+) -> Result<(PlayerJs, Format, Option<String>)> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
+    let html = download_text(&build_watch_url(video_id), headers).await?;
+
+    let player_response = parse_player_response_from_html(&html)?;
+    if let Some(e) = player_response.playability_error() {
+        return Err(e);
+    }
+    let formats = extract_audio_formats_web(&player_response)?;
+    let selected = select_audio_format(&formats, itag)?.clone();
+    let visitor_data = parse_ytcfg(&html).ok().and_then(|ytcfg| ytcfg.visitor_data);
+    Ok((load_player(&html).await?, selected, visitor_data))
+}
+
+/// Resolve the web stream URLs of many videos, solving their challenges
+/// with one EJS invocation per player version
+///
+/// `itag` selects the format as in `download_video`. Videos that can't be
+/// resolved are skipped, their download takes the usual path. Returns the
+/// number of resolved videos.
+pub async fn resolve_urls(video_ids: &[String], itag: Option<u64>) -> Result<usize> {
+    let mut batches: HashMap<String, PlayerBatch> = HashMap::new();
+    for video_id in video_ids {
+        match fetch_format(video_id, itag).await {
+            Ok((player, selected, visitor_data)) => {
+                batches
+                    .entry(player.id.clone())
+                    .or_insert_with(|| PlayerBatch {
+                        player,
+                        videos: vec![],
+                    })
+                    .videos
+                    .push(BatchVideo {
+                        video_id: video_id.clone(),
+                        selected,
+                        visitor_data,
+                    });
+            }
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("[debug] resolve_urls: skipping {video_id}: {_e}");
+            }
+        }
+    }
+
+    let mut count = 0;
+    for batch in batches.into_values() {
+        let (n, sig): (Vec<_>, Vec<_>) = batch
+            .videos
+            .iter()
+            .map(|video| format_challenges(&video.selected))
+            .unzip();
+        let n: Vec<String> = n.into_iter().flatten().collect();
+        let sig: Vec<String> = sig.into_iter().flatten().collect();
+        if let Err(_e) = solve_batch(&batch.player, &n, &sig) {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "[debug] resolve_urls: skipping player {}: {_e}",
+                batch.player.id
+            );
+            continue;
+        }
+
+        for video in batch.videos {
+            let Ok(url) = solve_format(&video.selected, &batch.player) else {
+                continue;
+            };
+            let url =
+                with_gvs_token(url, "web", &video.video_id, video.visitor_data.as_deref()).await;
+            resolved()
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert((video.video_id, itag), url);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,391 @@
+//! Caption tracks (timedtext).
+//!
+//! The player response lists creator captions and automatic speech
+//! recognition (ASR) tracks, the latter exposed with an `ai-` language
+//! prefix like other platforms' generated subtitles. Any translatable track
+//! can be machine translated by adding `tlang` to its URL. Tracks are
+//! requested as JSON3, the XML formats are parsed as well.
+
+use crate::core::Subtitle;
+use crate::download::download_text;
+use crate::error::{MusicFreeError, Result};
+use crate::subtitle::SubtitleLine;
+use crate::youtube::core::parse_player_response_from_html;
+use crate::youtube::types::{CaptionTrack, PlayerResponse};
+use crate::youtube::utils::build_watch_url;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Deserialize)]
+struct Json3 {
+    #[serde(default)]
+    events: Vec<Json3Event>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Json3Event {
+    #[serde(rename = "tStartMs", default)]
+    start_ms: u64,
+    #[serde(rename = "dDurationMs", default)]
+    duration_ms: u64,
+    /// Missing on window and style events
+    #[serde(default)]
+    segs: Vec<Json3Segment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Json3Segment {
+    #[serde(default)]
+    utf8: String,
+}
+
+/// Set a query parameter of a timedtext URL, replacing any previous value
+fn with_param(url: &str, key: &str, value: &str) -> Result<String> {
+    let mut url = Url::parse(url)
+        .map_err(|e| MusicFreeError::InvalidUrl(format!("Invalid caption URL {url}: {e}")))?;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != key)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(key, value);
+    Ok(url.to_string())
+}
+
+fn track_subtitle(track: &CaptionTrack) -> Option<Subtitle> {
+    let url = if track.base_url.starts_with('/') {
+        format!("https://www.youtube.com{}", track.base_url)
+    } else {
+        track.base_url.clone()
+    };
+    let is_asr = track.kind.as_deref() == Some("asr");
+    Some(Subtitle {
+        lang: if is_asr {
+            format!("ai-{}", track.language_code)
+        } else {
+            track.language_code.clone()
+        },
+        name: track
+            .name
+            .as_ref()
+            .map(|name| name.text())
+            .unwrap_or_else(|| track.language_code.clone()),
+        url: with_param(&url, "fmt", "json3").ok()?,
+        translatable: track.is_translatable,
+    })
+}
+
+/// Caption tracks of a player response, creator captions first
+pub fn get_captions(player_response: &PlayerResponse) -> Vec<Subtitle> {
+    let Some(captions) = &player_response.captions else {
+        return Vec::new();
+    };
+    let tracks = &captions.player_captions_tracklist_renderer.caption_tracks;
+    let (asr, creator): (Vec<_>, Vec<_>) = tracks
+        .iter()
+        .partition(|track| track.kind.as_deref() == Some("asr"));
+    creator
+        .into_iter()
+        .chain(asr)
+        .filter_map(track_subtitle)
+        .collect()
+}
+
+/// Caption tracks of a video, read from its watch page
+///
+/// Playlist, search and channel entries carry no tracks, they are looked
+/// up when lyrics are saved.
+pub async fn get_video_captions(video_id: &str) -> Result<Vec<Subtitle>> {
+    let html = download_text(&build_watch_url(video_id), HeaderMap::new()).await?;
+    let player_response = parse_player_response_from_html(&html)?;
+    Ok(get_captions(&player_response))
+}
+
+/// Machine translation of a caption track into `lang`, an error for
+/// tracks YouTube doesn't translate
+pub fn translate_caption(subtitle: &Subtitle, lang: &str) -> Result<Subtitle> {
+    if !subtitle.translatable {
+        return Err(MusicFreeError::ExtractionFailed(format!(
+            "Caption track {} can't be translated",
+            subtitle.lang
+        )));
+    }
+    Ok(Subtitle {
+        lang: lang.to_string(),
+        name: format!("{lang} (translated from {})", subtitle.name),
+        url: with_param(&subtitle.url, "tlang", lang)?,
+        // YouTube translates from the original track only
+        translatable: false,
+    })
+}
+
+fn parse_json3(text: &str) -> Result<Vec<SubtitleLine>> {
+    let json: Json3 = serde_json::from_str(text)?;
+    Ok(json
+        .events
+        .into_iter()
+        .filter_map(|event| {
+            let text: String = event.segs.iter().map(|s| s.utf8.as_str()).collect();
+            let text = text.trim();
+            (!text.is_empty()).then(|| SubtitleLine {
+                start: event.start_ms as f64 / 1000.0,
+                end: (event.start_ms + event.duration_ms) as f64 / 1000.0,
+                text: text.to_string(),
+            })
+        })
+        .collect())
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Text of an element without its child tags
+fn strip_tags(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn attribute<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {key}=\""))? + key.len() + 3;
+    tag[start..].split('"').next()
+}
+
+/// Parse the XML formats: srv3 `<p t="ms" d="ms">` or the legacy
+/// `<text start="s" dur="s">`
+fn parse_xml(text: &str) -> Vec<SubtitleLine> {
+    let (element, scale, start_key, duration_key) = if text.contains("<p ") {
+        ("p", 1000.0, "t", "d")
+    } else {
+        ("text", 1.0, "start", "dur")
+    };
+    let open = format!("<{element} ");
+    let close = format!("</{element}>");
+
+    let mut lines = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        let body_end = rest.find(&close).unwrap_or(rest.len());
+        let body = rest.get(tag_end + 1..body_end).unwrap_or_default();
+        rest = &rest[body_end..];
+
+        let number = |key| {
+            attribute(tag, key)
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0)
+                / scale
+        };
+        let mut text = unescape(&strip_tags(body));
+        // The legacy format escapes the HTML of the text once more
+        if element == "text" {
+            text = unescape(&text);
+        }
+        let text = text.trim().to_string();
+        if !text.is_empty() {
+            let start = number(start_key);
+            lines.push(SubtitleLine {
+                start,
+                end: start + number(duration_key),
+                text,
+            });
+        }
+    }
+    lines
+}
+
+/// Parse a timedtext document, JSON3 or XML
+pub fn parse_timedtext(text: &str) -> Result<Vec<SubtitleLine>> {
+    if text.trim_start().starts_with('{') {
+        parse_json3(text)
+    } else {
+        Ok(parse_xml(text))
+    }
+}
+
+/// Download a caption track
+pub async fn download_caption(url: &str) -> Result<Vec<SubtitleLine>> {
+    let text = download_text(url, HeaderMap::new()).await?;
+    // Tracks that need a PO token answer with an empty body
+    if text.trim().is_empty() {
+        return Err(MusicFreeError::ExtractionFailed(format!(
+            "Empty caption track: {url}"
+        )));
+    }
+    parse_timedtext(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(start: f64, end: f64, text: &str) -> SubtitleLine {
+        SubtitleLine {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_json3() {
+        let json = r#"{
+            "wireMagic": "pb3",
+            "pens": [{}],
+            "wsWinStyles": [{}],
+            "events": [
+                {"tStartMs": 0, "dDurationMs": 367040, "id": 1, "wpWinPosId": 1, "wsWinStyleId": 1},
+                {"tStartMs": 1360, "dDurationMs": 4080, "wWinId": 1,
+                 "segs": [{"utf8": "Never"}, {"utf8": " gonna", "tOffsetMs": 400}, {"utf8": " give you up"}]},
+                {"tStartMs": 3440, "dDurationMs": 2000, "wWinId": 1, "aAppend": 1,
+                 "segs": [{"utf8": "\n"}]},
+                {"tStartMs": 5440, "dDurationMs": 1500, "segs": [{"utf8": "  [Music]  "}]}
+            ]
+        }"#;
+        assert_eq!(
+            parse_timedtext(json).unwrap(),
+            vec![
+                line(1.36, 5.44, "Never gonna give you up"),
+                line(5.44, 6.94, "[Music]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_srv3() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+<head><ws id="0"/></head>
+<body>
+<p t="1360" d="4080"><s>We&#39;re</s><s t="400"> no strangers</s></p>
+<p t="5440" d="2000" w="1" a="1">
+</p>
+<p t="7000" d="1200">Rock &amp; roll</p>
+</body>
+</timedtext>"#;
+        assert_eq!(
+            parse_timedtext(xml).unwrap(),
+            vec![
+                line(1.36, 5.44, "We're no strangers"),
+                line(7.0, 8.2, "Rock & roll"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_legacy_timedtext() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8" ?><transcript>
+<text start="1.36" dur="4.08">We&amp;#39;re no strangers to love</text>
+<text start="5.44" dur="2.5">&amp;lt;i&amp;gt;You know the rules&amp;lt;/i&amp;gt;</text>
+<text start="8" dur="1"></text>
+</transcript>"#;
+        assert_eq!(
+            parse_timedtext(xml).unwrap(),
+            vec![
+                line(1.36, 5.44, "We're no strangers to love"),
+                line(5.44, 7.94, "<i>You know the rules</i>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_json3() {
+        assert!(parse_timedtext(r#"{"events": [{"tStartMs": "soon"}]}"#).is_err());
+    }
+
+    #[test]
+    fn orders_creator_captions_before_asr() {
+        let player_response: PlayerResponse = serde_json::from_value(serde_json::json!({
+            "videoDetails": {
+                "videoId": "dQw4w9WgXcQ",
+                "title": "title",
+            },
+            "streamingData": {},
+            "captions": {
+                "playerCaptionsTracklistRenderer": {
+                    "captionTracks": [
+                        {
+                            "baseUrl": "https://www.youtube.com/api/timedtext?v=dQw4w9WgXcQ&lang=en&kind=asr",
+                            "name": {"simpleText": "English (auto-generated)"},
+                            "languageCode": "en",
+                            "kind": "asr",
+                            "isTranslatable": true
+                        },
+                        {
+                            "baseUrl": "/api/timedtext?v=dQw4w9WgXcQ&lang=ja&fmt=srv3",
+                            "name": {"runs": [{"text": "Japanese"}]},
+                            "languageCode": "ja",
+                            "isTranslatable": false
+                        }
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+
+        let captions = get_captions(&player_response);
+        let langs: Vec<&str> = captions.iter().map(|s| s.lang.as_str()).collect();
+        assert_eq!(langs, ["ja", "ai-en"]);
+        assert_eq!(
+            captions[0].url,
+            "https://www.youtube.com/api/timedtext?v=dQw4w9WgXcQ&lang=ja&fmt=json3"
+        );
+        assert_eq!(captions[1].name, "English (auto-generated)");
+
+        assert!(translate_caption(&captions[0], "en").is_err());
+        let translated = translate_caption(&captions[1], "de").unwrap();
+        assert_eq!(translated.lang, "de");
+        assert!(translated.url.ends_with("&fmt=json3&tlang=de"));
+        assert!(!translated.translatable);
+    }
+}
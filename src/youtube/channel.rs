@@ -0,0 +1,230 @@
+//! YouTube channels.
+//!
+//! The videos, live and shorts tabs are read from the uploads playlists of
+//! the channel (`UU`, `UULV` and `UUSH` followed by the channel ID without
+//! `UC`). The playlists tab is returned as nested playlists, their videos
+//! are not extracted.
+
+use crate::download::{download_text, post_json};
+use crate::error::{MusicFreeError, Result};
+use crate::youtube::config::config;
+use crate::youtube::core::{
+    collect_pages, extract_playlist_audio, lazy_ytcfg, parse_initial_data, web_innertube_headers,
+};
+use crate::youtube::types::{
+    ChannelContinuationResponse, ChannelInitialData, ChannelPlaylistItem, InnertubeBrowseRequest,
+    InnertubeContext, YtConfig,
+};
+use crate::youtube::utils::{
+    ChannelUrl, WEB_USER_AGENT, build_channel_url, build_playlist_url, is_valid_playlist_id,
+    largest_thumbnail,
+};
+use crate::{Platform, Playlist};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use std::sync::OnceLock;
+
+async fn download_channel_page(url: &str) -> Result<String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
+    download_text(url, headers).await
+}
+
+/// Channel ID and title from the metadata of a channel page
+fn channel_metadata(data: &ChannelInitialData) -> Result<(String, String)> {
+    let renderer = &data
+        .metadata
+        .as_ref()
+        .ok_or_else(|| MusicFreeError::ConfigParseError("Channel metadata not found".to_string()))?
+        .channel_metadata_renderer;
+    Ok((renderer.external_id.clone(), renderer.title.clone()))
+}
+
+fn nested_playlist(id: String, title: String, cover: Option<String>) -> Playlist {
+    let mut playlist = Playlist::new(Some(id.clone()), Some(title), Platform::Youtube);
+    playlist.download_url = Some(build_playlist_url(&id));
+    playlist.cover = cover;
+    playlist
+}
+
+/// Collect the playlists of a page, returning the continuation token if any
+fn collect_items(items: Vec<ChannelPlaylistItem>, playlists: &mut Vec<Playlist>) -> Option<String> {
+    let mut continuation = None;
+    for item in items {
+        if let Some(rich_item) = item.rich_item_renderer {
+            continuation = collect_items(vec![*rich_item.content], playlists).or(continuation);
+        }
+        if let Some(renderer) = item.grid_playlist_renderer {
+            let cover = renderer
+                .thumbnail
+                .and_then(|t| largest_thumbnail(t.thumbnails));
+            playlists.push(nested_playlist(
+                renderer.playlist_id,
+                renderer.title.text(),
+                cover,
+            ));
+        }
+        // Lockups are also used for videos and podcasts
+        if let Some(lockup) = item.lockup_view_model
+            && (lockup.content_type == "LOCKUP_CONTENT_TYPE_PLAYLIST"
+                || is_valid_playlist_id(&lockup.content_id))
+        {
+            let title = lockup
+                .metadata
+                .map(|m| m.lockup_metadata_view_model.title.content)
+                .unwrap_or_else(|| lockup.content_id.clone());
+            let cover = lockup
+                .content_image
+                .and_then(|image| image.collection_thumbnail_view_model)
+                .and_then(|view| {
+                    largest_thumbnail(view.primary_thumbnail.thumbnail_view_model.image.sources)
+                });
+            playlists.push(nested_playlist(lockup.content_id, title, cover));
+        }
+        if let Some(renderer) = item.continuation_item_renderer {
+            continuation = Some(renderer.continuation_endpoint.continuation_command.token);
+        }
+    }
+    continuation
+}
+
+/// Items of the selected tab of a channel page
+fn selected_tab_items(data: ChannelInitialData) -> Vec<ChannelPlaylistItem> {
+    let Some(contents) = data.contents else {
+        return vec![];
+    };
+    let Some(content) = contents
+        .two_column_browse_results_renderer
+        .tabs
+        .into_iter()
+        .filter_map(|tab| tab.tab_renderer)
+        .find(|tab| tab.selected)
+        .and_then(|tab| tab.content)
+    else {
+        return vec![];
+    };
+
+    if let Some(grid) = content.rich_grid_renderer {
+        return grid.contents;
+    }
+    content
+        .section_list_renderer
+        .map(|list| list.contents)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|section| section.item_section_renderer)
+        .flat_map(|section| section.contents)
+        .filter_map(|item| item.grid_renderer)
+        .flat_map(|grid| grid.items)
+        .collect()
+}
+
+/// Fetch the next page of the playlists tab through the Innertube `browse` endpoint
+async fn fetch_channel_continuation(
+    ytcfg: &YtConfig,
+    token: String,
+) -> Result<Vec<ChannelPlaylistItem>> {
+    let api_url = format!(
+        "https://www.youtube.com/youtubei/v1/browse?key={}&prettyPrint=false",
+        ytcfg.innertube_api_key
+    );
+    let request = InnertubeBrowseRequest {
+        context: InnertubeContext {
+            client: ytcfg.innertube_context.client.clone(),
+            third_party: None,
+        },
+        browse_id: None,
+        continuation: Some(token),
+    };
+    let resp: ChannelContinuationResponse =
+        post_json(&api_url, &request, web_innertube_headers(ytcfg)?).await?;
+
+    Ok(resp
+        .on_response_received_actions
+        .into_iter()
+        .filter_map(|action| action.append_continuation_items_action)
+        .flat_map(|action| action.continuation_items)
+        .collect())
+}
+
+/// List the playlists tab of a channel
+///
+/// `YoutubeConfig::playlist_limit` bounds the number of playlists.
+async fn extract_channel_playlists(channel: &ChannelUrl) -> Result<Playlist> {
+    let page_url = format!("{}/playlists", build_channel_url(&channel.path));
+    let html = download_channel_page(&page_url).await?;
+    let data: ChannelInitialData = parse_initial_data(&html)?;
+    let (channel_id, title) = channel_metadata(&data)?;
+    let avatar = data
+        .metadata
+        .as_ref()
+        .and_then(|m| m.channel_metadata_renderer.avatar.clone())
+        .and_then(|a| largest_thumbnail(a.thumbnails));
+
+    let config = config();
+    let ytcfg = &OnceLock::new();
+    let html = &html;
+    let mut playlists = collect_pages(
+        selected_tab_items(data),
+        collect_items,
+        |token| async move { fetch_channel_continuation(lazy_ytcfg(ytcfg, html)?, token).await },
+        |count| config.is_full(count),
+        |count| config.report_progress(count),
+    )
+    .await?;
+    if let Some(limit) = config.playlist_limit {
+        playlists.truncate(limit);
+    }
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] YouTube channel {}: {} playlists",
+        channel_id,
+        playlists.len()
+    );
+
+    let mut playlist = Playlist::new(Some(channel_id), Some(title), Platform::Youtube);
+    playlist.download_url = Some(page_url);
+    playlist.cover = avatar;
+    playlist.playlists = playlists;
+    Ok(playlist)
+}
+
+/// Fails to compile when channel extraction stops being `Send`
+fn _assert_send(channel: &ChannelUrl) {
+    fn is_send<T: Send>(_: &T) {}
+    is_send(&extract_channel(channel));
+}
+
+/// Extract a channel URL
+///
+/// The videos, live and shorts tabs return the uploads of the channel, the
+/// playlists tab returns a playlist without audios whose `playlists` lists
+/// the channel's playlists.
+pub async fn extract_channel(channel: &ChannelUrl) -> Result<(Playlist, Option<usize>)> {
+    let Some(prefix) = channel.tab.uploads_prefix() else {
+        return Ok((extract_channel_playlists(channel).await?, None));
+    };
+
+    // Handles and custom URLs have to be resolved to the channel ID
+    let channel_id = match &channel.channel_id {
+        Some(id) => id.clone(),
+        None => {
+            let html = download_channel_page(&build_channel_url(&channel.path)).await?;
+            let data: ChannelInitialData = parse_initial_data(&html)?;
+            channel_metadata(&data)?.0
+        }
+    };
+    let uploads_id = channel_id
+        .strip_prefix("UC")
+        .map(|id| format!("{prefix}{id}"))
+        .ok_or_else(|| MusicFreeError::InvalidUrl(format!("Invalid channel ID: {channel_id}")))?;
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] YouTube channel {} {:?} -> {}",
+        channel_id, channel.tab, uploads_id
+    );
+
+    let html = download_text(&build_playlist_url(&uploads_id), HeaderMap::new()).await?;
+    extract_playlist_audio(&uploads_id, &html).await
+}
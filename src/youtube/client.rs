@@ -0,0 +1,437 @@
+//! Innertube client profiles and the player API download strategy.
+//!
+//! Each profile describes one YouTube app: its `context.client`, headers
+//! and user agent. Downloads ask the player API as each configured client
+//! in turn, since clients get blocked, stop returning streams or hand out
+//! URLs answering 403 independently of each other.
+
+use crate::cookies::cookie_value;
+use crate::download::download_binary_chunked;
+use crate::error::{MusicFreeError, Result};
+use crate::headers::{download_headers, with_origin, with_sec_fetch};
+use crate::utils::get_sha1;
+use crate::youtube::config::config;
+use crate::youtube::core::{extract_audio_formats_web, fetch_player, select_audio_format};
+use crate::youtube::player::{PlayerJs, load_player};
+use crate::youtube::potoken::with_gvs_token;
+use crate::youtube::types::{Format, InnertubeContext, YtConfig};
+use crate::youtube::utils::{
+    ANDROID_VR_USER_AGENT, IOS_USER_AGENT, MWEB_USER_AGENT, SAFARI_USER_AGENT, TV_USER_AGENT,
+    WEB_USER_AGENT,
+};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, ORIGIN, USER_AGENT};
+
+#[cfg(feature = "ytdlp-ejs")]
+use crate::youtube::ejs::{solve_cipher, solve_n};
+
+/// An Innertube client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientProfile {
+    /// Name used by `YoutubeConfig::clients` and the CLI
+    pub name: &'static str,
+    /// `context.client.clientName`
+    pub client_name: &'static str,
+    /// `X-YouTube-Client-Name`
+    pub client_id: &'static str,
+    pub client_version: &'static str,
+    pub user_agent: &'static str,
+    pub device_make: Option<&'static str>,
+    pub device_model: Option<&'static str>,
+    pub os_name: Option<&'static str>,
+    pub os_version: Option<&'static str>,
+    pub android_sdk_version: Option<u32>,
+    pub origin: &'static str,
+    /// Embedded player, the request names the embedding page
+    pub embedded: bool,
+    /// Streams are ciphered, so the player.js version has to be sent and
+    /// the URLs solved with it
+    pub requires_js: bool,
+    /// Accepts the logged-in session of imported cookies
+    pub supports_cookies: bool,
+}
+
+pub const ANDROID_VR: ClientProfile = ClientProfile {
+    name: "android_vr",
+    client_name: "ANDROID_VR",
+    client_id: "28",
+    client_version: "1.65.10",
+    user_agent: ANDROID_VR_USER_AGENT,
+    device_make: Some("Oculus"),
+    device_model: Some("Quest 3"),
+    os_name: Some("Android"),
+    os_version: Some("12L"),
+    android_sdk_version: Some(32),
+    origin: "https://www.youtube.com",
+    embedded: false,
+    requires_js: false,
+    supports_cookies: false,
+};
+
+pub const IOS: ClientProfile = ClientProfile {
+    name: "ios",
+    client_name: "IOS",
+    client_id: "5",
+    client_version: "20.10.4",
+    user_agent: IOS_USER_AGENT,
+    device_make: Some("Apple"),
+    device_model: Some("iPhone16,2"),
+    os_name: Some("iPhone"),
+    os_version: Some("18.3.2.22D82"),
+    android_sdk_version: None,
+    origin: "https://www.youtube.com",
+    embedded: false,
+    requires_js: false,
+    supports_cookies: false,
+};
+
+pub const TV_EMBEDDED: ClientProfile = ClientProfile {
+    name: "tv_embedded",
+    client_name: "TVHTML5_SIMPLY_EMBEDDED_PLAYER",
+    client_id: "85",
+    client_version: "2.0",
+    user_agent: TV_USER_AGENT,
+    device_make: None,
+    device_model: None,
+    os_name: None,
+    os_version: None,
+    android_sdk_version: None,
+    origin: "https://www.youtube.com",
+    embedded: true,
+    requires_js: true,
+    supports_cookies: true,
+};
+
+pub const WEB_SAFARI: ClientProfile = ClientProfile {
+    name: "web_safari",
+    client_name: "WEB",
+    client_id: "1",
+    client_version: "2.20250312.04.00",
+    user_agent: SAFARI_USER_AGENT,
+    device_make: None,
+    device_model: None,
+    os_name: None,
+    os_version: None,
+    android_sdk_version: None,
+    origin: "https://www.youtube.com",
+    embedded: false,
+    requires_js: true,
+    supports_cookies: true,
+};
+
+pub const MWEB: ClientProfile = ClientProfile {
+    name: "mweb",
+    client_name: "MWEB",
+    client_id: "2",
+    client_version: "2.20250311.03.00",
+    user_agent: MWEB_USER_AGENT,
+    device_make: None,
+    device_model: None,
+    os_name: None,
+    os_version: None,
+    android_sdk_version: None,
+    origin: "https://m.youtube.com",
+    embedded: false,
+    requires_js: true,
+    supports_cookies: true,
+};
+
+pub const WEB_MUSIC: ClientProfile = ClientProfile {
+    name: "web_music",
+    client_name: "WEB_REMIX",
+    client_id: "67",
+    client_version: "1.20250310.01.00",
+    user_agent: WEB_USER_AGENT,
+    device_make: None,
+    device_model: None,
+    os_name: None,
+    os_version: None,
+    android_sdk_version: None,
+    origin: "https://music.youtube.com",
+    embedded: false,
+    requires_js: true,
+    supports_cookies: true,
+};
+
+/// All known clients
+pub const CLIENT_PROFILES: &[ClientProfile] =
+    &[ANDROID_VR, IOS, TV_EMBEDDED, WEB_SAFARI, MWEB, WEB_MUSIC];
+
+/// Clients tried, in order, when `YoutubeConfig::clients` is empty
+pub const DEFAULT_CLIENTS: &[&str] = &["android_vr", "ios", "tv_embedded", "web_safari"];
+
+/// Look up a client by name
+pub fn client_profile(name: &str) -> Option<&'static ClientProfile> {
+    CLIENT_PROFILES.iter().find(|p| p.name == name)
+}
+
+/// Clients to try, in order
+///
+/// Unknown names are skipped, as are clients needing player.js when the
+/// crate is built without a JS solver.
+pub fn configured_clients() -> Vec<&'static ClientProfile> {
+    let config = config();
+    let names: Vec<&str> = if config.clients.is_empty() {
+        DEFAULT_CLIENTS.to_vec()
+    } else {
+        config.clients.iter().map(String::as_str).collect()
+    };
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let profile = client_profile(name);
+            #[cfg(debug_assertions)]
+            if profile.is_none() {
+                eprintln!("[debug] Unknown YouTube client: {name}");
+            }
+            profile
+        })
+        .filter(|profile| {
+            #[cfg(debug_assertions)]
+            if !profile.is_supported() {
+                eprintln!("[debug] {} needs player.js, skipped", profile.name);
+            }
+            profile.is_supported()
+        })
+        .collect()
+}
+
+impl ClientProfile {
+    /// Whether this build can use the client, those needing player.js
+    /// require a JS solver
+    pub fn is_supported(&self) -> bool {
+        cfg!(feature = "ytdlp-ejs") || !self.requires_js
+    }
+
+    /// Innertube `context` of the client
+    pub fn context(&self) -> InnertubeContext {
+        let mut client = serde_json::json!({
+            "clientName": self.client_name,
+            "clientVersion": self.client_version,
+            "userAgent": self.user_agent,
+            // Playability reasons are matched in English
+            "hl": "en",
+        });
+        let fields = [
+            ("deviceMake", self.device_make),
+            ("deviceModel", self.device_model),
+            ("osName", self.os_name),
+            ("osVersion", self.os_version),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                client[key] = value.into();
+            }
+        }
+        if let Some(sdk) = self.android_sdk_version {
+            client["androidSdkVersion"] = sdk.into();
+        }
+
+        InnertubeContext {
+            client,
+            third_party: self
+                .embedded
+                .then(|| serde_json::json!({ "embedUrl": "https://www.youtube.com/" })),
+        }
+    }
+
+    /// Headers of Innertube API calls made as this client
+    pub fn headers(&self, visitor_data: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(USER_AGENT, HeaderValue::from_static(self.user_agent));
+        headers.insert(
+            "X-YouTube-Client-Name",
+            HeaderValue::from_static(self.client_id),
+        );
+        headers.insert(
+            "X-YouTube-Client-Version",
+            HeaderValue::from_static(self.client_version),
+        );
+        headers.insert(ORIGIN, HeaderValue::from_static(self.origin));
+        if self.supports_cookies {
+            with_sapisid_hash(&mut headers, self.origin);
+        }
+        if let Some(vd) = visitor_data
+            && let Ok(val) = HeaderValue::from_str(vd)
+        {
+            headers.insert("X-Goog-Visitor-Id", val);
+        }
+        headers
+    }
+}
+
+/// Authorize Innertube calls with the logged-in session of imported cookies
+///
+/// `SAPISIDHASH <time>_<sha1("<time> <SAPISID> <origin>")>`, nothing is
+/// added when the jar has no `SAPISID`.
+pub(crate) fn with_sapisid_hash(headers: &mut HeaderMap, origin: &str) {
+    let Some(sapisid) = cookie_value("https://www.youtube.com", "SAPISID")
+        .or_else(|| cookie_value("https://www.youtube.com", "__Secure-3PAPISID"))
+    else {
+        return;
+    };
+    let time = chrono::Utc::now().timestamp();
+    let hash = get_sha1(&format!("{time} {sapisid} {origin}"));
+    let Ok(mut value) = HeaderValue::from_str(&format!("SAPISIDHASH {time}_{hash}")) else {
+        return;
+    };
+    value.set_sensitive(true);
+    headers.insert(AUTHORIZATION, value);
+    headers.insert("X-Goog-AuthUser", HeaderValue::from_static("0"));
+    if let Ok(origin) = HeaderValue::from_str(origin) {
+        headers.insert("X-Origin", origin);
+    }
+}
+
+/// `signatureTimestamp` of a player.js
+fn signature_timestamp(player_js: &str) -> Option<u64> {
+    ["signatureTimestamp:", "sts:"].iter().find_map(|key| {
+        let start = player_js.find(key)? + key.len();
+        let digits: String = player_js[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+/// Download audio through the player API of one client.
+///
+/// 1. Call `fetch_player` as `profile`
+/// 2. Select format `itag`, or the best audio format (itag 140 preferred)
+/// 3. Solve the signature / n of the URL via EJS if needed
+/// 4. Download with the client's UA
+pub async fn client_download(
+    profile: &ClientProfile,
+    video_id: &str,
+    itag: Option<u64>,
+    ytcfg: &YtConfig,
+    html: &str,
+) -> Result<Vec<u8>> {
+    let player = if profile.requires_js {
+        Some(load_player(html).await?)
+    } else {
+        None
+    };
+    let sts = player
+        .as_ref()
+        .and_then(|p| signature_timestamp(&p.code))
+        .or(ytcfg.sts);
+    let player_response = fetch_player(profile, video_id, ytcfg, sts).await?;
+    if let Some(e) = player_response.playability_error() {
+        return Err(e);
+    }
+
+    #[cfg(debug_assertions)]
+    {
+        let title = &player_response.video_details.title;
+        let format_count = player_response.streaming_data.formats.len()
+            + player_response.streaming_data.adaptive_formats.len();
+        eprintln!(
+            "[debug] {} API: title={title:?}, formats={format_count}",
+            profile.name
+        );
+    }
+
+    let formats = extract_audio_formats_web(&player_response)?;
+    let format = select_audio_format(&formats, itag)?;
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] {} selected format: itag={}, mime={}",
+        profile.name, format.itag, format.mime_type
+    );
+
+    let download_url = resolve_client_url(format, html, player).await?;
+    let download_url = with_gvs_token(
+        download_url,
+        profile.name,
+        video_id,
+        ytcfg.visitor_data.as_deref(),
+    )
+    .await;
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] {} download URL: {}",
+        profile.name,
+        crate::utils::strip_query(&download_url)
+    );
+
+    let mut dl_headers = download_headers(profile.user_agent, "https://www.youtube.com/");
+    with_origin(&mut dl_headers, "https://www.youtube.com");
+    with_sec_fetch(&mut dl_headers);
+
+    download_binary_chunked(&download_url, dl_headers).await
+}
+
+/// Resolve the download URL of a format.
+/// Plain `url`s may carry an `&n=` that needs decryption, `signatureCipher`
+/// needs both s and n decryption. player.js is loaded when not given.
+#[cfg_attr(not(feature = "ytdlp-ejs"), allow(unused_variables))]
+async fn resolve_client_url(
+    format: &Format,
+    html: &str,
+    player: Option<PlayerJs>,
+) -> Result<String> {
+    #[cfg(not(feature = "ytdlp-ejs"))]
+    {
+        format
+            .url
+            .clone()
+            .or_else(|| format.signature_cipher.clone())
+            .ok_or(MusicFreeError::AudioNotFound)
+    }
+
+    #[cfg(feature = "ytdlp-ejs")]
+    {
+        if let Some(raw_url) = &format.url {
+            if raw_url.contains("&n=") {
+                let player = match player {
+                    Some(player) => player,
+                    None => load_player(html).await?,
+                };
+
+                #[cfg(debug_assertions)]
+                eprintln!("[debug] n-decrypt: {}", crate::utils::strip_query(raw_url));
+
+                let decrypted = solve_n(raw_url, &player)?;
+
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "[debug] n-decrypted: {}",
+                    crate::utils::strip_query(&decrypted)
+                );
+
+                return Ok(decrypted);
+            }
+            return Ok(raw_url.clone());
+        }
+
+        if let Some(cipher) = &format.signature_cipher {
+            let player = match player {
+                Some(player) => player,
+                None => load_player(html).await?,
+            };
+            return solve_cipher(cipher, &player);
+        }
+
+        Err(MusicFreeError::AudioNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_signature_timestamps() {
+        assert_eq!(
+            signature_timestamp("var a={signatureTimestamp:20123,b:1}"),
+            Some(20123)
+        );
+        assert_eq!(signature_timestamp("x={sts:19950};"), Some(19950));
+        assert_eq!(signature_timestamp("no timestamp"), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Process-wide YouTube extraction settings.
+//!
+//! `Extractor::extract` only receives a URL, so settings that callers (and
+//! the CLI) want to tune are installed once with `set_config` and read by
+//! the extraction code.
+
+use crate::core::Quality;
+use crate::youtube::potoken::PoTokenProvider;
+use crate::youtube::selector::FormatSelector;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+/// Progress callback, called with the number of items fetched so far
+pub type Progress = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// YouTube extraction settings
+#[derive(Clone, Default)]
+pub struct YoutubeConfig {
+    /// Stop fetching playlist pages once this many videos are collected
+    pub playlist_limit: Option<usize>,
+    /// Number of videos fetched from a Mix (`RD...`), `None` uses
+    /// `DEFAULT_MIX_LIMIT`. Mixes never end, so they are always cut
+    pub mix_limit: Option<usize>,
+    /// Reports paging progress of long playlists
+    pub progress: Option<Progress>,
+    /// Innertube clients tried in order when downloading, by
+    /// `ClientProfile::name`. Empty uses `DEFAULT_CLIENTS`
+    pub clients: Vec<String>,
+    /// Directory caching player.js by player id, `None` uses
+    /// `musicfree/player` in the user's cache directory
+    pub player_cache_dir: Option<PathBuf>,
+    /// Supplies PO tokens for player requests and stream URLs
+    pub po_token: Option<Arc<dyn PoTokenProvider>>,
+    /// Audio format of videos without a chosen itag, `None` uses the
+    /// selector of `quality`
+    pub format: Option<FormatSelector>,
+    /// Picks the default format selector, `None` uses `DEFAULT_SELECTOR`
+    pub quality: Option<Quality>,
+}
+
+impl fmt::Debug for YoutubeConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("YoutubeConfig")
+            .field("playlist_limit", &self.playlist_limit)
+            .field("mix_limit", &self.mix_limit)
+            .field("progress", &self.progress.is_some())
+            .field("clients", &self.clients)
+            .field("player_cache_dir", &self.player_cache_dir)
+            .field("po_token", &self.po_token.is_some())
+            .field("format", &self.format.as_ref().map(ToString::to_string))
+            .field("quality", &self.quality)
+            .finish()
+    }
+}
+
+/// Number of videos fetched from a Mix when `mix_limit` is not set
+pub const DEFAULT_MIX_LIMIT: usize = 50;
+
+impl YoutubeConfig {
+    pub(crate) fn is_full(&self, len: usize) -> bool {
+        self.playlist_limit.is_some_and(|limit| len >= limit)
+    }
+
+    /// Selector of the audio format to download
+    pub fn format_selector(&self) -> FormatSelector {
+        self.format
+            .clone()
+            .or_else(|| self.quality.map(FormatSelector::from_quality))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn report_progress(&self, len: usize) {
+        if let Some(progress) = &self.progress {
+            progress(len);
+        }
+    }
+}
+
+static CONFIG: RwLock<Option<Arc<YoutubeConfig>>> = RwLock::new(None);
+
+/// Install the settings used by subsequent extractions
+pub fn set_config(config: YoutubeConfig) {
+    let mut guard = CONFIG.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(Arc::new(config));
+}
+
+/// Current settings, the defaults when none were installed
+pub fn config() -> Arc<YoutubeConfig> {
+    let guard = CONFIG.read().unwrap_or_else(|e| e.into_inner());
+    guard.clone().unwrap_or_default()
+}
@@ -1,16 +1,33 @@
 use crate::download::{download_text, post_json};
 use crate::error::{MusicFreeError, Result};
+use crate::utils::parse_length;
+use crate::youtube::captions::get_captions;
+use crate::youtube::channel::extract_channel;
+use crate::youtube::client::{
+    ClientProfile, client_download, configured_clients, with_sapisid_hash,
+};
+use crate::youtube::config::config;
+use crate::youtube::live::live_playlist;
+use crate::youtube::mix::extract_mix;
+use crate::youtube::music::extract_music;
 use crate::youtube::parse_id;
+use crate::youtube::potoken::{PoTokenContext, PoTokenRequest, po_token};
+use crate::youtube::search::extract_search;
+use crate::youtube::selector::find_itag;
 use crate::youtube::types::{
-    ContentPlaybackContext, Format, InnertubeContext, InnertubeRequest, PlaybackContext,
-    PlayerResponse, PlaylistContent, Title, YtConfig, YtInitialData,
+    BrowseContinuationResponse, ContentPlaybackContext, Format, InnertubeBrowseRequest,
+    InnertubeContext, InnertubeRequest, PlaybackContext, PlayerResponse, PlaylistContent,
+    PlaylistVideoRenderer, ServiceIntegrityDimensions, Title, VideoElement, YtConfig,
+    YtInitialData,
 };
 use crate::youtube::utils::{
-    ANDROID_VR_USER_AGENT, WEB_USER_AGENT, build_playlist_url, build_thumbnail_url,
-    build_watch_url, is_valid_playlist_id, is_valid_video_id, parse_playlist_id,
+    WEB_USER_AGENT, YoutubeInput, build_playlist_url, build_thumbnail_url, build_watch_url,
+    is_mix_id, is_valid_playlist_id, parse_input, parse_playlist_id,
 };
-use crate::{Audio, AudioFormat, Platform, Playlist};
+use crate::{Audio, AudioFormat, Locator, Platform, Playlist};
 use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue, ORIGIN, USER_AGENT};
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
 
 pub(crate) async fn get_player_url(html: &str) -> Option<String> {
     let marker = r#"name="player/base""#;
@@ -24,6 +41,78 @@ pub(crate) async fn get_player_url(html: &str) -> Option<String> {
     Some(url)
 }
 
+impl PlayerResponse {
+    /// Whether the response lists any stream, live manifests included
+    pub fn has_streams(&self) -> bool {
+        let streaming_data = &self.streaming_data;
+        !streaming_data.formats.is_empty()
+            || !streaming_data.adaptive_formats.is_empty()
+            || streaming_data.is_manifest_only()
+    }
+
+    /// Why the video can't be played, `None` when it is playable
+    pub fn playability_error(&self) -> Option<MusicFreeError> {
+        let status = self.playability_status.as_ref()?;
+        if status.status == "OK" {
+            return None;
+        }
+
+        let renderer = status
+            .error_screen
+            .as_ref()
+            .and_then(|screen| screen.player_error_message_renderer.as_ref());
+        let reason = status
+            .reason
+            .clone()
+            .or_else(|| renderer.and_then(|r| r.reason.as_ref()).map(Title::text))
+            .unwrap_or_else(|| status.status.clone());
+        let message = match renderer.and_then(|r| r.subreason.as_ref()) {
+            Some(subreason) => format!("{reason} ({})", subreason.text()),
+            None => reason,
+        };
+
+        // The status decides, the (English) message only tells apart what
+        // shares one, e.g. private and members-only videos
+        let lower = message.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+        let is_age_gate = status.desktop_legacy_age_gate_reason.is_some()
+            || mentions(&[
+                "confirm your age",
+                "age-restricted",
+                "inappropriate for some users",
+            ]);
+
+        Some(match status.status.as_str() {
+            "AGE_CHECK_REQUIRED" | "AGE_VERIFICATION_REQUIRED" | "CONTENT_CHECK_REQUIRED" => {
+                MusicFreeError::AgeRestricted(message)
+            }
+            "LOGIN_REQUIRED" | "UNPLAYABLE" if is_age_gate => {
+                MusicFreeError::AgeRestricted(message)
+            }
+            "LOGIN_REQUIRED" if mentions(&["private"]) => MusicFreeError::Private(message),
+            "LOGIN_REQUIRED" => MusicFreeError::LoginRequired(message),
+            // Members-only videos need a member's account
+            "UNPLAYABLE" if mentions(&["member"]) => MusicFreeError::LoginRequired(message),
+            "UNPLAYABLE" if mentions(&["country", "region", "location"]) => {
+                MusicFreeError::GeoBlocked(message)
+            }
+            _ => MusicFreeError::Unavailable(message),
+        })
+    }
+}
+
+/// Whether an error explains why a video can't be played
+pub(crate) fn is_playability_error(error: &MusicFreeError) -> bool {
+    matches!(
+        error,
+        MusicFreeError::LoginRequired(_)
+            | MusicFreeError::AgeRestricted(_)
+            | MusicFreeError::GeoBlocked(_)
+            | MusicFreeError::Private(_)
+            | MusicFreeError::Unavailable(_)
+    )
+}
+
 /// Extract audio formats from player response (web client)
 pub fn extract_audio_formats_web(player_response: &PlayerResponse) -> Result<Vec<&Format>> {
     let formats = &player_response.streaming_data.formats;
@@ -35,99 +124,203 @@ pub fn extract_audio_formats_web(player_response: &PlayerResponse) -> Result<Vec
     Ok(formats.collect())
 }
 
-/// Fetch player response from YouTube Android API
-pub async fn parse_player(video_id: &str, ytcfg: &YtConfig) -> Result<PlayerResponse> {
+/// Fetch player response from the Innertube player API as `profile`
+///
+/// `signature_timestamp` is required by clients with ciphered streams.
+pub async fn fetch_player(
+    profile: &ClientProfile,
+    video_id: &str,
+    ytcfg: &YtConfig,
+    signature_timestamp: Option<u64>,
+) -> Result<PlayerResponse> {
     let api_url = format!(
         "https://www.youtube.com/youtubei/v1/player?key={}&prettyPrint=false",
         ytcfg.innertube_api_key
     );
 
-    let client = serde_json::json!({
-          "clientName": "ANDROID_VR",
-          "clientVersion": "1.65.10",
-          "deviceMake": "Oculus",
-          "deviceModel": "Quest 3",
-          "androidSdkVersion": 32,
-          "userAgent": ANDROID_VR_USER_AGENT,
-          "osName": "Android",
-          "osVersion": "12L",
-    });
-
+    let po_token = po_token(PoTokenRequest {
+        context: PoTokenContext::Player,
+        client: profile.name,
+        video_id,
+        visitor_data: ytcfg.visitor_data.as_deref(),
+    })
+    .await;
     let request_body = InnertubeRequest {
         video_id: video_id.to_string(),
-        context: InnertubeContext { client },
+        context: profile.context(),
         playback_context: PlaybackContext {
             content_playback_context: ContentPlaybackContext {
                 // html5_preference: "HTML5_PREF_WANTS".to_string(),
                 pcm2: "yes".to_string(),
+                signature_timestamp,
             },
         },
         content_check_ok: true,
         racy_check_ok: true,
+        service_integrity_dimensions: po_token
+            .map(|po_token| ServiceIntegrityDimensions { po_token }),
     };
 
+    let headers = profile.headers(ytcfg.visitor_data.as_deref());
+    let player_response: PlayerResponse = post_json(&api_url, &request_body, headers).await?;
+    Ok(player_response)
+}
+
+/// Fetch player response, trying the configured clients in order until one
+/// returns streams
+pub async fn parse_player(video_id: &str, ytcfg: &YtConfig) -> Result<PlayerResponse> {
+    let mut last_error = MusicFreeError::AudioNotFound;
+    for profile in configured_clients() {
+        let sts = ytcfg.sts.filter(|_| profile.requires_js);
+        match fetch_player(profile, video_id, ytcfg, sts).await {
+            Ok(pr) if pr.has_streams() => return Ok(pr),
+            Ok(pr) => {
+                last_error = pr
+                    .playability_error()
+                    .unwrap_or(MusicFreeError::AudioNotFound)
+            }
+            Err(e) => last_error = e,
+        }
+
+        #[cfg(debug_assertions)]
+        eprintln!("[debug] {} player failed: {last_error}", profile.name);
+    }
+    Err(last_error)
+}
+
+/// Headers for Innertube calls made as the web client of `ytcfg`
+pub(crate) fn web_innertube_headers(ytcfg: &YtConfig) -> Result<HeaderMap> {
     let mut headers = HeaderMap::new();
     headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    headers.insert(USER_AGENT, HeaderValue::from_str(ANDROID_VR_USER_AGENT)?);
-    headers.insert("X-YouTube-Client-Name", HeaderValue::from_static("28"));
+    headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
+    headers.insert("X-YouTube-Client-Name", HeaderValue::from_static("1"));
     headers.insert(
         "X-YouTube-Client-Version",
-        HeaderValue::from_static("1.65.10"),
+        HeaderValue::from_str(&ytcfg.innertube_client_version)?,
     );
     headers.insert(ORIGIN, HeaderValue::from_static("https://www.youtube.com"));
-
+    with_sapisid_hash(&mut headers, "https://www.youtube.com");
     if let Some(vd) = &ytcfg.visitor_data
         && let Ok(val) = HeaderValue::from_str(vd)
     {
         headers.insert("X-Goog-Visitor-Id", val);
     }
-
-    let player_response: PlayerResponse = post_json(&api_url, &request_body, headers).await?;
-    Ok(player_response)
+    Ok(headers)
 }
 
-fn get_fetch_url(url: &str) -> (String, bool) {
-    if let Some(playlist_id) = parse_playlist_id(url) {
-        return (build_playlist_url(&playlist_id), true);
+/// Collect a paged list, following continuation tokens
+///
+/// `collect` adds the items of a page and returns its continuation token,
+/// `fetch` loads the page behind a token. Paging ends when no token is
+/// left or `is_full` holds, `progress` gets the count after each page.
+pub(crate) async fn collect_pages<I, T, Fut>(
+    first_page: Vec<I>,
+    collect: impl Fn(Vec<I>, &mut Vec<T>) -> Option<String>,
+    fetch: impl Fn(String) -> Fut,
+    is_full: impl Fn(usize) -> bool,
+    progress: impl Fn(usize),
+) -> Result<Vec<T>>
+where
+    Fut: Future<Output = Result<Vec<I>>>,
+{
+    let mut results = vec![];
+    let mut continuation = collect(first_page, &mut results);
+    progress(results.len());
+    while let Some(token) = continuation.take() {
+        if is_full(results.len()) {
+            break;
+        }
+        let items = fetch(token).await?;
+        continuation = collect(items, &mut results);
+        progress(results.len());
     }
-    if is_valid_video_id(url) {
-        return (build_watch_url(url), false);
+    Ok(results)
+}
+
+/// The ytcfg of `html`, parsed into `cell` on first use by pages that
+/// only need it to follow continuations
+pub(crate) fn lazy_ytcfg<'a>(cell: &'a OnceLock<YtConfig>, html: &str) -> Result<&'a YtConfig> {
+    if let Some(ytcfg) = cell.get() {
+        return Ok(ytcfg);
     }
-    (url.to_string(), false)
+    let ytcfg = parse_ytcfg(html)?;
+    Ok(cell.get_or_init(|| ytcfg))
+}
+
+/// `Extractor::extract` needs `Send` futures, keep the paging helpers from
+/// holding `!Sync` state across awaits
+fn _assert_send() {
+    fn is_send<T: Send>(_: &T) {}
+    is_send(&extract_playlist_audio("", ""));
 }
 
 /// Extract playlist information from YouTube URL or ID
 pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
-    // Construct full URL for fetching HTML
-    let (fetch_url, is_playlist) = get_fetch_url(url);
-    let html = crate::download::download_text(&fetch_url, HeaderMap::new()).await?;
-
-    // Handle playlist
-    if is_playlist {
-        return extract_playlist_audio(url, &html).await;
+    let input = parse_input(url).ok_or_else(|| {
+        MusicFreeError::InvalidUrl(format!("Cannot extract video ID from: {}", url))
+    })?;
+
+    match input {
+        YoutubeInput::Search(query) => extract_search(&query).await,
+        YoutubeInput::Music(music_url) => extract_music(&music_url).await,
+        YoutubeInput::Channel(channel) => extract_channel(&channel).await,
+        // Mixes have no playlist page, they are generated by watch-next
+        YoutubeInput::Playlist { playlist_id, .. } if is_mix_id(&playlist_id) => {
+            extract_mix(url, &playlist_id).await
+        }
+        YoutubeInput::Playlist { playlist_id, .. } => {
+            let html = download_text(&build_playlist_url(&playlist_id), HeaderMap::new()).await?;
+            extract_playlist_audio(url, &html).await
+        }
+        YoutubeInput::Video(video_id) => extract_video(&video_id).await,
     }
+}
 
-    // Single video processing
-    let video_id = &parse_id(url)?;
+/// Extract the audio formats of a single video
+async fn extract_video(video_id: &str) -> Result<(Playlist, Option<usize>)> {
+    let html = download_text(&build_watch_url(video_id), HeaderMap::new()).await?;
     let ytcfg = parse_ytcfg(&html)?;
-    let player_response = if let Ok(pr) = parse_player_response_from_html(&html) {
-        pr
-    } else {
-        parse_player(video_id, &ytcfg).await?
+    let player_response = match parse_player_response_from_html(&html) {
+        Ok(pr) if pr.has_streams() => pr,
+        html_response => match parse_player(video_id, &ytcfg).await {
+            Ok(pr) => pr,
+            // The page's own reason beats a client's generic failure
+            Err(e) if is_playability_error(&e) => return Err(e),
+            Err(e) => {
+                return Err(html_response
+                    .ok()
+                    .and_then(|pr| pr.playability_error())
+                    .unwrap_or(e));
+            }
+        },
     };
+    // Live streams are recorded from their manifests
+    if let Some(live) = live_playlist(video_id, &player_response) {
+        return Ok(live);
+    }
+
     let title = &player_response.video_details.title;
-    let audios: Vec<Audio> = extract_audio_formats_web(&player_response)?
-        .into_iter()
-        .map(|i| {
+    let captions = get_captions(&player_response);
+    // The format is picked like for playlist entries, by the configured
+    // selector, and again when downloading since clients list their own
+    let formats = extract_audio_formats_web(&player_response)?;
+    let audios: Vec<Audio> = select_best_audio_format(&formats)
+        .ok()
+        .map(|format| {
             let mut audio = Audio::new(
-                video_id.clone(),
+                video_id.to_string(),
                 title.clone(),
                 build_watch_url(video_id),
                 Platform::Youtube,
             )
-            .with_format(AudioFormat::from_youtube(&i.mime_type))
-            .with_cover(build_thumbnail_url(video_id));
-            if let Some(ms) = i
+            .with_format(AudioFormat::from_youtube(&format.mime_type))
+            .with_cover(build_thumbnail_url(video_id))
+            .with_locator(Locator::Youtube {
+                video_id: video_id.to_string(),
+                itag: None,
+            })
+            .with_subtitles(captions);
+            if let Some(ms) = format
                 .approx_duration_ms
                 .clone()
                 .and_then(|s| s.parse::<u64>().ok())
@@ -136,6 +329,7 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
             }
             audio
         })
+        .into_iter()
         .collect();
 
     // For single video, use first audio's download_url
@@ -147,6 +341,7 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
         title: Some(title.clone()),
         audios,
         cover: Some(build_thumbnail_url(video_id)),
+        playlists: Vec::new(),
         platform: Platform::Youtube,
     };
 
@@ -161,7 +356,10 @@ pub async fn extract_audio(url: &str) -> Result<(Playlist, Option<usize>)> {
 }
 
 /// Extract playlist audio from YouTube playlist URL or ID
-async fn extract_playlist_audio(url: &str, html: &str) -> Result<(Playlist, Option<usize>)> {
+pub(crate) async fn extract_playlist_audio(
+    url: &str,
+    html: &str,
+) -> Result<(Playlist, Option<usize>)> {
     // Extract playlist ID from URL or use URL as playlist ID
     let playlist_id = if is_valid_playlist_id(url) {
         url.to_string()
@@ -174,11 +372,36 @@ async fn extract_playlist_audio(url: &str, html: &str) -> Result<(Playlist, Opti
     let requested_video_id = parse_id(url).ok();
 
     let yt_data = parse_yt_initial_data(html)?;
-    let videos = extract_playlist_videos(&yt_data)?;
+    let first_page = extract_playlist_videos(&yt_data)?;
 
     // Extract playlist title from ytInitialData
     let playlist_title = extract_playlist_title(&yt_data)?;
 
+    // The page only renders the first ~100 videos, page through the rest
+    let config = config();
+    let ytcfg = &OnceLock::new();
+    let mut videos = collect_pages(
+        vec![(first_page, extract_playlist_continuation(&yt_data))],
+        |pages, videos| {
+            let mut continuation = None;
+            for (page, next) in pages {
+                videos.extend(page);
+                continuation = next;
+            }
+            continuation
+        },
+        |token| async move {
+            let page = fetch_playlist_continuation(lazy_ytcfg(ytcfg, html)?, token).await?;
+            Ok(vec![page])
+        },
+        |count| config.is_full(count),
+        |count| config.report_progress(count),
+    )
+    .await?;
+    if let Some(limit) = config.playlist_limit {
+        videos.truncate(limit);
+    }
+
     let mut audios = Vec::new();
     let mut position = None;
 
@@ -191,18 +414,7 @@ async fn extract_playlist_audio(url: &str, html: &str) -> Result<(Playlist, Opti
             position = Some(index);
         }
 
-        let mut audio = Audio::new(
-            video.video_id.clone(),
-            video.title,
-            format!("https://www.youtube.com{}", video.url),
-            Platform::Youtube,
-        )
-        .with_cover(build_thumbnail_url(&video.video_id));
-
-        if let Some(d) = video.duration {
-            audio.duration = Some(d);
-        }
-        audios.push(audio);
+        audios.push(video.into_audio());
     }
 
     let cover = audios.iter().find_map(|i| i.cover.clone());
@@ -216,6 +428,7 @@ async fn extract_playlist_audio(url: &str, html: &str) -> Result<(Playlist, Opti
         title: Some(playlist_title),
         audios,
         cover,
+        playlists: Vec::new(),
         platform: Platform::Youtube,
     };
 
@@ -229,44 +442,72 @@ async fn extract_playlist_audio(url: &str, html: &str) -> Result<(Playlist, Opti
     Ok((playlist, final_position))
 }
 
-/// Download audio with Android-first, Web+EJS fallback strategy (yt-dlp style).
+/// Download audio: Innertube clients in order, with Web+EJS fallback.
 ///
-/// Phase 1 (Android): Fetch Android Innertube API → `c=ANDROID` URL → download.
-///   Android CDN is more lenient and usually works without a browser fingerprint.
-///
-/// Phase 2 (Web fallback): If Android fails, extract player_response from web HTML,
-///   decrypt signatureCipher via EJS, and download with Web UA.
-/// Download audio: Android-first with Web+EJS fallback.
-///
-/// Phase 1 → `android::android_download` (Android Innertube API)
-/// Phase 2 → `web::web_download` (Web HTML + EJS decryption)
+/// Phase 0 → the URL resolved ahead by `batch::resolve_urls`, if any
+/// Phase 1 → `client::client_download` for each of `configured_clients`,
+///   moving on when a client is unplayable, has no streams or gets 403
+/// Phase 2 → `web::web_download_format` (Web HTML + EJS decryption)
 pub async fn download_audio(url: &str) -> Result<Vec<u8>> {
-    let video_id = &parse_id(url)?;
+    let video_id = parse_id(url)?;
+    download_video(&video_id, None).await
+}
+
+/// Download a video's audio, using the format `itag` when it is available
+pub async fn download_video(video_id: &str, itag: Option<u64>) -> Result<Vec<u8>> {
+    #[cfg(feature = "ytdlp-ejs")]
+    if let Some(url) = crate::youtube::batch::take_resolved(video_id, itag) {
+        match crate::youtube::web::download_web_url(&url).await {
+            Ok(data) => return Ok(data),
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("[debug] Resolved URL failed: {_e}, trying clients");
+            }
+        }
+    }
+
     let page_url = build_watch_url(video_id);
     let mut web_headers = HeaderMap::new();
     web_headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
     let html = download_text(&page_url, web_headers).await?;
     let ytcfg = parse_ytcfg(&html)?;
 
-    match crate::youtube::android::android_download(video_id, &ytcfg, &html).await {
-        Ok(data) => return Ok(data),
-        #[allow(unused_variables)]
-        Err(e) => {
-            #[cfg(debug_assertions)]
-            eprintln!("[debug] Android download failed: {e}, falling back to Web+EJS");
+    // Kept to report why the video is unplayable if every client fails
+    let mut playability = None;
+    for profile in configured_clients() {
+        match client_download(profile, video_id, itag, &ytcfg, &html).await {
+            Ok(data) => return Ok(data),
+            Err(e) => {
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "[debug] {} download failed: {e}, trying next client",
+                    profile.name
+                );
+                if is_playability_error(&e) {
+                    playability.get_or_insert(e);
+                }
+            }
         }
     }
-    crate::youtube::web::web_download(&html).await
+    match crate::youtube::web::web_download_format(itag, &html).await {
+        Err(e) if !is_playability_error(&e) => Err(playability.unwrap_or(e)),
+        result => result,
+    }
+}
+
+/// Select the format `itag`, or the best audio format when it is not offered
+pub fn select_audio_format<'a>(formats: &'a [&'a Format], itag: Option<u64>) -> Result<&'a Format> {
+    match itag.and_then(|itag| find_itag(formats, itag)) {
+        Some(format) => Ok(format),
+        None => select_best_audio_format(formats),
+    }
 }
 
-/// Select best audio format: prefer itag 140 (m4a audio-only).
+/// Select the best audio format by `YoutubeConfig::format_selector`
 pub fn select_best_audio_format<'a>(formats: &'a [&'a Format]) -> Result<&'a Format> {
-    formats
-        .iter()
-        .find(|f| f.itag == 140)
-        .or_else(|| formats.iter().find(|f| f.mime_type.starts_with("audio/")))
-        .or_else(|| formats.first())
-        .copied()
+    config()
+        .format_selector()
+        .select(formats)
         .ok_or(MusicFreeError::AudioNotFound)
 }
 
@@ -341,6 +582,11 @@ pub fn parse_player_response_from_html(html: &str) -> Result<PlayerResponse> {
 
 /// Extract ytInitialData from HTML
 pub fn parse_yt_initial_data(html: &str) -> Result<YtInitialData> {
+    parse_initial_data(html)
+}
+
+/// Extract ytInitialData from HTML as `T`, for pages with another layout
+pub(crate) fn parse_initial_data<T: DeserializeOwned>(html: &str) -> Result<T> {
     let st = "var ytInitialData = ";
     let ed = "};";
 
@@ -374,6 +620,28 @@ pub struct PlaylistVideoInfo {
     pub duration: Option<u64>,
 }
 
+impl PlaylistVideoInfo {
+    /// Convert into an audio entry of a playlist
+    pub fn into_audio(self) -> Audio {
+        let mut audio = Audio::new(
+            self.video_id.clone(),
+            self.title,
+            format!("https://www.youtube.com{}", self.url),
+            Platform::Youtube,
+        )
+        .with_cover(build_thumbnail_url(&self.video_id))
+        .with_locator(Locator::Youtube {
+            video_id: self.video_id,
+            itag: None,
+        });
+
+        if let Some(d) = self.duration {
+            audio.duration = Some(d);
+        }
+        audio
+    }
+}
+
 /// Extract playlist video information from ytInitialData
 /// Supports both twoColumnWatchNextResults and twoColumnBrowseResultsRenderer formats
 pub fn extract_playlist_videos(yt_data: &YtInitialData) -> Result<Vec<PlaylistVideoInfo>> {
@@ -400,12 +668,16 @@ pub fn extract_playlist_videos(yt_data: &YtInitialData) -> Result<Vec<PlaylistVi
                     .url
                     .clone();
                 let video_id = renderer.navigation_endpoint.watch_endpoint.video_id.clone();
+                let duration = renderer
+                    .length_text
+                    .as_ref()
+                    .and_then(|length| parse_length(&length.text()));
 
                 result.push(PlaylistVideoInfo {
                     title,
                     url,
                     video_id,
-                    duration: None,
+                    duration,
                 });
             }
         }
@@ -429,29 +701,8 @@ pub fn extract_playlist_videos(yt_data: &YtInitialData) -> Result<Vec<PlaylistVi
                     .filter_map(|section| section.item_section_renderer.as_ref())
                     .flat_map(|renderer| &renderer.contents)
                     .flat_map(|item| &item.playlist_video_list_renderer.contents)
-                    .map(|video_element| {
-                        let renderer = &video_element.playlist_video_renderer;
-                        let video_id = renderer.video_id.clone();
-                        let title: String = renderer
-                            .title
-                            .runs
-                            .iter()
-                            .map(|r| r.text.as_str())
-                            .collect();
-                        let url = format!("/watch?v={}", video_id);
-                        let duration = video_element
-                            .playlist_video_renderer
-                            .length_seconds
-                            .parse()
-                            .ok();
-
-                        PlaylistVideoInfo {
-                            title,
-                            url,
-                            video_id,
-                            duration,
-                        }
-                    })
+                    .filter_map(|video_element| video_element.playlist_video_renderer.as_ref())
+                    .map(playlist_video_info)
                     .collect()
             })
             .unwrap_or_default();
@@ -464,6 +715,97 @@ pub fn extract_playlist_videos(yt_data: &YtInitialData) -> Result<Vec<PlaylistVi
     ))
 }
 
+fn playlist_video_info(renderer: &PlaylistVideoRenderer) -> PlaylistVideoInfo {
+    let video_id = renderer.video_id.clone();
+    let title: String = renderer
+        .title
+        .runs
+        .iter()
+        .map(|r| r.text.as_str())
+        .collect();
+    let url = format!("/watch?v={}", video_id);
+    let duration = renderer.length_seconds.parse().ok();
+
+    PlaylistVideoInfo {
+        title,
+        url,
+        video_id,
+        duration,
+    }
+}
+
+fn continuation_token(elements: &[VideoElement]) -> Option<String> {
+    elements
+        .iter()
+        .rev()
+        .find_map(|element| element.continuation_item_renderer.as_ref())
+        .map(|renderer| {
+            renderer
+                .continuation_endpoint
+                .continuation_command
+                .token
+                .clone()
+        })
+}
+
+/// Extract the continuation token of a playlist page, present when the
+/// playlist has more videos than the page rendered
+pub fn extract_playlist_continuation(yt_data: &YtInitialData) -> Option<String> {
+    let browse_results = yt_data
+        .contents
+        .two_column_browse_results_renderer
+        .as_ref()?;
+    let tab = browse_results
+        .tabs
+        .iter()
+        .find(|tab| tab.tab_renderer.selected)?;
+    tab.tab_renderer
+        .content
+        .section_list_renderer
+        .contents
+        .iter()
+        .filter_map(|section| section.item_section_renderer.as_ref())
+        .flat_map(|renderer| &renderer.contents)
+        .find_map(|item| continuation_token(&item.playlist_video_list_renderer.contents))
+}
+
+/// Fetch the next page of a playlist through the Innertube `browse` endpoint
+///
+/// Returns the videos of the page and the token of the following page.
+pub async fn fetch_playlist_continuation(
+    ytcfg: &YtConfig,
+    token: String,
+) -> Result<(Vec<PlaylistVideoInfo>, Option<String>)> {
+    let api_url = format!(
+        "https://www.youtube.com/youtubei/v1/browse?key={}&prettyPrint=false",
+        ytcfg.innertube_api_key
+    );
+    let request = InnertubeBrowseRequest {
+        context: InnertubeContext {
+            client: ytcfg.innertube_context.client.clone(),
+            third_party: None,
+        },
+        browse_id: None,
+        continuation: Some(token),
+    };
+    let resp: BrowseContinuationResponse =
+        post_json(&api_url, &request, web_innertube_headers(ytcfg)?).await?;
+
+    let elements: Vec<VideoElement> = resp
+        .on_response_received_actions
+        .into_iter()
+        .filter_map(|action| action.append_continuation_items_action)
+        .flat_map(|action| action.continuation_items)
+        .collect();
+
+    let videos = elements
+        .iter()
+        .filter_map(|element| element.playlist_video_renderer.as_ref())
+        .map(playlist_video_info)
+        .collect();
+    Ok((videos, continuation_token(&elements)))
+}
+
 /// Extract playlist title from ytInitialData
 /// Supports both twoColumnWatchNextResults and twoColumnBrowseResultsRenderer formats
 pub fn extract_playlist_title(yt_data: &YtInitialData) -> Result<String> {
@@ -486,3 +828,124 @@ pub fn extract_playlist_title(yt_data: &YtInitialData) -> Result<String> {
     // Fallback to default title if not found
     Ok("YouTube Playlist".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn classify(playability_status: serde_json::Value) -> Option<MusicFreeError> {
+        let response: PlayerResponse =
+            serde_json::from_value(json!({ "playabilityStatus": playability_status })).unwrap();
+        response.playability_error()
+    }
+
+    fn error_screen(reason: &str, subreason: &str) -> serde_json::Value {
+        json!({
+            "playerErrorMessageRenderer": {
+                "reason": { "simpleText": reason },
+                "subreason": { "runs": [{ "text": subreason }] },
+            }
+        })
+    }
+
+    #[test]
+    fn playable_responses_have_no_error() {
+        assert!(classify(json!({ "status": "OK" })).is_none());
+        let response: PlayerResponse = serde_json::from_value(json!({})).unwrap();
+        assert!(response.playability_error().is_none());
+    }
+
+    #[test]
+    fn classifies_age_gates() {
+        let cases = [
+            json!({ "status": "AGE_CHECK_REQUIRED", "reason": "Sign in to confirm your age" }),
+            json!({ "status": "CONTENT_CHECK_REQUIRED", "reason": "The following content may contain suicide or self-harm topics." }),
+            json!({
+                "status": "LOGIN_REQUIRED",
+                "reason": "Sign in to confirm your age",
+                "desktopLegacyAgeGateReason": 1,
+            }),
+            json!({
+                "status": "UNPLAYABLE",
+                "reason": "This video may be inappropriate for some users.",
+            }),
+        ];
+        for case in cases {
+            assert!(
+                matches!(
+                    classify(case.clone()),
+                    Some(MusicFreeError::AgeRestricted(_))
+                ),
+                "{case}"
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_login_required() {
+        match classify(json!({
+            "status": "LOGIN_REQUIRED",
+            "reason": "Sign in to confirm you're not a bot",
+        })) {
+            Some(MusicFreeError::LoginRequired(message)) => {
+                assert_eq!(message, "Sign in to confirm you're not a bot")
+            }
+            other => panic!("{other:?}"),
+        }
+
+        let members = classify(json!({
+            "status": "UNPLAYABLE",
+            "reason": "Join this channel to get access to members-only content like this video, and other exclusive perks.",
+        }));
+        assert!(matches!(members, Some(MusicFreeError::LoginRequired(_))));
+    }
+
+    #[test]
+    fn classifies_private_videos() {
+        let error = classify(json!({
+            "status": "LOGIN_REQUIRED",
+            "reason": "This video is private",
+            "errorScreen": error_screen(
+                "Private video",
+                "If the owner of this video has granted you access, please sign in.",
+            ),
+        }));
+        assert!(matches!(error, Some(MusicFreeError::Private(_))));
+    }
+
+    #[test]
+    fn classifies_geo_blocks_from_the_subreason() {
+        let error = classify(json!({
+            "status": "UNPLAYABLE",
+            "errorScreen": error_screen(
+                "Video unavailable",
+                "The uploader has not made this video available in your country",
+            ),
+        }));
+        match error {
+            Some(MusicFreeError::GeoBlocked(message)) => assert_eq!(
+                message,
+                "Video unavailable (The uploader has not made this video available in your country)"
+            ),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn classifies_the_rest_as_unavailable() {
+        let cases = [
+            json!({ "status": "ERROR", "reason": "This video has been removed by the uploader" }),
+            // The status wins over the message
+            json!({ "status": "ERROR", "reason": "This video is private" }),
+            json!({ "status": "LIVE_STREAM_OFFLINE", "reason": "This live event will begin in a few moments." }),
+            json!({ "status": "UNPLAYABLE" }),
+        ];
+        for case in cases {
+            assert!(
+                matches!(classify(case.clone()), Some(MusicFreeError::Unavailable(_))),
+                "{case}"
+            );
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
 use crate::error::{MusicFreeError, Result};
+use crate::youtube::player::{Challenge, PlayerJs};
+use crate::youtube::types::Format;
 use url::Url;
 use ytdlp_ejs::{
     JsChallengeInput, JsChallengeOutput, JsChallengeRequest, JsChallengeResponse, JsChallengeType,
@@ -27,7 +29,7 @@ fn extract_n_param(url: &Url) -> Result<String> {
         })
 }
 
-pub(crate) fn solve_n(url_str: &str, player: String) -> Result<String> {
+pub(crate) fn solve_n(url_str: &str, player: &PlayerJs) -> Result<String> {
     let url_obj = Url::parse(url_str)
         .map_err(|e| MusicFreeError::CipherParseError(format!("Failed to parse URL: {}", e)))?;
 
@@ -37,11 +39,8 @@ pub(crate) fn solve_n(url_str: &str, player: String) -> Result<String> {
     #[cfg(debug_assertions)]
     eprintln!("[debug] solve_n: input n={n}");
 
-    // Execute JS challenge for n parameter
-    let results = execute_js_challenges(player, vec![(JsChallengeType::N, vec![n.clone()])])?;
-
-    // Get the transformed n value
-    let new_n = results.get(&n).ok_or_else(|| {
+    solve_batch(player, std::slice::from_ref(&n), &[])?;
+    let new_n = player.solved(Challenge::N, &n).ok_or_else(|| {
         MusicFreeError::JsDecryptionFailed(
             "Failed to get valid response for n parameter".to_string(),
         )
@@ -51,7 +50,7 @@ pub(crate) fn solve_n(url_str: &str, player: String) -> Result<String> {
     eprintln!("[debug] solve_n: output n={new_n}");
 
     // Update URL with new n parameter
-    update_url_query(url_obj, &["n"], &[("n".to_string(), new_n.clone())])
+    update_url_query(url_obj, &["n"], &[("n".to_string(), new_n)])
 }
 
 /// Update URL query parameters, removing specified keys and adding new ones
@@ -71,7 +70,7 @@ fn update_url_query(
     Ok(url.to_string())
 }
 
-pub(crate) fn solve_cipher(cipher_str: &str, player: String) -> Result<String> {
+pub(crate) fn solve_cipher(cipher_str: &str, player: &PlayerJs) -> Result<String> {
     // Parse signatureCipher parameters
     let cipher_params: HashMap<String, String> = url::form_urlencoded::parse(cipher_str.as_bytes())
         .into_owned()
@@ -93,20 +92,13 @@ pub(crate) fn solve_cipher(cipher_str: &str, player: String) -> Result<String> {
     #[cfg(debug_assertions)]
     eprintln!("[debug] solve_cipher: s={s}, n={n}, sp={sp}");
 
-    // Execute JS challenges for both n and s parameters
-    let results = execute_js_challenges(
-        player,
-        vec![
-            (JsChallengeType::N, vec![n.clone()]),
-            (JsChallengeType::Sig, vec![s.to_string()]),
-        ],
-    )?;
+    solve_batch(player, std::slice::from_ref(&n), std::slice::from_ref(s))?;
 
     // Extract transformed values
-    let new_n = results.get(&n).ok_or_else(|| {
+    let new_n = player.solved(Challenge::N, &n).ok_or_else(|| {
         MusicFreeError::JsDecryptionFailed("Failed to decrypt n parameter".to_string())
     })?;
-    let new_sig = results.get(s).ok_or_else(|| {
+    let new_sig = player.solved(Challenge::Sig, s).ok_or_else(|| {
         MusicFreeError::JsDecryptionFailed("Failed to decrypt s parameter".to_string())
     })?;
 
@@ -117,16 +109,98 @@ pub(crate) fn solve_cipher(cipher_str: &str, player: String) -> Result<String> {
     update_url_query(
         url_obj,
         &["n", sp],
-        &[
-            ("n".to_string(), new_n.clone()),
-            (sp.to_string(), new_sig.clone()),
-        ],
+        &[("n".to_string(), new_n), (sp.to_string(), new_sig)],
+    )
+}
+
+/// Download URL of a format, solving its n / signature
+pub(crate) fn solve_format(format: &Format, player: &PlayerJs) -> Result<String> {
+    if let Some(raw_url) = &format.url {
+        if raw_url.contains("&n=") {
+            return solve_n(raw_url, player);
+        }
+        return Ok(raw_url.clone());
+    }
+    match &format.signature_cipher {
+        Some(cipher) => solve_cipher(cipher, player),
+        None => Err(MusicFreeError::AudioNotFound),
+    }
+}
+
+/// Values of the n / signature challenges of a format
+pub(crate) fn format_challenges(format: &Format) -> (Option<String>, Option<String>) {
+    let n_of = |url_str: &str| {
+        Url::parse(url_str)
+            .ok()
+            .and_then(|url| extract_n_param(&url).ok())
+    };
+    if let Some(raw_url) = &format.url {
+        return (n_of(raw_url), None);
+    }
+    let Some(cipher) = &format.signature_cipher else {
+        return (None, None);
+    };
+    let cipher_params: HashMap<String, String> = url::form_urlencoded::parse(cipher.as_bytes())
+        .into_owned()
+        .collect();
+    (
+        cipher_params.get("url").and_then(|url| n_of(url)),
+        cipher_params.get("s").cloned(),
     )
 }
 
+/// Solve many n and signature values with a single runtime invocation
+///
+/// Results are remembered on `player`, values solved before are skipped,
+/// so formats of many videos sharing a player can be solved up front and
+/// `solve_n` / `solve_cipher` then only rewrite URLs.
+pub(crate) fn solve_batch(player: &PlayerJs, n: &[String], sig: &[String]) -> Result<()> {
+    let pending = |challenge: Challenge, values: &[String]| {
+        let mut pending: Vec<String> = values
+            .iter()
+            .filter(|value| player.solved(challenge, value).is_none())
+            .cloned()
+            .collect();
+        pending.sort();
+        pending.dedup();
+        pending
+    };
+    let n = pending(Challenge::N, n);
+    let sig = pending(Challenge::Sig, sig);
+
+    let mut challenges = vec![];
+    if !n.is_empty() {
+        challenges.push((JsChallengeType::N, n.clone()));
+    }
+    if !sig.is_empty() {
+        challenges.push((JsChallengeType::Sig, sig.clone()));
+    }
+    if challenges.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] solve_batch: player={}, n={}, sig={}",
+        player.id,
+        n.len(),
+        sig.len()
+    );
+
+    let results = execute_js_challenges(&player.code, challenges)?;
+    for (challenge, values) in [(Challenge::N, n), (Challenge::Sig, sig)] {
+        for value in values {
+            if let Some(solved) = results.get(&value) {
+                player.remember(challenge, &value, solved);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Execute JS challenges and return response data
 fn execute_js_challenges(
-    player: String,
+    player: &str,
     challenges: Vec<(JsChallengeType, Vec<String>)>,
 ) -> Result<HashMap<String, String>> {
     let requests = challenges
@@ -137,8 +211,9 @@ fn execute_js_challenges(
         })
         .collect();
 
+    // The runtime takes an owned source, copied once per batch
     let input = JsChallengeInput::Player {
-        player,
+        player: player.to_string(),
         requests,
         output_preprocessed: false,
     };
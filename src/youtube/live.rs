@@ -0,0 +1,151 @@
+//! Live stream and premiere recording.
+//!
+//! While a stream is live, and for a while after it ends, the player
+//! response lists no formats, only the HLS and DASH manifests. The DASH
+//! manifest has audio-only representations that concatenate into an M4A;
+//! the HLS master playlist is the fallback, its variants mux AAC with
+//! video in MPEG-TS, the AAC of the smallest one is recorded as ADTS.
+
+use std::io::Write;
+use std::time::Instant;
+
+use crate::core::{Audio, AudioFormat, Locator, Platform, Playlist, RecordOptions};
+use crate::dash::record_dash;
+use crate::download::download_text;
+use crate::error::{MusicFreeError, Result};
+use crate::headers::{download_headers, with_origin};
+use crate::hls::record_hls;
+use crate::youtube::core::{parse_player, parse_player_response_from_html, parse_ytcfg};
+use crate::youtube::types::{PlayerResponse, StreamingData};
+use crate::youtube::utils::{WEB_USER_AGENT, build_thumbnail_url, build_watch_url};
+use reqwest::header::HeaderMap;
+
+/// Manifest protocol of a live stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestProtocol {
+    /// MPD with audio-only fMP4 representations
+    Dash,
+    /// HLS master playlist with muxed MPEG-TS variants
+    Hls,
+}
+
+/// Manifest a live stream is recorded from
+#[derive(Debug, Clone)]
+pub struct LiveManifest {
+    pub protocol: ManifestProtocol,
+    pub url: String,
+}
+
+impl LiveManifest {
+    /// Preferred manifest of the streaming data, DASH first
+    pub fn from_streaming_data(streaming_data: &StreamingData) -> Option<Self> {
+        let dash = streaming_data
+            .dash_manifest_url
+            .clone()
+            .map(|url| LiveManifest {
+                protocol: ManifestProtocol::Dash,
+                url,
+            });
+        dash.or_else(|| {
+            streaming_data
+                .hls_manifest_url
+                .clone()
+                .map(|url| LiveManifest {
+                    protocol: ManifestProtocol::Hls,
+                    url,
+                })
+        })
+    }
+
+    /// Format of the recorded file
+    pub fn format(&self) -> AudioFormat {
+        match self.protocol {
+            ManifestProtocol::Dash => AudioFormat::M4A,
+            ManifestProtocol::Hls => AudioFormat::AAC,
+        }
+    }
+}
+
+/// Fetch the player response of a live stream or premiere
+///
+/// The watch page usually lists the manifests, the configured clients are
+/// asked otherwise.
+pub async fn get_live_manifest(video_id: &str) -> Result<(PlayerResponse, LiveManifest)> {
+    let html = download_text(&build_watch_url(video_id), HeaderMap::new()).await?;
+    let player_response = match parse_player_response_from_html(&html) {
+        Ok(pr) if LiveManifest::from_streaming_data(&pr.streaming_data).is_some() => pr,
+        Ok(pr) if pr.video_details.is_upcoming => {
+            return Err(MusicFreeError::ExtractionFailed(format!(
+                "Video {video_id} has not started yet"
+            )));
+        }
+        _ => parse_player(video_id, &parse_ytcfg(&html)?).await?,
+    };
+
+    let manifest = LiveManifest::from_streaming_data(&player_response.streaming_data)
+        .ok_or(MusicFreeError::AudioNotFound)?;
+    Ok((player_response, manifest))
+}
+
+/// A video streamed through manifests only as a single live audio, `None`
+/// for videos with formats
+pub(crate) fn live_playlist(
+    video_id: &str,
+    player_response: &PlayerResponse,
+) -> Option<(Playlist, Option<usize>)> {
+    if !player_response.streaming_data.is_manifest_only() {
+        return None;
+    }
+    let manifest = LiveManifest::from_streaming_data(&player_response.streaming_data)?;
+    let title = player_response.video_details.title.clone();
+
+    let audio = Audio::new(
+        video_id.to_string(),
+        title.clone(),
+        build_watch_url(video_id),
+        Platform::Youtube,
+    )
+    .with_format(manifest.format())
+    .with_cover(build_thumbnail_url(video_id))
+    .with_locator(Locator::Youtube {
+        video_id: video_id.to_string(),
+        itag: None,
+    })
+    .with_live(true);
+
+    let playlist = Playlist {
+        id: None,
+        download_url: Some(audio.download_url.clone()),
+        title: Some(title),
+        audios: vec![audio],
+        cover: Some(build_thumbnail_url(video_id)),
+        playlists: Vec::new(),
+        platform: Platform::Youtube,
+    };
+    Some((playlist, Some(0)))
+}
+
+/// Record the audio of a live stream into `writer`
+///
+/// Stops when `options.duration` elapses or the stream ends. Ended streams
+/// whose manifests are still up are downloaded whole. Returns the number
+/// of bytes written.
+pub async fn record_live(
+    video_id: &str,
+    options: &RecordOptions,
+    writer: &mut (dyn Write + Send),
+) -> Result<u64> {
+    let (_, manifest) = get_live_manifest(video_id).await?;
+    let deadline = options.duration.map(|d| Instant::now() + d);
+
+    let mut headers = download_headers(WEB_USER_AGENT, "https://www.youtube.com/");
+    with_origin(&mut headers, "https://www.youtube.com");
+
+    #[cfg(debug_assertions)]
+    eprintln!("[debug] Recording {video_id} from {:?}", manifest.protocol);
+
+    match manifest.protocol {
+        ManifestProtocol::Dash => record_dash(&manifest.url, headers, deadline, writer).await,
+        ManifestProtocol::Hls => record_hls(&manifest.url, headers, deadline, writer).await,
+    }
+}
@@ -0,0 +1,128 @@
+//! YouTube Mix / radio (`RD...`) playlists.
+//!
+//! Mixes have no playlist page and never end. The watch page renders the
+//! first batch, further batches come from the Innertube `next` endpoint
+//! seeded with the last video, until enough unique videos are collected.
+
+use std::collections::HashSet;
+
+use crate::download::{download_text, post_json};
+use crate::error::Result;
+use crate::youtube::config::{DEFAULT_MIX_LIMIT, config};
+use crate::youtube::core::{
+    PlaylistVideoInfo, extract_playlist_title, extract_playlist_videos, parse_yt_initial_data,
+    parse_ytcfg, web_innertube_headers,
+};
+use crate::youtube::types::{InnertubeContext, InnertubeNextRequest, YtConfig, YtInitialData};
+use crate::youtube::utils::{
+    WEB_USER_AGENT, build_watch_url_with_playlist, is_valid_video_id, parse_id,
+};
+use crate::{Platform, Playlist};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+
+/// Seed video of a video based mix (`RD<id>`, `RDAMVM<id>`)
+fn seed_video_id(playlist_id: &str) -> Option<String> {
+    let rest = playlist_id
+        .strip_prefix("RDAMVM")
+        .or_else(|| playlist_id.strip_prefix("RD"))?;
+    is_valid_video_id(rest).then(|| rest.to_string())
+}
+
+/// Fetch the next batch of a mix following `video_id`
+async fn fetch_next(
+    ytcfg: &YtConfig,
+    video_id: &str,
+    playlist_id: &str,
+) -> Result<Vec<PlaylistVideoInfo>> {
+    let api_url = format!(
+        "https://www.youtube.com/youtubei/v1/next?key={}&prettyPrint=false",
+        ytcfg.innertube_api_key
+    );
+    let request = InnertubeNextRequest {
+        context: InnertubeContext {
+            client: ytcfg.innertube_context.client.clone(),
+            third_party: None,
+        },
+        video_id: Some(video_id.to_string()),
+        playlist_id: Some(playlist_id.to_string()),
+    };
+    let resp: YtInitialData = post_json(&api_url, &request, web_innertube_headers(ytcfg)?).await?;
+    extract_playlist_videos(&resp)
+}
+
+/// Append the videos not seen yet, returning how many were added
+fn push_unique(
+    videos: &mut Vec<PlaylistVideoInfo>,
+    seen: &mut HashSet<String>,
+    batch: Vec<PlaylistVideoInfo>,
+) -> usize {
+    let before = videos.len();
+    videos.extend(
+        batch
+            .into_iter()
+            .filter(|video| seen.insert(video.video_id.clone())),
+    );
+    videos.len() - before
+}
+
+/// Extract a mix as a playlist of unique videos
+///
+/// The number of videos is `YoutubeConfig::mix_limit`.
+pub async fn extract_mix(url: &str, playlist_id: &str) -> Result<(Playlist, Option<usize>)> {
+    let config = config();
+    let limit = config.mix_limit.unwrap_or(DEFAULT_MIX_LIMIT);
+
+    let requested_video_id = parse_id(url).ok();
+    let seed = requested_video_id
+        .clone()
+        .or_else(|| seed_video_id(playlist_id));
+    let page_url = match &seed {
+        Some(video_id) => build_watch_url_with_playlist(video_id, playlist_id),
+        None => format!("https://www.youtube.com/watch?list={playlist_id}"),
+    };
+
+    let mut web_headers = HeaderMap::new();
+    web_headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
+    let html = download_text(&page_url, web_headers).await?;
+    let ytcfg = parse_ytcfg(&html)?;
+    let yt_data = parse_yt_initial_data(&html)?;
+    let title = extract_playlist_title(&yt_data)?;
+
+    let mut seen = HashSet::new();
+    let mut videos = vec![];
+    push_unique(&mut videos, &mut seen, extract_playlist_videos(&yt_data)?);
+    config.report_progress(videos.len());
+
+    while videos.len() < limit {
+        let Some(last) = videos.last().map(|video| video.video_id.clone()) else {
+            break;
+        };
+        let batch = fetch_next(&ytcfg, &last, playlist_id).await?;
+        // The mix went around in a circle
+        if push_unique(&mut videos, &mut seen, batch) == 0 {
+            break;
+        }
+        config.report_progress(videos.len());
+    }
+    videos.truncate(limit);
+
+    let position =
+        requested_video_id.and_then(|id| videos.iter().position(|video| video.video_id == id));
+    let audios: Vec<_> = videos
+        .into_iter()
+        .map(PlaylistVideoInfo::into_audio)
+        .collect();
+    let cover = audios.first().and_then(|a| a.cover.clone());
+
+    let playlist = Playlist {
+        id: Some(playlist_id.to_string()),
+        download_url: Some(page_url),
+        title: Some(title),
+        audios,
+        cover,
+        playlists: Vec::new(),
+        platform: Platform::Youtube,
+    };
+
+    Ok((playlist, position))
+}
@@ -1,24 +1,59 @@
 use crate::Playlist;
-use crate::core::{Extractor, Platform};
+use crate::core::{Audio, Extractor, Locator, Platform, RecordOptions, Subtitle};
 use crate::error::Result;
+use crate::subtitle::SubtitleLine;
 use async_trait::async_trait;
+use std::io::Write;
 
+pub mod android;
+pub mod captions;
+pub mod channel;
+pub mod client;
+pub mod config;
 pub mod core;
+pub mod live;
+pub mod mix;
+pub mod music;
+pub mod player;
+pub mod potoken;
+pub mod search;
+pub mod selector;
 pub mod types;
 pub mod utils;
-pub mod android;
 pub mod web;
 
 // Re-export commonly used types and functions
+pub use captions::{
+    download_caption, get_captions, get_video_captions, parse_timedtext, translate_caption,
+};
+pub use channel::extract_channel;
+pub use client::{CLIENT_PROFILES, ClientProfile, DEFAULT_CLIENTS, client_profile};
+pub use config::{DEFAULT_MIX_LIMIT, Progress, YoutubeConfig, config, set_config};
 pub use core::*;
+pub use live::{LiveManifest, ManifestProtocol, get_live_manifest, record_live};
+pub use mix::extract_mix;
+pub use music::extract_music;
+pub use player::{PlayerJs, player_cache_dir, player_id};
+pub use potoken::{
+    CommandPoTokenProvider, PoTokenContext, PoTokenProvider, PoTokenRequest, StaticPoTokenProvider,
+};
+pub use search::{SearchQuery, extract_search, search};
+pub use selector::{DEFAULT_SELECTOR, FormatSelector};
 pub use types::{
-    ContentPlaybackContext, Format, InnertubeContext, InnertubeRequest, PlaybackContext,
-    PlayerResponse, YtConfig,
+    AudioTrack, ContentPlaybackContext, Format, InnertubeContext, InnertubeRequest,
+    PlaybackContext, PlayerResponse, YtConfig,
+};
+pub use utils::{
+    ChannelTab, ChannelUrl, MusicUrl, YoutubeInput, is_youtube_url, parse_channel_url, parse_id,
+    parse_input, parse_music_url, parse_search_query,
 };
-pub use utils::{is_youtube_url, parse_id};
 
+#[cfg(feature = "ytdlp-ejs")]
+pub mod batch;
 #[cfg(feature = "ytdlp-ejs")]
 mod ejs;
+#[cfg(feature = "ytdlp-ejs")]
+pub use batch::resolve_urls;
 
 /// YouTube extractor implementing the Extractor trait
 #[derive(Debug, Clone)]
@@ -38,6 +73,40 @@ impl Extractor for YoutubeExtractor {
         download_audio(url).await
     }
 
+    async fn download_audio(&self, audio: &Audio) -> Result<Vec<u8>> {
+        match &audio.locator {
+            Some(Locator::Youtube { video_id, itag }) => download_video(video_id, *itag).await,
+            _ => download_audio(&audio.download_url).await,
+        }
+    }
+
+    async fn record(
+        &self,
+        url: &str,
+        options: &RecordOptions,
+        writer: &mut (dyn Write + Send),
+    ) -> Result<u64> {
+        let video_id = parse_id(url)?;
+        record_live(&video_id, options, writer).await
+    }
+
+    async fn get_subtitles(&self, audio: &Audio) -> Result<Vec<Subtitle>> {
+        match &audio.locator {
+            Some(Locator::Youtube { video_id, .. }) if audio.subtitles.is_empty() => {
+                get_video_captions(video_id).await
+            }
+            _ => Ok(audio.subtitles.clone()),
+        }
+    }
+
+    async fn download_subtitle(&self, subtitle: &Subtitle) -> Result<Vec<SubtitleLine>> {
+        download_caption(&subtitle.url).await
+    }
+
+    fn translate_subtitle(&self, subtitle: &Subtitle, lang: &str) -> Result<Subtitle> {
+        translate_caption(subtitle, lang)
+    }
+
     fn platform(&self) -> Platform {
         Platform::Youtube
     }
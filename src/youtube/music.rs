@@ -0,0 +1,311 @@
+//! YouTube Music through the `WEB_REMIX` Innertube client.
+//!
+//! Music pages carry artist, album and track number metadata the regular
+//! web pages lack. Tracks come from the `next` endpoint, albums
+//! (`MPREb_...` pages and `OLAK5uy_...` playlists) and playlists from
+//! `browse`. Audio is still downloaded through the regular video flow.
+
+use crate::download::post_json;
+use crate::error::{MusicFreeError, Result};
+use crate::utils::parse_length;
+use crate::youtube::client::WEB_MUSIC;
+use crate::youtube::config::config;
+use crate::youtube::core::collect_pages;
+use crate::youtube::types::{
+    InnertubeBrowseRequest, InnertubeNextRequest, MusicBrowseResponse, MusicContinuationResponse,
+    MusicHeaderRenderer, MusicListItem, MusicNextResponse, MusicPanelVideoRenderer, MusicRun,
+    MusicShelfItem,
+};
+use crate::youtube::utils::{
+    MusicUrl, build_music_watch_url, build_thumbnail_url, largest_thumbnail,
+};
+use crate::{Audio, Locator, Platform, Playlist};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+const MUSIC_ORIGIN: &str = "https://music.youtube.com";
+
+/// Prefix of the playlists backing albums, they list tracks in album order
+const ALBUM_PLAYLIST_PREFIX: &str = "OLAK5uy_";
+
+const ARTIST_PAGE: &str = "MUSIC_PAGE_TYPE_ARTIST";
+const ALBUM_PAGE: &str = "MUSIC_PAGE_TYPE_ALBUM";
+
+async fn music_post<T: DeserializeOwned, B: Serialize>(endpoint: &str, body: &B) -> Result<T> {
+    let api_url = format!("{MUSIC_ORIGIN}/youtubei/v1/{endpoint}?prettyPrint=false");
+    post_json(&api_url, body, WEB_MUSIC.headers(None)).await
+}
+
+/// Names of the linked artists, joined
+fn artists(runs: &[MusicRun]) -> Option<String> {
+    let names: Vec<_> = runs
+        .iter()
+        .filter(|run| run.page_type() == Some(ARTIST_PAGE))
+        .map(|run| run.text.as_str())
+        .collect();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// Title of the linked album
+fn album(runs: &[MusicRun]) -> Option<String> {
+    runs.iter()
+        .find(|run| run.page_type() == Some(ALBUM_PAGE))
+        .map(|run| run.text.clone())
+}
+
+fn track_audio(video_id: String, title: String) -> Audio {
+    Audio::new(
+        video_id.clone(),
+        title,
+        build_music_watch_url(&video_id),
+        Platform::Youtube,
+    )
+    .with_cover(build_thumbnail_url(&video_id))
+    .with_locator(Locator::Youtube {
+        video_id,
+        itag: None,
+    })
+}
+
+fn panel_audio(renderer: MusicPanelVideoRenderer) -> Audio {
+    let mut audio = track_audio(renderer.video_id, renderer.title.text());
+    if let Some(byline) = renderer.long_byline_text {
+        // Unlinked artists ("Various Artists") are the first part of the byline
+        let artist = artists(&byline.runs).or_else(|| byline.runs.first().map(|r| r.text.clone()));
+        if let Some(artist) = artist {
+            audio = audio.with_artist(artist);
+        }
+        if let Some(album) = album(&byline.runs) {
+            audio = audio.with_album(album);
+        }
+    }
+    if let Some(duration) = renderer.length_text.and_then(|t| parse_length(&t.text())) {
+        audio = audio.with_duration(duration);
+    }
+    if let Some(cover) = renderer
+        .thumbnail
+        .and_then(|t| largest_thumbnail(t.thumbnails))
+    {
+        audio = audio.with_cover(cover);
+    }
+    audio
+}
+
+/// Extract a single track with its artist and album
+async fn extract_track(video_id: &str) -> Result<(Playlist, Option<usize>)> {
+    let request = InnertubeNextRequest {
+        context: WEB_MUSIC.context(),
+        video_id: Some(video_id.to_string()),
+        playlist_id: None,
+    };
+    let resp: MusicNextResponse = music_post("next", &request).await?;
+
+    // The queue starts with the requested track
+    let renderer = resp
+        .contents
+        .into_iter()
+        .flat_map(|c| {
+            c.single_column_music_watch_next_results_renderer
+                .tabbed_renderer
+                .watch_next_tabbed_results_renderer
+                .tabs
+        })
+        .filter_map(|tab| tab.tab_renderer.content?.music_queue_renderer?.content)
+        .flat_map(|queue| queue.playlist_panel_renderer.contents)
+        .filter_map(|item| item.playlist_panel_video_renderer)
+        .find(|renderer| renderer.video_id == video_id)
+        .ok_or(MusicFreeError::VideoNotFound)?;
+
+    let audio = panel_audio(renderer);
+    let playlist = Playlist {
+        id: None,
+        download_url: Some(audio.download_url.clone()),
+        title: Some(audio.title.clone()),
+        cover: audio.cover.clone(),
+        audios: vec![audio],
+        playlists: Vec::new(),
+        platform: Platform::Youtube,
+    };
+    Ok((playlist, Some(0)))
+}
+
+/// Audio of a track list row, `None` for unavailable tracks
+fn list_item_audio(item: MusicListItem) -> Option<Audio> {
+    let video_id = item.playlist_item_data?.video_id;
+    let mut columns = item
+        .flex_columns
+        .into_iter()
+        .map(|c| c.music_responsive_list_item_flex_column_renderer.text);
+    let title = columns.next().unwrap_or_default().text();
+    let runs: Vec<MusicRun> = columns.flat_map(|text| text.runs).collect();
+
+    let mut audio = track_audio(video_id, title);
+    if let Some(artist) = artists(&runs) {
+        audio = audio.with_artist(artist);
+    }
+    if let Some(album) = album(&runs) {
+        audio = audio.with_album(album);
+    }
+    if let Some(track) = item.index.and_then(|i| i.text().trim().parse().ok()) {
+        audio = audio.with_track(track);
+    }
+    if let Some(duration) = item.fixed_columns.first().and_then(|c| {
+        parse_length(
+            &c.music_responsive_list_item_fixed_column_renderer
+                .text
+                .text(),
+        )
+    }) {
+        audio = audio.with_duration(duration);
+    }
+    if let Some(cover) = item
+        .thumbnail
+        .and_then(|t| largest_thumbnail(t.music_thumbnail_renderer.thumbnail.thumbnails))
+    {
+        audio = audio.with_cover(cover);
+    }
+    Some(audio)
+}
+
+/// Collect the tracks of a page, returning the continuation token if any
+fn collect_items(items: Vec<MusicShelfItem>, audios: &mut Vec<Audio>) -> Option<String> {
+    let mut continuation = None;
+    for item in items {
+        if let Some(audio) = item
+            .music_responsive_list_item_renderer
+            .and_then(list_item_audio)
+        {
+            audios.push(audio);
+        }
+        if let Some(renderer) = item.continuation_item_renderer {
+            continuation = Some(renderer.continuation_endpoint.continuation_command.token);
+        }
+    }
+    continuation
+}
+
+async fn browse<T: DeserializeOwned>(
+    browse_id: Option<String>,
+    continuation: Option<String>,
+) -> Result<T> {
+    let request = InnertubeBrowseRequest {
+        context: WEB_MUSIC.context(),
+        browse_id,
+        continuation,
+    };
+    music_post("browse", &request).await
+}
+
+/// Rows of the next page of a track list
+async fn browse_continuation(token: String) -> Result<Vec<MusicShelfItem>> {
+    let resp: MusicContinuationResponse = browse(None, Some(token)).await?;
+    Ok(resp
+        .on_response_received_actions
+        .into_iter()
+        .filter_map(|action| action.append_continuation_items_action)
+        .flat_map(|action| action.continuation_items)
+        .collect())
+}
+
+/// Extract an album page (`MPREb_...`) or a playlist (`VL<playlist id>`)
+///
+/// Album tracks get the album title, the album artist when the row has
+/// none, and their track number. `YoutubeConfig::playlist_limit` bounds
+/// long playlists.
+async fn extract_browse(browse_id: &str, is_album: bool) -> Result<Playlist> {
+    let resp: MusicBrowseResponse = browse(Some(browse_id.to_string()), None).await?;
+    let results = resp
+        .contents
+        .ok_or_else(|| MusicFreeError::InvalidResponse(format!("Empty page: {browse_id}")))?
+        .two_column_browse_results_renderer;
+
+    let header: Option<MusicHeaderRenderer> = results
+        .tabs
+        .into_iter()
+        .filter_map(|tab| tab.tab_renderer?.content)
+        .flat_map(|list| list.section_list_renderer.contents)
+        .find_map(|section| section.music_responsive_header_renderer);
+    let items: Vec<MusicShelfItem> = results
+        .secondary_contents
+        .into_iter()
+        .flat_map(|list| list.section_list_renderer.contents)
+        .filter_map(|section| {
+            section
+                .music_shelf_renderer
+                .or(section.music_playlist_shelf_renderer)
+        })
+        .flat_map(|shelf| shelf.contents)
+        .collect();
+
+    let config = config();
+    let mut audios = collect_pages(
+        items,
+        collect_items,
+        browse_continuation,
+        |count| config.is_full(count),
+        |count| config.report_progress(count),
+    )
+    .await?;
+    if let Some(limit) = config.playlist_limit {
+        audios.truncate(limit);
+    }
+
+    let title = header.as_ref().map(|h| h.title.text());
+    let cover = header
+        .as_ref()
+        .and_then(|h| h.thumbnail.clone())
+        .and_then(|t| largest_thumbnail(t.music_thumbnail_renderer.thumbnail.thumbnails));
+    let album_artist = header
+        .and_then(|h| h.strapline_text_one)
+        .map(|text| text.text());
+
+    if is_album {
+        for (index, audio) in audios.iter_mut().enumerate() {
+            if audio.album.is_none() {
+                audio.album = title.clone();
+            }
+            if audio.artist.is_none() {
+                audio.artist = album_artist.clone();
+            }
+            // Album rows lack thumbnails, the album cover applies to all
+            if let Some(cover) = &cover {
+                audio.cover = Some(cover.clone());
+            }
+            audio.track.get_or_insert(index as u32 + 1);
+        }
+    }
+
+    let mut playlist = Playlist::new(
+        Some(browse_id.to_string()),
+        title.or_else(|| Some("YouTube Music".to_string())),
+        Platform::Youtube,
+    );
+    playlist.cover = cover.or_else(|| audios.iter().find_map(|a| a.cover.clone()));
+    playlist.audios = audios;
+    Ok(playlist)
+}
+
+/// Extract a music.youtube.com track, album or playlist
+pub async fn extract_music(music_url: &MusicUrl) -> Result<(Playlist, Option<usize>)> {
+    match music_url {
+        MusicUrl::Track(video_id) => extract_track(video_id).await,
+        MusicUrl::Album(browse_id) => {
+            let mut playlist = extract_browse(browse_id, true).await?;
+            playlist.download_url = Some(format!("{MUSIC_ORIGIN}/browse/{browse_id}"));
+            Ok((playlist, None))
+        }
+        MusicUrl::Playlist {
+            playlist_id,
+            video_id,
+        } => {
+            let is_album = playlist_id.starts_with(ALBUM_PLAYLIST_PREFIX);
+            let mut playlist = extract_browse(&format!("VL{playlist_id}"), is_album).await?;
+            playlist.id = Some(playlist_id.clone());
+            playlist.download_url = Some(format!("{MUSIC_ORIGIN}/playlist?list={playlist_id}"));
+            let position = video_id
+                .as_ref()
+                .and_then(|id| playlist.audios.iter().position(|a| &a.id == id));
+            Ok((playlist, position))
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! player.js cache.
+//!
+//! Ciphered stream URLs are solved with the player.js named by the watch
+//! page (`/s/player/<id>/.../base.js`). It is several megabytes and only
+//! changes every few days, so it is kept in memory and on disk by player
+//! id, and the n / signature values solved with the current player are
+//! remembered until a watch page names another one. A playlist then
+//! fetches and parses the player once.
+//!
+//! The solved values decide which URLs are downloaded, so the disk cache
+//! lives in the user's cache directory, private to the user, and a cached
+//! file is only used when it still looks like a player.
+
+use crate::download::download_text;
+use crate::error::{MusicFreeError, Result};
+use crate::youtube::config::config;
+use crate::youtube::core::get_player_url;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Global every player.js defines
+const PLAYER_MARKER: &str = "_yt_player";
+
+/// A challenge solved with player.js
+#[cfg(feature = "ytdlp-ejs")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Challenge {
+    /// The `n` parameter throttling the stream
+    N,
+    /// The `s` of a `signatureCipher`
+    Sig,
+}
+
+/// Values solved with one player, replaced when another player is used
+#[cfg(feature = "ytdlp-ejs")]
+#[derive(Debug, Default)]
+struct Solved {
+    player: String,
+    values: HashMap<(Challenge, String), String>,
+}
+
+fn players() -> &'static Mutex<HashMap<String, Arc<str>>> {
+    static PLAYERS: OnceLock<Mutex<HashMap<String, Arc<str>>>> = OnceLock::new();
+    PLAYERS.get_or_init(Default::default)
+}
+
+#[cfg(feature = "ytdlp-ejs")]
+fn solved() -> &'static Mutex<Solved> {
+    static SOLVED: OnceLock<Mutex<Solved>> = OnceLock::new();
+    SOLVED.get_or_init(Default::default)
+}
+
+/// A downloaded player.js
+#[derive(Debug, Clone)]
+pub struct PlayerJs {
+    /// Player id, e.g. `6450230e`
+    pub id: String,
+    pub code: Arc<str>,
+}
+
+#[cfg(feature = "ytdlp-ejs")]
+impl PlayerJs {
+    /// A value solved earlier with this player
+    pub(crate) fn solved(&self, challenge: Challenge, input: &str) -> Option<String> {
+        let solved = solved().lock().unwrap_or_else(|e| e.into_inner());
+        if solved.player != self.id {
+            return None;
+        }
+        solved.values.get(&(challenge, input.to_string())).cloned()
+    }
+
+    /// Remember a solved value, forgetting those of a previous player
+    pub(crate) fn remember(&self, challenge: Challenge, input: &str, output: &str) {
+        let mut solved = solved().lock().unwrap_or_else(|e| e.into_inner());
+        if solved.player != self.id {
+            solved.player = self.id.clone();
+            solved.values.clear();
+        }
+        solved
+            .values
+            .insert((challenge, input.to_string()), output.to_string());
+    }
+}
+
+/// Player id of a player.js URL
+pub fn player_id(player_url: &str) -> Option<&str> {
+    let start = player_url.find("/s/player/")? + "/s/player/".len();
+    let id = player_url[start..].split('/').next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())).then_some(id)
+}
+
+/// Directory of the on-disk cache, `YoutubeConfig::player_cache_dir` or
+/// `musicfree/player` in the user's cache directory. `None` when the
+/// platform has no cache directory, the disk cache is then skipped
+pub fn player_cache_dir() -> Option<PathBuf> {
+    config()
+        .player_cache_dir
+        .clone()
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("musicfree").join("player")))
+}
+
+/// Whether `code` can be a player.js, a truncated or foreign file is not
+fn is_player_code(code: &str) -> bool {
+    code.contains(PLAYER_MARKER)
+}
+
+/// Whether other users can write to `path`
+#[cfg(unix)]
+fn is_shared(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map_or(true, |meta| meta.permissions().mode() & 0o022 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_shared(_path: &Path) -> bool {
+    false
+}
+
+/// Create the cache directory, accessible to the current user only
+fn create_cache_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+fn read_cached(id: &str) -> Option<String> {
+    let dir = player_cache_dir()?;
+    let path = dir.join(format!("{id}.js"));
+    // A file others could have planted is never run
+    if is_shared(&dir) || is_shared(&path) {
+        return None;
+    }
+    let code = std::fs::read_to_string(path).ok()?;
+    is_player_code(&code).then_some(code)
+}
+
+/// Write through a temporary file so concurrent readers never see a
+/// partial player
+fn write_cached(id: &str, code: &str) -> std::io::Result<()> {
+    // Distinguishes the temporary files of concurrent writes in a process
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    let Some(dir) = player_cache_dir() else {
+        return Ok(());
+    };
+    create_cache_dir(&dir)?;
+    let tmp = dir.join(format!(
+        "{id}.js.{}.{}",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&tmp, code)?;
+    std::fs::rename(&tmp, dir.join(format!("{id}.js")))
+}
+
+/// The player.js referenced by a watch page, from memory, disk or the network
+pub(crate) async fn load_player(html: &str) -> Result<PlayerJs> {
+    let player_url = get_player_url(html)
+        .await
+        .ok_or(MusicFreeError::PlayerJsNotFound)?;
+    let id = player_id(&player_url)
+        .ok_or(MusicFreeError::PlayerJsNotFound)?
+        .to_string();
+
+    if let Some(code) = players().lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+        return Ok(PlayerJs {
+            id,
+            code: code.clone(),
+        });
+    }
+
+    let code = match read_cached(&id) {
+        Some(code) => {
+            #[cfg(debug_assertions)]
+            eprintln!("[debug] player.js {id} from disk cache");
+            code
+        }
+        None => {
+            let code = download_text(&player_url, HeaderMap::new()).await?;
+            #[cfg(debug_assertions)]
+            eprintln!("[debug] player.js {id} downloaded: {player_url}");
+            // The cache is an optimization, a read-only disk is fine
+            if is_player_code(&code)
+                && let Err(_e) = write_cached(&id, &code)
+            {
+                #[cfg(debug_assertions)]
+                eprintln!("[debug] Failed to cache player.js {id}: {_e}");
+            }
+            code
+        }
+    };
+
+    let code: Arc<str> = code.into();
+    players()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.clone(), code.clone());
+    Ok(PlayerJs { id, code })
+}
@@ -0,0 +1,247 @@
+//! Proof-of-origin (PO) tokens.
+//!
+//! YouTube asks some clients for a PO token attesting requests come from a
+//! genuine app, and answers 403 or throttles streams without one. The crate
+//! does not mint tokens: a `PoTokenProvider` installed as
+//! `YoutubeConfig::po_token` supplies them. Player requests send the token
+//! as `serviceIntegrityDimensions.poToken`, stream URLs as `pot`.
+
+use crate::error::{MusicFreeError, Result};
+use crate::youtube::config::config;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use url::Url;
+
+/// What a token is requested for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoTokenContext {
+    /// Innertube player requests, bound to the video
+    Player,
+    /// googlevideo stream URLs, bound to the visitor data
+    Gvs,
+}
+
+impl PoTokenContext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PoTokenContext::Player => "player",
+            PoTokenContext::Gvs => "gvs",
+        }
+    }
+}
+
+/// A token request
+#[derive(Debug, Clone, Copy)]
+pub struct PoTokenRequest<'a> {
+    pub context: PoTokenContext,
+    /// `ClientProfile::name` of the client, `web` for the watch page player
+    pub client: &'a str,
+    pub video_id: &'a str,
+    pub visitor_data: Option<&'a str>,
+}
+
+/// Supplies PO tokens to the YouTube extractor
+#[async_trait]
+pub trait PoTokenProvider: Send + Sync {
+    /// Token for a request, `None` to send the request without one
+    async fn po_token(&self, request: &PoTokenRequest<'_>) -> Result<Option<String>>;
+}
+
+fn non_empty(token: &str) -> Option<String> {
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// A fixed token, or one read from a file
+///
+/// The file is read on every request, so it can be refreshed while a long
+/// playlist downloads.
+#[derive(Debug, Clone)]
+pub enum StaticPoTokenProvider {
+    Token(String),
+    File(PathBuf),
+}
+
+impl StaticPoTokenProvider {
+    pub fn new(token: impl Into<String>) -> Self {
+        StaticPoTokenProvider::Token(token.into())
+    }
+
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        StaticPoTokenProvider::File(path.into())
+    }
+}
+
+#[async_trait]
+impl PoTokenProvider for StaticPoTokenProvider {
+    async fn po_token(&self, _request: &PoTokenRequest<'_>) -> Result<Option<String>> {
+        match self {
+            StaticPoTokenProvider::Token(token) => Ok(non_empty(token)),
+            StaticPoTokenProvider::File(path) => Ok(non_empty(&std::fs::read_to_string(path)?)),
+        }
+    }
+}
+
+/// Runs a local program printing the token on stdout
+///
+/// The request is passed in the environment as `MUSICFREE_POT_CONTEXT`
+/// (`player` or `gvs`), `MUSICFREE_POT_CLIENT`, `MUSICFREE_POT_VIDEO_ID`
+/// and `MUSICFREE_POT_VISITOR_DATA`. Tokens are reused for requests bound
+/// to the same video (player) or visitor data (gvs).
+#[derive(Debug)]
+pub struct CommandPoTokenProvider {
+    program: String,
+    args: Vec<String>,
+    cache: Mutex<HashMap<(PoTokenContext, String, String), String>>,
+}
+
+impl CommandPoTokenProvider {
+    pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+        CommandPoTokenProvider {
+            program: program.into(),
+            args,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Parse a command line with shell quoting, e.g.
+    /// `"/opt/po token/gen" --client 'web music'`. `None` when it is empty
+    /// or its quotes are unbalanced
+    pub fn from_command_line(command: &str) -> Option<Self> {
+        let mut parts = shlex::split(command)?.into_iter();
+        let program = parts.next()?;
+        Some(Self::new(program, parts.collect()))
+    }
+}
+
+#[async_trait]
+impl PoTokenProvider for CommandPoTokenProvider {
+    async fn po_token(&self, request: &PoTokenRequest<'_>) -> Result<Option<String>> {
+        let binding = match request.context {
+            PoTokenContext::Player => request.video_id,
+            PoTokenContext::Gvs => request.visitor_data.unwrap_or_default(),
+        };
+        let key = (
+            request.context,
+            request.client.to_string(),
+            binding.to_string(),
+        );
+        if let Some(token) = self
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return Ok(Some(token.clone()));
+        }
+
+        let output = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .env("MUSICFREE_POT_CONTEXT", request.context.as_str())
+            .env("MUSICFREE_POT_CLIENT", request.client)
+            .env("MUSICFREE_POT_VIDEO_ID", request.video_id)
+            .env(
+                "MUSICFREE_POT_VISITOR_DATA",
+                request.visitor_data.unwrap_or_default(),
+            )
+            .output()
+            .await
+            .map_err(|e| MusicFreeError::CommandError(format!("{}: {}", self.program, e)))?;
+        if !output.status.success() {
+            return Err(MusicFreeError::CommandError(format!(
+                "{} exited with {}: {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let token = stdout.lines().next().and_then(non_empty);
+        if let Some(token) = &token {
+            self.cache
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key, token.clone());
+        }
+        Ok(token)
+    }
+}
+
+/// Token from the configured provider
+///
+/// Provider failures are not fatal, the request is sent without a token.
+pub(crate) async fn po_token(request: PoTokenRequest<'_>) -> Option<String> {
+    let provider = config().po_token.clone()?;
+    provider
+        .po_token(&request)
+        .await
+        .inspect_err(|_e| {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "[debug] PO token provider failed ({} {}): {_e}",
+                request.context.as_str(),
+                request.client
+            );
+        })
+        .unwrap_or_default()
+}
+
+/// Add the `pot` parameter of the `gvs` token to a stream URL
+pub(crate) async fn with_gvs_token(
+    download_url: String,
+    client: &str,
+    video_id: &str,
+    visitor_data: Option<&str>,
+) -> String {
+    let request = PoTokenRequest {
+        context: PoTokenContext::Gvs,
+        client,
+        video_id,
+        visitor_data,
+    };
+    let Some(token) = po_token(request).await else {
+        return download_url;
+    };
+    let Ok(mut url) = Url::parse(&download_url) else {
+        return download_url;
+    };
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != "pot")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("pot", &token);
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_command_lines() {
+        let provider = CommandPoTokenProvider::from_command_line(
+            r#""/opt/po token/gen" --client 'web music' plain\ arg"#,
+        )
+        .unwrap();
+        assert_eq!(provider.program, "/opt/po token/gen");
+        assert_eq!(provider.args, ["--client", "web music", "plain arg"]);
+
+        let provider = CommandPoTokenProvider::from_command_line("  gen  ").unwrap();
+        assert_eq!(provider.program, "gen");
+        assert!(provider.args.is_empty());
+    }
+
+    #[test]
+    fn rejects_empty_and_unbalanced_command_lines() {
+        assert!(CommandPoTokenProvider::from_command_line("").is_none());
+        assert!(CommandPoTokenProvider::from_command_line("   ").is_none());
+        assert!(CommandPoTokenProvider::from_command_line("gen 'web").is_none());
+    }
+}
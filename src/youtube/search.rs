@@ -0,0 +1,175 @@
+//! YouTube video search through the Innertube `search` endpoint.
+//!
+//! The first page is requested with the query, further pages with the
+//! continuation token found at the end of the previous one.
+
+use crate::download::{download_text, post_json};
+use crate::error::Result;
+use crate::utils::parse_length;
+use crate::youtube::config::config;
+use crate::youtube::core::{collect_pages, parse_ytcfg, web_innertube_headers};
+use crate::youtube::types::{
+    InnertubeContext, InnertubeSearchRequest, SearchResponse, SearchSection, VideoRenderer,
+};
+use crate::youtube::utils::{
+    WEB_USER_AGENT, build_search_url, build_thumbnail_url, build_watch_url,
+};
+use crate::{Audio, Locator, Platform, Playlist};
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+
+/// Input prefix selecting a search instead of a URL
+pub const SEARCH_PREFIX: &str = "ytsearch:";
+
+/// Number of results returned when the input has no explicit limit
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// `params` filter restricting results to videos
+const VIDEOS_ONLY_PARAMS: &str = "EgIQAQ==";
+
+/// A parsed `ytsearch:` input
+pub use crate::core::SearchQuery;
+
+fn video_audio(video: VideoRenderer) -> Option<Audio> {
+    // Live streams and premieres have no length and can't be downloaded
+    let duration = parse_length(&video.length_text?.text())?;
+
+    let cover = video
+        .thumbnail
+        .and_then(|t| t.thumbnails.into_iter().max_by_key(|t| t.width))
+        .map(|t| t.url)
+        .unwrap_or_else(|| build_thumbnail_url(&video.video_id));
+
+    let mut audio = Audio::new(
+        video.video_id.clone(),
+        video.title.text(),
+        build_watch_url(&video.video_id),
+        Platform::Youtube,
+    )
+    .with_duration(duration)
+    .with_cover(cover)
+    .with_locator(Locator::Youtube {
+        video_id: video.video_id,
+        itag: None,
+    });
+
+    if let Some(owner) = video.owner_text {
+        audio = audio.with_artist(owner.text());
+    }
+    Some(audio)
+}
+
+/// Sections of the first page or of a continuation
+fn response_sections(resp: SearchResponse) -> Vec<SearchSection> {
+    match resp.contents {
+        Some(contents) => {
+            contents
+                .two_column_search_results_renderer
+                .primary_contents
+                .section_list_renderer
+                .contents
+        }
+        None => resp
+            .on_response_received_commands
+            .into_iter()
+            .filter_map(|c| c.append_continuation_items_action)
+            .flat_map(|a| a.continuation_items)
+            .collect(),
+    }
+}
+
+/// Collect the videos of a page, returning the continuation token if any
+///
+/// Pages may hold only live streams or premieres, which are skipped, the
+/// results end with the continuation
+fn collect_sections(sections: Vec<SearchSection>, audios: &mut Vec<Audio>) -> Option<String> {
+    #[cfg(debug_assertions)]
+    let before = audios.len();
+
+    let mut continuation = None;
+    for section in sections {
+        if let Some(items) = section.item_section_renderer {
+            audios.extend(
+                items
+                    .contents
+                    .into_iter()
+                    .filter_map(|item| item.video_renderer)
+                    .filter_map(video_audio),
+            );
+        }
+        if let Some(renderer) = section.continuation_item_renderer {
+            continuation = Some(renderer.continuation_endpoint.continuation_command.token);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    eprintln!(
+        "[debug] YouTube search page: {} results, continuation={}",
+        audios.len() - before,
+        continuation.is_some()
+    );
+    continuation
+}
+
+/// Search YouTube videos, returning at most `limit` results
+pub async fn search(query: &str, limit: usize) -> Result<Playlist> {
+    let mut web_headers = HeaderMap::new();
+    web_headers.insert(USER_AGENT, HeaderValue::from_static(WEB_USER_AGENT));
+    let html = download_text(&build_search_url(query), web_headers).await?;
+    let ytcfg = parse_ytcfg(&html)?;
+
+    let api_url = &format!(
+        "https://www.youtube.com/youtubei/v1/search?key={}&prettyPrint=false",
+        ytcfg.innertube_api_key
+    );
+    let headers = &web_innertube_headers(&ytcfg)?;
+    let request = |query: Option<String>, continuation: Option<String>| InnertubeSearchRequest {
+        context: InnertubeContext {
+            client: ytcfg.innertube_context.client.clone(),
+            third_party: None,
+        },
+        params: query.is_some().then(|| VIDEOS_ONLY_PARAMS.to_string()),
+        query,
+        continuation,
+    };
+
+    let first: SearchResponse = post_json(
+        api_url,
+        &request(Some(query.to_string()), None),
+        headers.clone(),
+    )
+    .await?;
+
+    let config = config();
+    let mut audios = collect_pages(
+        response_sections(first),
+        collect_sections,
+        |token| {
+            let body = request(None, Some(token));
+            async move {
+                let resp: SearchResponse = post_json(api_url, &body, headers.clone()).await?;
+                Ok(response_sections(resp))
+            }
+        },
+        |count| count >= limit,
+        |count| config.report_progress(count),
+    )
+    .await?;
+    audios.truncate(limit);
+
+    let cover = audios.first().and_then(|a| a.cover.clone());
+    Ok(Playlist {
+        id: Some(format!("{SEARCH_PREFIX}{query}")),
+        download_url: Some(build_search_url(query)),
+        title: Some(query.to_string()),
+        audios,
+        cover,
+        playlists: Vec::new(),
+        platform: Platform::Youtube,
+    })
+}
+
+/// Extract the results of a `ytsearch:` input
+pub async fn extract_search(query: &SearchQuery) -> Result<(Playlist, Option<usize>)> {
+    let playlist = search(&query.query, query.limit).await?;
+    Ok((playlist, None))
+}
@@ -0,0 +1,504 @@
+//! Audio format selector expressions.
+//!
+//! A selector lists alternatives separated by `/`, the first one matching a
+//! format wins:
+//!
+//! - `140`: the format with this itag
+//! - `bestaudio`, `worstaudio`: the audio-only format with the highest or
+//!   lowest bitrate, `best` and `worst` fall back to muxed formats
+//! - `[key op value]` filters after any of these, e.g.
+//!   `bestaudio[acodec=opus][abr<=160]`
+//! - bare filters, separated by `,`, as a shorthand for `bestaudio[...]`,
+//!   e.g. `lang=ja` or `abr<=128`
+//!
+//! Filter keys are `acodec` (`opus`, `mp4a`), `ext` (`m4a`, `webm`), `abr`
+//! (kbit/s), `lang` (audio track language, `ja` also matches `ja-JP`),
+//! `drc` (`true`, `false`) and `itag`. Numbers compare with `=`, `!=`, `<`,
+//! `<=`, `>`, `>=`, text with `=` and `!=`.
+//!
+//! Without a language filter the original audio track is preferred over
+//! dubs, and DRC variants are only picked when nothing else matches.
+
+use crate::core::Quality;
+use crate::error::{MusicFreeError, Result};
+use crate::youtube::types::Format;
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn compare<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            Op::Eq => left == right,
+            Op::Ne => left != right,
+            Op::Lt => left < right,
+            Op::Le => left <= right,
+            Op::Gt => left > right,
+            Op::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Acodec(Op, String),
+    Ext(Op, String),
+    Lang(Op, String),
+    Abr(Op, u64),
+    Itag(Op, u64),
+    Drc(bool),
+}
+
+impl Filter {
+    fn parse(s: &str) -> Result<Self> {
+        let invalid = || MusicFreeError::InvalidFormatSelector(format!("Invalid filter: {s:?}"));
+        let start = s.find(['=', '!', '<', '>']).ok_or_else(invalid)?;
+        let key = s[..start].trim().to_ascii_lowercase();
+        let rest = &s[start..];
+        let (op, value) = [
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("=", Op::Eq),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| rest.strip_prefix(token).map(|value| (op, value.trim())))
+        .ok_or_else(invalid)?;
+        if value.is_empty() {
+            return Err(invalid());
+        }
+
+        let text = |value: &str| match op {
+            Op::Eq | Op::Ne => Ok(value.to_ascii_lowercase()),
+            _ => Err(MusicFreeError::InvalidFormatSelector(format!(
+                "{key} only supports = and !=: {s:?}"
+            ))),
+        };
+        let number = |value: &str| value.parse::<u64>().map_err(|_| invalid());
+        match key.as_str() {
+            "acodec" => Ok(Filter::Acodec(op, text(value)?)),
+            "ext" => Ok(Filter::Ext(op, text(value)?)),
+            "lang" | "language" => Ok(Filter::Lang(op, text(value)?)),
+            "abr" => Ok(Filter::Abr(op, number(value)?)),
+            "itag" => Ok(Filter::Itag(op, number(value)?)),
+            "drc" => {
+                let drc = match text(value)?.as_str() {
+                    "true" | "yes" | "1" => true,
+                    "false" | "no" | "0" => false,
+                    _ => return Err(invalid()),
+                };
+                // `drc!=true` is `drc=false`
+                Ok(Filter::Drc(drc == (op == Op::Eq)))
+            }
+            _ => Err(MusicFreeError::InvalidFormatSelector(format!(
+                "Unknown filter key: {key:?}"
+            ))),
+        }
+    }
+
+    fn matches(&self, format: &Format) -> bool {
+        match self {
+            Filter::Acodec(op, codec) => {
+                let acodec = format.acodec().map(str::to_ascii_lowercase);
+                op.compare(acodec.as_deref(), Some(codec.as_str()))
+            }
+            Filter::Ext(op, ext) => op.compare(format.ext(), ext.as_str()),
+            Filter::Lang(op, lang) => {
+                let matches = format.language().is_some_and(|language| {
+                    let language = language.to_ascii_lowercase();
+                    language == *lang || language.starts_with(&format!("{lang}-"))
+                });
+                matches == (*op == Op::Eq)
+            }
+            Filter::Abr(op, abr) => format.abr().is_some_and(|value| op.compare(value, *abr)),
+            Filter::Itag(op, itag) => op.compare(format.itag, *itag),
+            Filter::Drc(drc) => format.is_drc == *drc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Alternative {
+    Itag(u64),
+    Rank {
+        best: bool,
+        audio_only: bool,
+        filters: Vec<Filter>,
+    },
+}
+
+impl Alternative {
+    fn parse(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            return Err(MusicFreeError::InvalidFormatSelector(
+                "Empty alternative".to_string(),
+            ));
+        }
+        if let Ok(itag) = s.parse() {
+            return Ok(Alternative::Itag(itag));
+        }
+
+        let (base, mut rest) = match s.find('[') {
+            Some(start) => (&s[..start], &s[start..]),
+            None => (s, ""),
+        };
+        let (best, audio_only) = match base.to_ascii_lowercase().as_str() {
+            "bestaudio" | "ba" | "" => (true, true),
+            "worstaudio" | "wa" => (false, true),
+            "best" | "b" => (true, false),
+            "worst" | "w" => (false, false),
+            _ if rest.is_empty() => {
+                let filters = s.split(',').map(Filter::parse).collect::<Result<_>>()?;
+                return Ok(Alternative::Rank {
+                    best: true,
+                    audio_only: true,
+                    filters,
+                });
+            }
+            _ => {
+                return Err(MusicFreeError::InvalidFormatSelector(format!(
+                    "Unknown format: {base:?}"
+                )));
+            }
+        };
+
+        let mut filters = vec![];
+        while !rest.is_empty() {
+            let group = rest
+                .strip_prefix('[')
+                .and_then(|r| r.split_once(']'))
+                .ok_or_else(|| {
+                    MusicFreeError::InvalidFormatSelector(format!("Unclosed filter in {s:?}"))
+                })?;
+            filters.push(Filter::parse(group.0)?);
+            rest = group.1;
+        }
+        Ok(Alternative::Rank {
+            best,
+            audio_only,
+            filters,
+        })
+    }
+
+    fn select<'a>(&self, formats: &[&'a Format]) -> Option<&'a Format> {
+        match self {
+            Alternative::Itag(itag) => find_itag(formats, *itag),
+            Alternative::Rank {
+                best,
+                audio_only,
+                filters,
+            } => {
+                let candidates = formats
+                    .iter()
+                    .filter(|f| !audio_only || f.is_audio_only())
+                    .filter(|f| filters.iter().all(|filter| filter.matches(f)));
+                // The bitrate of muxed formats counts their video, they only
+                // win when there is no audio-only format
+                let rank = |f: &Format| (f.is_audio_only(), f.is_default_track(), !f.is_drc);
+                let abr = |f: &Format| f.abr().unwrap_or_default();
+                if *best {
+                    candidates.max_by_key(|f| (rank(f), abr(f))).copied()
+                } else {
+                    candidates
+                        .max_by_key(|f| (rank(f), Reverse(abr(f))))
+                        .copied()
+                }
+            }
+        }
+    }
+}
+
+/// The format `itag`, preferring the original track without DRC when
+/// several formats share it
+pub(crate) fn find_itag<'a>(formats: &[&'a Format], itag: u64) -> Option<&'a Format> {
+    formats
+        .iter()
+        .filter(|f| f.itag == itag)
+        .max_by_key(|f| (f.is_default_track(), !f.is_drc))
+        .copied()
+}
+
+/// Selector used when neither a selector nor a quality is configured: AAC
+/// 140 plays everywhere, then the best other audio-only format
+pub const DEFAULT_SELECTOR: &str = "140/bestaudio/best";
+
+/// A parsed format selector expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatSelector {
+    source: String,
+    alternatives: Vec<Alternative>,
+}
+
+impl FormatSelector {
+    /// Default selector of a quality, muxed formats are the last resort
+    ///
+    /// - `Super`: `bestaudio/best`, usually Opus 251
+    /// - `High`: `bestaudio[acodec=mp4a]/bestaudio/best`, AAC 140 plays
+    ///   everywhere
+    /// - `Standard`: `bestaudio[abr<=96]/worstaudio/worst`
+    /// - `Low`: `worstaudio/worst`
+    pub fn from_quality(quality: Quality) -> Self {
+        let source = match quality {
+            Quality::Super => "bestaudio/best",
+            Quality::High => "bestaudio[acodec=mp4a]/bestaudio/best",
+            Quality::Standard => "bestaudio[abr<=96]/worstaudio/worst",
+            Quality::Low => "worstaudio/worst",
+        };
+        source.parse().expect("Built-in format selector")
+    }
+
+    /// The first format matched by an alternative
+    pub fn select<'a>(&self, formats: &[&'a Format]) -> Option<&'a Format> {
+        self.alternatives
+            .iter()
+            .find_map(|alternative| alternative.select(formats))
+    }
+}
+
+impl Default for FormatSelector {
+    fn default() -> Self {
+        DEFAULT_SELECTOR.parse().expect("Built-in format selector")
+    }
+}
+
+impl FromStr for FormatSelector {
+    type Err = MusicFreeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let source = s.trim();
+        if source.is_empty() {
+            return Err(MusicFreeError::InvalidFormatSelector(
+                "Empty format selector".to_string(),
+            ));
+        }
+        let alternatives = source
+            .split('/')
+            .map(|alternative| Alternative::parse(alternative.trim()))
+            .collect::<Result<_>>()?;
+        Ok(FormatSelector {
+            source: source.to_string(),
+            alternatives,
+        })
+    }
+}
+
+impl fmt::Display for FormatSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(itag: u64, mime_type: &str, bitrate: u64, track: Option<(&str, bool)>) -> Format {
+        let mut format = serde_json::json!({
+            "itag": itag,
+            "mimeType": mime_type,
+            "lastModified": "1700000000000000",
+            "quality": "tiny",
+            "url": format!("https://rr1---sn.googlevideo.com/videoplayback?itag={itag}"),
+            "averageBitrate": bitrate,
+        });
+        if let Some((id, audio_is_default)) = track {
+            format["audioTrack"] = serde_json::json!({
+                "displayName": id,
+                "id": id,
+                "audioIsDefault": audio_is_default,
+            });
+        }
+        serde_json::from_value(format).unwrap()
+    }
+
+    /// Formats of a video dubbed in Japanese
+    fn formats() -> Vec<Format> {
+        let en = Some(("en.4", true));
+        let ja = Some(("ja.3", false));
+        vec![
+            format(
+                18,
+                r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#,
+                500_000,
+                None,
+            ),
+            format(139, r#"audio/mp4; codecs="mp4a.40.5""#, 48_000, en),
+            format(140, r#"audio/mp4; codecs="mp4a.40.2""#, 129_000, en),
+            format(140, r#"audio/mp4; codecs="mp4a.40.2""#, 128_000, ja),
+            format(249, r#"audio/webm; codecs="opus""#, 52_000, en),
+            format(251, r#"audio/webm; codecs="opus""#, 135_000, en),
+            format(251, r#"audio/webm; codecs="opus""#, 131_000, ja),
+        ]
+    }
+
+    fn select(selector: &str, formats: &[Format]) -> Option<(u64, Option<String>)> {
+        let formats: Vec<&Format> = formats.iter().collect();
+        let selector: FormatSelector = selector.parse().unwrap();
+        selector
+            .select(&formats)
+            .map(|f| (f.itag, f.language().map(str::to_string)))
+    }
+
+    fn itag(selector: &str, formats: &[Format]) -> Option<u64> {
+        select(selector, formats).map(|(itag, _)| itag)
+    }
+
+    #[test]
+    fn picks_original_track_by_default() {
+        let formats = formats();
+        assert_eq!(itag("bestaudio", &formats), Some(251));
+        assert_eq!(itag("worstaudio", &formats), Some(139));
+        assert_eq!(
+            select("bestaudio", &formats),
+            Some((251, Some("en".to_string())))
+        );
+        assert_eq!(itag(DEFAULT_SELECTOR, &formats), Some(140));
+    }
+
+    #[test]
+    fn itag_prefers_original_track_without_drc() {
+        let ja = Some(("ja.3", false));
+        let en = Some(("en.4", true));
+        let mut drc = format(140, r#"audio/mp4; codecs="mp4a.40.2""#, 129_000, en);
+        drc.is_drc = true;
+        let formats = vec![
+            format(140, r#"audio/mp4; codecs="mp4a.40.2""#, 128_000, ja),
+            drc,
+            format(140, r#"audio/mp4; codecs="mp4a.40.2""#, 129_000, en),
+        ];
+        let selected = |selector: &str| {
+            let formats: Vec<&Format> = formats.iter().collect();
+            let selector: FormatSelector = selector.parse().unwrap();
+            selector
+                .select(&formats)
+                .map(|f| (f.language().map(str::to_string), f.is_drc))
+        };
+        assert_eq!(selected("140"), Some((Some("en".to_string()), false)));
+        assert_eq!(
+            selected(DEFAULT_SELECTOR),
+            Some((Some("en".to_string()), false))
+        );
+    }
+
+    #[test]
+    fn falls_back_through_alternatives() {
+        let formats = formats();
+        assert_eq!(itag("141/bestaudio[acodec=mp4a]", &formats), Some(140));
+        assert_eq!(itag("bestaudio[abr>500]/249", &formats), Some(249));
+        assert_eq!(itag("bestaudio[abr>500]/best", &formats), Some(251));
+        assert_eq!(itag("141/bestaudio[abr>500]", &formats), None);
+    }
+
+    #[test]
+    fn muxed_formats_only_match_best_and_worst() {
+        let formats = vec![format(
+            18,
+            r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#,
+            500_000,
+            None,
+        )];
+        assert_eq!(itag("bestaudio", &formats), None);
+        assert_eq!(itag("bestaudio/best", &formats), Some(18));
+        assert_eq!(itag("best[acodec=mp4a]", &formats), Some(18));
+    }
+
+    #[test]
+    fn filters_by_language_codec_and_bitrate() {
+        let formats = formats();
+        assert_eq!(
+            select("lang=ja", &formats),
+            Some((251, Some("ja".to_string())))
+        );
+        assert_eq!(
+            select("bestaudio[lang=ja][ext=m4a]", &formats),
+            Some((140, Some("ja".to_string())))
+        );
+        assert_eq!(
+            select("lang!=en", &formats),
+            Some((251, Some("ja".to_string())))
+        );
+        assert_eq!(itag("acodec=opus,abr<=100", &formats), Some(249));
+        assert_eq!(
+            itag("bestaudio[itag!=251][acodec=opus]", &formats),
+            Some(249)
+        );
+        assert_eq!(itag("lang=de", &formats), None);
+    }
+
+    #[test]
+    fn regional_languages_match_their_base_language() {
+        let formats = vec![format(
+            251,
+            r#"audio/webm; codecs="opus""#,
+            131_000,
+            Some(("ja-JP.3", true)),
+        )];
+        assert_eq!(itag("lang=ja", &formats), Some(251));
+        assert_eq!(itag("lang=ja-jp", &formats), Some(251));
+        assert_eq!(itag("lang=j", &formats), None);
+    }
+
+    #[test]
+    fn prefers_formats_without_drc() {
+        let mut drc = format(251, r#"audio/webm; codecs="opus""#, 160_000, None);
+        drc.is_drc = true;
+        let plain = format(251, r#"audio/webm; codecs="opus""#, 135_000, None);
+        let formats = [drc, plain];
+        let selected = |selector: &str| {
+            let formats: Vec<&Format> = formats.iter().collect();
+            let selector: FormatSelector = selector.parse().unwrap();
+            selector.select(&formats).map(|f| f.is_drc)
+        };
+        assert_eq!(selected("bestaudio"), Some(false));
+        assert_eq!(selected("bestaudio[drc=true]"), Some(true));
+        assert_eq!(selected("bestaudio[drc!=false]"), Some(true));
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        for selector in [
+            "",
+            "  ",
+            "bestaudio[abr<=128",
+            "bestaudio[abr]",
+            "bestaudio[abr<=]",
+            "bestaudio[abr<=fast]",
+            "bestaudio[acodec<opus]",
+            "bestaudio[drc=maybe]",
+            "bestaudio[size<10]",
+            "bestvideo",
+            "lang=ja,",
+            "140/",
+        ] {
+            assert!(
+                matches!(
+                    selector.parse::<FormatSelector>(),
+                    Err(MusicFreeError::InvalidFormatSelector(_))
+                ),
+                "{selector:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_source_expression() {
+        let selector: FormatSelector = " bestaudio[acodec=opus]/140 ".parse().unwrap();
+        assert_eq!(selector.to_string(), "bestaudio[acodec=opus]/140");
+        assert_eq!(
+            FormatSelector::from_quality(Quality::High).to_string(),
+            "bestaudio[acodec=mp4a]/bestaudio/best"
+        );
+    }
+}
@@ -2,26 +2,117 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerResponse {
-    #[serde(rename = "streamingData")]
+    /// Why the video can't be played, missing on older responses
+    #[serde(rename = "playabilityStatus")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playability_status: Option<PlayabilityStatus>,
+    /// Empty when the video is not playable
+    #[serde(rename = "streamingData", default)]
     pub streaming_data: StreamingData,
-    #[serde(rename = "videoDetails")]
+    #[serde(rename = "videoDetails", default)]
     pub video_details: VideoDetails,
+    /// Caption tracks, missing when the video has none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captions: Option<Captions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Captions {
+    #[serde(rename = "playerCaptionsTracklistRenderer")]
+    pub player_captions_tracklist_renderer: CaptionTracklist,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptionTracklist {
+    #[serde(rename = "captionTracks", default)]
+    pub caption_tracks: Vec<CaptionTrack>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionTrack {
+    /// timedtext URL, relative for some clients
+    #[serde(rename = "baseUrl")]
+    pub base_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<Title>,
+    #[serde(rename = "languageCode")]
+    pub language_code: String,
+    /// `asr` for automatic speech recognition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(rename = "isTranslatable", default)]
+    pub is_translatable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayabilityStatus {
+    /// `OK`, `LOGIN_REQUIRED`, `UNPLAYABLE`, `ERROR`, `AGE_CHECK_REQUIRED`...
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Set when the video is behind the age gate
+    #[serde(rename = "desktopLegacyAgeGateReason")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_legacy_age_gate_reason: Option<u64>,
+    #[serde(rename = "errorScreen")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_screen: Option<ErrorScreen>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorScreen {
+    #[serde(rename = "playerErrorMessageRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player_error_message_renderer: Option<PlayerErrorMessageRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerErrorMessageRenderer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Title>,
+    /// Details, e.g. the uploader's blocking notice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subreason: Option<Title>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VideoDetails {
     #[serde(rename = "videoId")]
     pub video_id: String,
     pub title: String,
-    #[serde(rename = "lengthSeconds")]
+    #[serde(rename = "lengthSeconds", default)]
     pub length_seconds: String,
+    /// Currently live, streamed through the manifests only
+    #[serde(rename = "isLive", default)]
+    pub is_live: bool,
+    /// A scheduled live stream or premiere that hasn't started
+    #[serde(rename = "isUpcoming", default)]
+    pub is_upcoming: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StreamingData {
+    #[serde(default)]
     pub formats: Vec<Format>,
-    #[serde(rename = "adaptiveFormats")]
+    #[serde(rename = "adaptiveFormats", default)]
     pub adaptive_formats: Vec<Format>,
+    /// HLS master playlist, live streams and premieres
+    #[serde(rename = "hlsManifestUrl")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hls_manifest_url: Option<String>,
+    /// DASH manifest, live streams and premieres
+    #[serde(rename = "dashManifestUrl")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dash_manifest_url: Option<String>,
+}
+
+impl StreamingData {
+    /// Whether the streams are only reachable through a manifest
+    pub fn is_manifest_only(&self) -> bool {
+        self.formats.is_empty()
+            && self.adaptive_formats.is_empty()
+            && (self.hls_manifest_url.is_some() || self.dash_manifest_url.is_some())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +131,73 @@ pub struct Format {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "approxDurationMs")]
     pub approx_duration_ms: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>,
+    #[serde(rename = "averageBitrate", default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_bitrate: Option<u64>,
+    /// Language of dubbed videos, which have one format per track
+    #[serde(rename = "audioTrack", default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_track: Option<AudioTrack>,
+    /// Dynamic range compressed variant of another format
+    #[serde(rename = "isDrc", default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_drc: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioTrack {
+    #[serde(rename = "displayName", default)]
+    pub display_name: String,
+    /// Language and track number, e.g. `ja.4`
+    pub id: String,
+    #[serde(rename = "audioIsDefault", default)]
+    pub audio_is_default: bool,
+}
+
+impl Format {
+    /// Whether the format has no video
+    pub fn is_audio_only(&self) -> bool {
+        self.mime_type.starts_with("audio/")
+    }
+
+    /// Container, `m4a`, `webm` or `mp4`
+    pub fn ext(&self) -> &'static str {
+        if self.mime_type.starts_with("audio/mp4") {
+            "m4a"
+        } else if self.mime_type.contains("/webm") {
+            "webm"
+        } else {
+            "mp4"
+        }
+    }
+
+    /// Audio codec, e.g. `opus` or `mp4a`, without its profile
+    pub fn acodec(&self) -> Option<&str> {
+        let codecs = self.mime_type.split("codecs=\"").nth(1)?;
+        let codecs = codecs.split('"').next()?;
+        // Muxed formats list the video codec first
+        let codec = codecs.split(',').map(str::trim).next_back()?;
+        codec.split('.').next()
+    }
+
+    /// Average bitrate in kbit/s
+    pub fn abr(&self) -> Option<u64> {
+        self.average_bitrate.or(self.bitrate).map(|b| b / 1000)
+    }
+
+    /// Language of the audio track, e.g. `ja` or `en-US`
+    pub fn language(&self) -> Option<&str> {
+        let track = self.audio_track.as_ref()?;
+        track.id.split('.').next()
+    }
+
+    /// Whether this is the original audio of the video, formats without
+    /// tracks always are
+    pub fn is_default_track(&self) -> bool {
+        self.audio_track.as_ref().is_none_or(|t| t.audio_is_default)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,11 +217,19 @@ pub struct YtConfig {
     pub innertube_api_version: String,
     #[serde(rename = "INNERTUBE_CLIENT_NAME")]
     pub innertube_client_name: String,
+    /// `signatureTimestamp` of the page's player.js
+    #[serde(rename = "STS")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sts: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InnertubeContext {
     pub client: serde_json::Value,
+    /// Embedding page, required by embedded player clients
+    #[serde(rename = "thirdParty", default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub third_party: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -77,6 +243,16 @@ pub struct InnertubeRequest {
     pub content_check_ok: bool,
     #[serde(rename = "racyCheckOk")]
     pub racy_check_ok: bool,
+    #[serde(rename = "serviceIntegrityDimensions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_integrity_dimensions: Option<ServiceIntegrityDimensions>,
+}
+
+/// Carries the PO token of a player request
+#[derive(Debug, Serialize)]
+pub struct ServiceIntegrityDimensions {
+    #[serde(rename = "poToken")]
+    pub po_token: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -90,6 +266,10 @@ pub struct ContentPlaybackContext {
     // #[serde(rename = "html5Preference")]
     // pub html5_preference: String,
     pub pcm2: String,
+    /// Version of the player.js that will solve the stream ciphers
+    #[serde(rename = "signatureTimestamp")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_timestamp: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -179,17 +359,24 @@ pub struct PlaylistVideoListRenderer {
     pub contents: Vec<VideoElement>,
 }
 
+/// An entry of a playlist page: a video, or the continuation of the list
+/// after the last video
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoElement {
     #[serde(rename = "playlistVideoRenderer")]
-    pub playlist_video_renderer: PlaylistVideoRenderer,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playlist_video_renderer: Option<PlaylistVideoRenderer>,
+    #[serde(rename = "continuationItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_item_renderer: Option<ContinuationItemRenderer>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistVideoRenderer {
     #[serde(rename = "videoId")]
     pub video_id: String,
-    #[serde(rename = "lengthSeconds")]
+    /// Missing for deleted and private videos
+    #[serde(rename = "lengthSeconds", default)]
     pub length_seconds: String,
     pub title: PlaylistVideoRendererTitle,
 }
@@ -238,6 +425,9 @@ pub struct PlaylistVideoContent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistPanelVideoRenderer {
     pub title: Title,
+    #[serde(rename = "lengthText")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_text: Option<Title>,
     #[serde(rename = "navigationEndpoint")]
     pub navigation_endpoint: NavigationEndpoint,
 }
@@ -283,3 +473,679 @@ pub struct WatchEndpoint {
     #[serde(rename = "videoId")]
     pub video_id: String,
 }
+
+impl Title {
+    /// Plain text of the title
+    pub fn text(&self) -> String {
+        match self {
+            Title::SimpleText { simple_text } => simple_text.clone(),
+            Title::Runs { runs } => runs.iter().map(|r| r.text.as_str()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InnertubeSearchRequest {
+    pub context: InnertubeContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Search filter, e.g. videos only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<String>,
+    /// Token of the next page, replaces `query`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InnertubeNextRequest {
+    pub context: InnertubeContext,
+    #[serde(rename = "videoId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_id: Option<String>,
+    #[serde(rename = "playlistId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playlist_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InnertubeBrowseRequest {
+    pub context: InnertubeContext,
+    /// Page to open, e.g. `VL<playlist id>` or an album `MPREb_...`
+    #[serde(rename = "browseId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browse_id: Option<String>,
+    /// Token of the next page, replaces `browse_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowseContinuationResponse {
+    #[serde(rename = "onResponseReceivedActions", default)]
+    pub on_response_received_actions: Vec<ContinuationAction<VideoElement>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<SearchContents>,
+    /// Present on continuation pages
+    #[serde(rename = "onResponseReceivedCommands", default)]
+    pub on_response_received_commands: Vec<ContinuationAction<SearchSection>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchContents {
+    #[serde(rename = "twoColumnSearchResultsRenderer")]
+    pub two_column_search_results_renderer: TwoColumnSearchResultsRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoColumnSearchResultsRenderer {
+    #[serde(rename = "primaryContents")]
+    pub primary_contents: SearchPrimaryContents,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPrimaryContents {
+    #[serde(rename = "sectionListRenderer")]
+    pub section_list_renderer: SearchSectionListRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSectionListRenderer {
+    pub contents: Vec<SearchSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSection {
+    #[serde(rename = "itemSectionRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_section_renderer: Option<SearchItemSectionRenderer>,
+    #[serde(rename = "continuationItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_item_renderer: Option<ContinuationItemRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchItemSectionRenderer {
+    #[serde(default)]
+    pub contents: Vec<SearchItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchItem {
+    /// Other result kinds (channels, shelves, ads) are ignored
+    #[serde(rename = "videoRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_renderer: Option<VideoRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoRenderer {
+    #[serde(rename = "videoId")]
+    pub video_id: String,
+    pub title: Title,
+    /// Missing for live streams and upcoming premieres
+    #[serde(rename = "lengthText")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_text: Option<Title>,
+    #[serde(rename = "ownerText")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_text: Option<Title>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<Thumbnails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thumbnails {
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+    #[serde(default)]
+    pub width: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationItemRenderer {
+    #[serde(rename = "continuationEndpoint")]
+    pub continuation_endpoint: ContinuationEndpoint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationEndpoint {
+    #[serde(rename = "continuationCommand")]
+    pub continuation_command: ContinuationCommand,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationCommand {
+    pub token: String,
+}
+
+/// Action of a continuation response, `T` is the kind of appended items
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationAction<T> {
+    #[serde(rename = "appendContinuationItemsAction")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub append_continuation_items_action: Option<AppendContinuationItemsAction<T>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendContinuationItemsAction<T> {
+    #[serde(rename = "continuationItems")]
+    pub continuation_items: Vec<T>,
+}
+
+/// `ytInitialData` of a channel page, only the parts used to list playlists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelInitialData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ChannelMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<ChannelContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMetadata {
+    #[serde(rename = "channelMetadataRenderer")]
+    pub channel_metadata_renderer: ChannelMetadataRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMetadataRenderer {
+    /// Channel ID (`UC...`)
+    #[serde(rename = "externalId")]
+    pub external_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<Thumbnails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelContents {
+    #[serde(rename = "twoColumnBrowseResultsRenderer")]
+    pub two_column_browse_results_renderer: ChannelBrowseResults,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelBrowseResults {
+    #[serde(default)]
+    pub tabs: Vec<ChannelTabItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTabItem {
+    /// Missing for the search tab (`expandableTabRenderer`)
+    #[serde(rename = "tabRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_renderer: Option<ChannelTabRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTabRenderer {
+    #[serde(default)]
+    pub selected: bool,
+    /// Only the selected tab has content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<ChannelTabContent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTabContent {
+    #[serde(rename = "sectionListRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section_list_renderer: Option<ChannelSectionList>,
+    #[serde(rename = "richGridRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rich_grid_renderer: Option<ChannelGrid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSectionList {
+    #[serde(default)]
+    pub contents: Vec<ChannelSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSection {
+    #[serde(rename = "itemSectionRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_section_renderer: Option<ChannelItemSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelItemSection {
+    #[serde(default)]
+    pub contents: Vec<ChannelSectionItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSectionItem {
+    #[serde(rename = "gridRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_renderer: Option<ChannelGridItems>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelGridItems {
+    #[serde(default)]
+    pub items: Vec<ChannelPlaylistItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelGrid {
+    #[serde(default)]
+    pub contents: Vec<ChannelPlaylistItem>,
+}
+
+/// An item of the playlists tab, depending on the page layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelPlaylistItem {
+    #[serde(rename = "gridPlaylistRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_playlist_renderer: Option<GridPlaylistRenderer>,
+    #[serde(rename = "lockupViewModel")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lockup_view_model: Option<LockupViewModel>,
+    /// Wrapper used by the rich grid layout
+    #[serde(rename = "richItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rich_item_renderer: Option<RichItemRenderer>,
+    #[serde(rename = "continuationItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_item_renderer: Option<ContinuationItemRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RichItemRenderer {
+    pub content: Box<ChannelPlaylistItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridPlaylistRenderer {
+    #[serde(rename = "playlistId")]
+    pub playlist_id: String,
+    pub title: Title,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<Thumbnails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupViewModel {
+    /// Playlist ID for playlist lockups
+    #[serde(rename = "contentId")]
+    pub content_id: String,
+    #[serde(rename = "contentType", default)]
+    pub content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<LockupMetadata>,
+    #[serde(rename = "contentImage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_image: Option<LockupContentImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupMetadata {
+    #[serde(rename = "lockupMetadataViewModel")]
+    pub lockup_metadata_view_model: LockupMetadataViewModel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupMetadataViewModel {
+    pub title: LockupText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupText {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockupContentImage {
+    #[serde(rename = "collectionThumbnailViewModel")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_thumbnail_view_model: Option<CollectionThumbnailViewModel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionThumbnailViewModel {
+    #[serde(rename = "primaryThumbnail")]
+    pub primary_thumbnail: PrimaryThumbnail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrimaryThumbnail {
+    #[serde(rename = "thumbnailViewModel")]
+    pub thumbnail_view_model: ThumbnailViewModel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailViewModel {
+    pub image: ImageSources,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageSources {
+    #[serde(default)]
+    pub sources: Vec<Thumbnail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelContinuationResponse {
+    #[serde(rename = "onResponseReceivedActions", default)]
+    pub on_response_received_actions: Vec<ContinuationAction<ChannelPlaylistItem>>,
+}
+
+/// Text of YouTube Music renderers, runs link to artist and album pages
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MusicText {
+    #[serde(default)]
+    pub runs: Vec<MusicRun>,
+}
+
+impl MusicText {
+    /// Plain text of all runs
+    pub fn text(&self) -> String {
+        self.runs.iter().map(|r| r.text.as_str()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicRun {
+    pub text: String,
+    #[serde(rename = "navigationEndpoint")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub navigation_endpoint: Option<MusicNavigationEndpoint>,
+}
+
+impl MusicRun {
+    /// Kind of page the run links to, e.g. `MUSIC_PAGE_TYPE_ARTIST`
+    pub fn page_type(&self) -> Option<&str> {
+        let configs = self
+            .navigation_endpoint
+            .as_ref()?
+            .browse_endpoint
+            .as_ref()?
+            .browse_endpoint_context_supported_configs
+            .as_ref()?;
+        Some(&configs.browse_endpoint_context_music_config.page_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicNavigationEndpoint {
+    #[serde(rename = "browseEndpoint")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browse_endpoint: Option<MusicBrowseEndpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseEndpoint {
+    #[serde(rename = "browseId")]
+    pub browse_id: String,
+    #[serde(rename = "browseEndpointContextSupportedConfigs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browse_endpoint_context_supported_configs: Option<MusicBrowseConfigs>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseConfigs {
+    #[serde(rename = "browseEndpointContextMusicConfig")]
+    pub browse_endpoint_context_music_config: MusicPageConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicPageConfig {
+    #[serde(rename = "pageType")]
+    pub page_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicThumbnail {
+    #[serde(rename = "musicThumbnailRenderer")]
+    pub music_thumbnail_renderer: MusicThumbnailRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicThumbnailRenderer {
+    pub thumbnail: Thumbnails,
+}
+
+/// Response of the `next` endpoint for the `WEB_REMIX` client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicNextResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<MusicNextContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicNextContents {
+    #[serde(rename = "singleColumnMusicWatchNextResultsRenderer")]
+    pub single_column_music_watch_next_results_renderer: MusicWatchNextResults,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicWatchNextResults {
+    #[serde(rename = "tabbedRenderer")]
+    pub tabbed_renderer: MusicTabbedRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicTabbedRenderer {
+    #[serde(rename = "watchNextTabbedResultsRenderer")]
+    pub watch_next_tabbed_results_renderer: MusicWatchNextTabs,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicWatchNextTabs {
+    #[serde(default)]
+    pub tabs: Vec<MusicWatchNextTab>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicWatchNextTab {
+    #[serde(rename = "tabRenderer")]
+    pub tab_renderer: MusicWatchNextTabRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicWatchNextTabRenderer {
+    /// Only the "Up next" tab has the queue inline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<MusicQueueContent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicQueueContent {
+    #[serde(rename = "musicQueueRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music_queue_renderer: Option<MusicQueueRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicQueueRenderer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<MusicQueue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicQueue {
+    #[serde(rename = "playlistPanelRenderer")]
+    pub playlist_panel_renderer: MusicPlaylistPanel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicPlaylistPanel {
+    #[serde(default)]
+    pub contents: Vec<MusicPanelItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicPanelItem {
+    #[serde(rename = "playlistPanelVideoRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playlist_panel_video_renderer: Option<MusicPanelVideoRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicPanelVideoRenderer {
+    #[serde(rename = "videoId")]
+    pub video_id: String,
+    #[serde(default)]
+    pub title: MusicText,
+    /// `artist • album • year`, with links on artist and album
+    #[serde(rename = "longBylineText")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_byline_text: Option<MusicText>,
+    #[serde(rename = "lengthText")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_text: Option<MusicText>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<Thumbnails>,
+}
+
+/// Response of the `browse` endpoint for albums and playlists (`WEB_REMIX`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<MusicBrowseContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseContents {
+    #[serde(rename = "twoColumnBrowseResultsRenderer")]
+    pub two_column_browse_results_renderer: MusicTwoColumnResults,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicTwoColumnResults {
+    /// The header is in the first tab
+    #[serde(default)]
+    pub tabs: Vec<MusicBrowseTab>,
+    /// The track list
+    #[serde(rename = "secondaryContents")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secondary_contents: Option<MusicSectionList>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseTab {
+    #[serde(rename = "tabRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_renderer: Option<MusicBrowseTabRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicBrowseTabRenderer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<MusicSectionList>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicSectionList {
+    #[serde(rename = "sectionListRenderer")]
+    pub section_list_renderer: MusicSectionListRenderer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicSectionListRenderer {
+    #[serde(default)]
+    pub contents: Vec<MusicSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicSection {
+    #[serde(rename = "musicResponsiveHeaderRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music_responsive_header_renderer: Option<MusicHeaderRenderer>,
+    /// Album track list
+    #[serde(rename = "musicShelfRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music_shelf_renderer: Option<MusicShelf>,
+    /// Playlist track list
+    #[serde(rename = "musicPlaylistShelfRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music_playlist_shelf_renderer: Option<MusicShelf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicHeaderRenderer {
+    #[serde(default)]
+    pub title: MusicText,
+    /// Album artist, or the playlist owner
+    #[serde(rename = "straplineTextOne")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strapline_text_one: Option<MusicText>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<MusicThumbnail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicShelf {
+    #[serde(default)]
+    pub contents: Vec<MusicShelfItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicShelfItem {
+    #[serde(rename = "musicResponsiveListItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music_responsive_list_item_renderer: Option<MusicListItem>,
+    #[serde(rename = "continuationItemRenderer")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_item_renderer: Option<ContinuationItemRenderer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicListItem {
+    /// Title, then artists and album depending on the page
+    #[serde(rename = "flexColumns", default)]
+    pub flex_columns: Vec<MusicFlexColumn>,
+    /// Track length
+    #[serde(rename = "fixedColumns", default)]
+    pub fixed_columns: Vec<MusicFixedColumn>,
+    /// Track number on album pages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<MusicText>,
+    /// Missing for unavailable tracks
+    #[serde(rename = "playlistItemData")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playlist_item_data: Option<MusicPlaylistItemData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<MusicThumbnail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicFlexColumn {
+    #[serde(rename = "musicResponsiveListItemFlexColumnRenderer")]
+    pub music_responsive_list_item_flex_column_renderer: MusicColumnText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicFixedColumn {
+    #[serde(rename = "musicResponsiveListItemFixedColumnRenderer")]
+    pub music_responsive_list_item_fixed_column_renderer: MusicColumnText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicColumnText {
+    #[serde(default)]
+    pub text: MusicText,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicPlaylistItemData {
+    #[serde(rename = "videoId")]
+    pub video_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicContinuationResponse {
+    #[serde(rename = "onResponseReceivedActions", default)]
+    pub on_response_received_actions: Vec<ContinuationAction<MusicShelfItem>>,
+}
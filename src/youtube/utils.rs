@@ -1,11 +1,21 @@
 use crate::error::{MusicFreeError, Result};
+use crate::youtube::search::{DEFAULT_SEARCH_LIMIT, SEARCH_PREFIX, SearchQuery};
+use crate::youtube::types::Thumbnail;
 
 pub const WEB_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 pub const ANDROID_USER_AGENT: &str =
     "com.google.android.youtube/20.10.38 (Linux; U; Android 11) gzip";
 pub const ANDROID_VR_USER_AGENT: &str = "com.google.android.apps.youtube.vr.oculus/1.65.10 (Linux; U; Android 12L; eureka-user Build/SQ3A.220605.009.A1) gzip";
+pub const IOS_USER_AGENT: &str =
+    "com.google.ios.youtube/20.10.4 (iPhone16,2; U; CPU iOS 18_3_2 like Mac OS X;)";
+pub const SAFARI_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.5 Safari/605.1.15,gzip(gfe)";
+pub const MWEB_USER_AGENT: &str = "Mozilla/5.0 (iPad; CPU OS 16_7_10 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1,gzip(gfe)";
+pub const TV_USER_AGENT: &str = "Mozilla/5.0 (ChromiumStylePlatform) Cobalt/Version";
 
 /// Parse video ID from YouTube URL or direct video ID string
+///
+/// Understands `watch?v=`, `youtu.be/`, `/shorts/`, `/embed/`, `/live/`,
+/// `/v/`, youtube-nocookie.com embeds and `attribution_link` redirects.
 pub fn parse_id(url: &str) -> Result<String> {
     // Direct video ID (11 characters)
     if is_valid_video_id(url) {
@@ -13,43 +23,65 @@ pub fn parse_id(url: &str) -> Result<String> {
     }
 
     // Validate it's a YouTube URL first
-    if !is_youtube_url(url) {
+    let Some(parsed) = parse_youtube_url(url) else {
         return Err(MusicFreeError::InvalidUrl(format!(
             "Not a valid YouTube URL: {}",
             url
         )));
-    }
+    };
 
-    // youtube.com/watch?v=VIDEO_ID
-    if let Some(pos) = url.find("v=") {
-        let id: String = url[pos + 2..]
-            .chars()
-            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
-            .take(11)
-            .collect();
-        if is_valid_video_id(&id) {
-            return Ok(id);
-        }
+    video_id_from_url(&parsed)
+        .ok_or_else(|| MusicFreeError::InvalidUrl(format!("Cannot extract video ID from: {}", url)))
+}
+
+fn video_id_from_url(parsed: &url::Url) -> Option<String> {
+    // attribution_link?u=/watch%3Fv%3DVIDEO_ID
+    if parsed.path() == "/attribution_link" {
+        let target = parsed.join(&query_param(parsed, "u")?).ok()?;
+        return video_id_from_url(&target);
     }
 
-    // youtu.be/VIDEO_ID
-    if url.contains("youtu.be/")
-        && let Some(pos) = url.find("youtu.be/")
-    {
-        let id: String = url[pos + 9..]
-            .chars()
-            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
-            .take(11)
-            .collect();
-        if is_valid_video_id(&id) {
-            return Ok(id);
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    let id = if parsed.domain().is_some_and(|d| d.ends_with("youtu.be")) {
+        // youtu.be/VIDEO_ID
+        segments.next().map(str::to_string)
+    } else if let Some(id) = query_param(parsed, "v") {
+        // youtube.com/watch?v=VIDEO_ID
+        Some(id)
+    } else {
+        // youtube.com/shorts/VIDEO_ID and friends
+        match (segments.next(), segments.next()) {
+            (Some("shorts" | "embed" | "live" | "v" | "e"), Some(id)) => Some(id.to_string()),
+            _ => None,
         }
-    }
+    };
+    id.filter(|id| is_valid_video_id(id))
+}
+
+/// Value of a query parameter, decoded
+fn query_param(parsed: &url::Url, name: &str) -> Option<String> {
+    parsed
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
 
-    Err(MusicFreeError::InvalidUrl(format!(
-        "Cannot extract video ID from: {}",
-        url
-    )))
+/// Parse a YouTube URL, with or without scheme
+fn parse_youtube_url(url: &str) -> Option<url::Url> {
+    let normalized = if !url.starts_with("http://") && !url.starts_with("https://") {
+        format!("https://{}", url)
+    } else {
+        url.to_string()
+    };
+    let parsed = url::Url::parse(&normalized).ok()?;
+    is_youtube_domain(parsed.domain()?).then_some(parsed)
+}
+
+/// Strict domain matching - youtube.com, youtu.be, youtube-nocookie.com or their subdomains
+fn is_youtube_domain(domain: &str) -> bool {
+    ["youtube.com", "youtu.be", "youtube-nocookie.com"]
+        .iter()
+        .any(|d| domain == *d || domain.ends_with(&format!(".{d}")))
 }
 
 /// Validate if a string is a valid YouTube video ID (11 characters, alphanumeric + - and _)
@@ -62,8 +94,14 @@ pub fn is_valid_video_id(id: &str) -> bool {
 
 /// Validate if a string is a valid YouTube playlist ID
 /// Playlist IDs typically start with PL, UU, LL, RD, or OL and are 16-34 characters long
+/// (album mixes such as `RDCLAK5uy_...` and album playlists `OLAK5uy_...` are longer)
 pub fn is_valid_playlist_id(id: &str) -> bool {
-    if id.len() < 2 || id.len() > 34 {
+    let max_len = if is_mix_id(id) || id.starts_with("OLAK5uy_") {
+        48
+    } else {
+        34
+    };
+    if id.len() < 2 || id.len() > max_len {
         return false;
     }
 
@@ -71,7 +109,7 @@ pub fn is_valid_playlist_id(id: &str) -> bool {
     let has_valid_prefix = id.starts_with("PL")
         || id.starts_with("UU")
         || id.starts_with("LL")
-        // || id.starts_with("RD") // skip RD (Radio/MIX)
+        || is_mix_id(id)
         || id.starts_with("OL")
         || id.starts_with("FL");
 
@@ -83,6 +121,11 @@ pub fn is_valid_playlist_id(id: &str) -> bool {
     has_valid_prefix && valid_chars
 }
 
+/// Check if a playlist ID is a Mix / radio, generated from a seed video
+pub fn is_mix_id(id: &str) -> bool {
+    id.starts_with("RD")
+}
+
 /// Check if URL is a YouTube link using strict domain validation
 pub fn is_youtube_url(url: &str) -> bool {
     // If it's a valid video or playlist ID, consider it valid
@@ -90,34 +133,12 @@ pub fn is_youtube_url(url: &str) -> bool {
         return true;
     }
 
-    // Parse URL to check domain strictly
-    if let Ok(parsed) = url::Url::parse(url)
-        && let Some(domain) = parsed.domain()
-    {
-        // Strict domain matching - must be exactly youtube.com or youtu.be (or subdomains)
-        return domain == "youtube.com"
-            || domain.ends_with(".youtube.com")
-            || domain == "youtu.be"
-            || domain.ends_with(".youtu.be");
-    }
-
-    // Fallback for URLs without scheme
-    let normalized = if !url.starts_with("http://") && !url.starts_with("https://") {
-        format!("https://{}", url)
-    } else {
-        url.to_string()
-    };
-
-    if let Ok(parsed) = url::Url::parse(&normalized)
-        && let Some(domain) = parsed.domain()
-    {
-        return domain == "youtube.com"
-            || domain.ends_with(".youtube.com")
-            || domain == "youtu.be"
-            || domain.ends_with(".youtu.be");
+    // Search input
+    if parse_search_query(url).is_some() {
+        return true;
     }
 
-    false
+    parse_youtube_url(url).is_some()
 }
 
 /// Check if URL contains a playlist parameter or is a valid playlist ID
@@ -146,16 +167,181 @@ pub fn parse_playlist_id(url: &str) -> Option<String> {
     }
 
     // Extract from URL parameter
-    if let Some(pos) = url.find("list=") {
-        let id: String = url[pos + 5..]
+    parse_youtube_url(url)
+        .and_then(|parsed| query_param(&parsed, "list"))
+        .filter(|id| is_valid_playlist_id(id))
+}
+
+/// Tab of a channel page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelTab {
+    Videos,
+    Streams,
+    Shorts,
+    Playlists,
+}
+
+impl ChannelTab {
+    /// Prefix replacing `UC` in the channel ID to get the uploads playlist of the tab
+    pub fn uploads_prefix(self) -> Option<&'static str> {
+        match self {
+            ChannelTab::Videos => Some("UU"),
+            ChannelTab::Streams => Some("UULV"),
+            ChannelTab::Shorts => Some("UUSH"),
+            ChannelTab::Playlists => None,
+        }
+    }
+}
+
+/// A parsed channel URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelUrl {
+    /// Path of the channel page, e.g. `@handle` or `channel/UC...`
+    pub path: String,
+    /// Channel ID, known only for `/channel/UC...` URLs
+    pub channel_id: Option<String>,
+    pub tab: ChannelTab,
+}
+
+/// Validate if a string is a YouTube channel ID (`UC` + 22 characters)
+pub fn is_valid_channel_id(id: &str) -> bool {
+    id.len() == 24
+        && id.starts_with("UC")
+        && id
             .chars()
-            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
-            .collect();
-        if is_valid_playlist_id(&id) {
-            return Some(id);
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Parse `/@handle`, `/channel/UC...`, `/c/name` and `/user/name` URLs,
+/// optionally followed by a tab. Other tabs (home, community...) select the videos
+pub fn parse_channel_url(url: &str) -> Option<ChannelUrl> {
+    let parsed = parse_youtube_url(url)?;
+    let domain = parsed.domain()?;
+    if domain != "youtube.com" && !domain.ends_with(".youtube.com") {
+        return None;
+    }
+
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    let first = segments.next()?;
+    let (path, channel_id) = match first {
+        handle if handle.starts_with('@') && handle.len() > 1 => (handle.to_string(), None),
+        "channel" => {
+            let id = segments.next().filter(|id| is_valid_channel_id(id))?;
+            (format!("channel/{id}"), Some(id.to_string()))
         }
+        "c" | "user" => (format!("{first}/{}", segments.next()?), None),
+        _ => return None,
+    };
+
+    let tab = match segments.next() {
+        Some("streams") => ChannelTab::Streams,
+        Some("shorts") => ChannelTab::Shorts,
+        Some("playlists") => ChannelTab::Playlists,
+        _ => ChannelTab::Videos,
+    };
+    Some(ChannelUrl {
+        path,
+        channel_id,
+        tab,
+    })
+}
+
+/// A parsed YouTube Music URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MusicUrl {
+    /// `watch?v=...`
+    Track(String),
+    /// `playlist?list=...` or `watch?v=...&list=...`, `OLAK5uy_...` lists are albums
+    Playlist {
+        playlist_id: String,
+        video_id: Option<String>,
+    },
+    /// `browse/MPREb_...` album page
+    Album(String),
+}
+
+/// Check if URL is on music.youtube.com
+pub fn is_music_url(url: &str) -> bool {
+    parse_youtube_url(url).is_some_and(|parsed| parsed.domain() == Some("music.youtube.com"))
+}
+
+/// Parse a music.youtube.com track, playlist or album URL
+pub fn parse_music_url(url: &str) -> Option<MusicUrl> {
+    let parsed = parse_youtube_url(url)?;
+    if parsed.domain() != Some("music.youtube.com") {
+        return None;
+    }
+
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    if let (Some("browse"), Some(browse_id)) = (segments.next(), segments.next())
+        && browse_id.starts_with("MPREb_")
+    {
+        return Some(MusicUrl::Album(browse_id.to_string()));
+    }
+
+    let video_id = parse_id(url).ok();
+    match parse_playlist_id(url) {
+        // Tracks played from search or a page come with their radio queue
+        Some(playlist_id) if is_mix_id(&playlist_id) && video_id.is_some() => {
+            video_id.map(MusicUrl::Track)
+        }
+        Some(playlist_id) => Some(MusicUrl::Playlist {
+            playlist_id,
+            video_id,
+        }),
+        None => video_id.map(MusicUrl::Track),
+    }
+}
+
+/// What a YouTube input points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YoutubeInput {
+    /// `ytsearch:` input
+    Search(SearchQuery),
+    /// music.youtube.com track, album or playlist
+    Music(MusicUrl),
+    Channel(ChannelUrl),
+    /// Playlist or Mix, `video_id` is the video being watched
+    Playlist {
+        playlist_id: String,
+        video_id: Option<String>,
+    },
+    Video(String),
+}
+
+/// Classify a YouTube URL or ID, `None` when it points to nothing extractable
+pub fn parse_input(input: &str) -> Option<YoutubeInput> {
+    if let Some(query) = parse_search_query(input) {
+        return Some(YoutubeInput::Search(query));
+    }
+    if let Some(music_url) = parse_music_url(input) {
+        return Some(YoutubeInput::Music(music_url));
+    }
+    if let Some(channel) = parse_channel_url(input) {
+        return Some(YoutubeInput::Channel(channel));
+    }
+
+    let video_id = parse_id(input).ok();
+    match parse_playlist_id(input) {
+        Some(playlist_id) => Some(YoutubeInput::Playlist {
+            playlist_id,
+            video_id,
+        }),
+        None => video_id.map(YoutubeInput::Video),
     }
-    None
+}
+
+/// Parse a search input: `ytsearch:<query>` or `ytsearch:<limit>:<query>`
+pub fn parse_search_query(input: &str) -> Option<SearchQuery> {
+    crate::utils::parse_search_query(input, SEARCH_PREFIX, DEFAULT_SEARCH_LIMIT)
+}
+
+/// URL of the widest thumbnail
+pub(crate) fn largest_thumbnail(thumbnails: Vec<Thumbnail>) -> Option<String> {
+    thumbnails
+        .into_iter()
+        .max_by_key(|t| t.width)
+        .map(|t| t.url)
 }
 
 /// Construct YouTube watch URL from video ID
@@ -176,7 +362,126 @@ pub fn build_watch_url_with_playlist(video_id: &str, playlist_id: &str) -> Strin
     )
 }
 
+/// Construct YouTube channel page URL from a channel path
+pub fn build_channel_url(path: &str) -> String {
+    format!("https://www.youtube.com/{}", path)
+}
+
+/// Construct YouTube Music watch URL from video ID
+pub fn build_music_watch_url(video_id: &str) -> String {
+    format!("https://music.youtube.com/watch?v={}", video_id)
+}
+
+/// Construct YouTube search results URL
+pub fn build_search_url(query: &str) -> String {
+    format!(
+        "https://www.youtube.com/results?search_query={}",
+        urlencoding::encode(query)
+    )
+}
+
 /// Construct YouTube thumbnail URL from video ID
 pub fn build_thumbnail_url(video_id: &str) -> String {
     format!("https://i.ytimg.com/vi/{}/hq720.jpg", video_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "dQw4w9WgXcQ";
+
+    #[test]
+    fn parses_video_ids() {
+        let cases = [
+            "dQw4w9WgXcQ",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42",
+            "https://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ?si=abc",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://youtube.com/shorts/dQw4w9WgXcQ?feature=share",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ?start=10",
+            "https://www.youtube.com/live/dQw4w9WgXcQ?si=abc",
+            "https://www.youtube.com/v/dQw4w9WgXcQ?version=3",
+            "https://www.youtube.com/e/dQw4w9WgXcQ",
+            "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ",
+            "https://www.youtube.com/attribution_link?a=xyz&u=/watch%3Fv%3DdQw4w9WgXcQ%26feature%3Dshare",
+            "https://music.youtube.com/watch?v=dQw4w9WgXcQ",
+        ];
+        for url in cases {
+            assert_eq!(parse_id(url).ok().as_deref(), Some(ID), "{url}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_video_urls() {
+        let cases = [
+            "https://www.youtube.com/watch?v=short",
+            "https://www.youtube.com/shorts/",
+            "https://www.youtube.com/feed/subscriptions",
+            "https://www.youtube.com/attribution_link?u=/feed/trending",
+            "https://notyoutube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtube.com.evil.com/watch?v=dQw4w9WgXcQ",
+        ];
+        for url in cases {
+            assert!(parse_id(url).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn classifies_inputs() {
+        let playlist = |playlist_id: &str, video_id: Option<&str>| YoutubeInput::Playlist {
+            playlist_id: playlist_id.to_string(),
+            video_id: video_id.map(str::to_string),
+        };
+        let cases = [
+            (
+                "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+                Some(YoutubeInput::Video(ID.to_string())),
+            ),
+            (
+                "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ",
+                Some(YoutubeInput::Video(ID.to_string())),
+            ),
+            (
+                "https://www.youtube.com/playlist?list=PLBCF2DAC6FFB574DE",
+                Some(playlist("PLBCF2DAC6FFB574DE", None)),
+            ),
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ&start_radio=1",
+                Some(playlist("RDdQw4w9WgXcQ", Some(ID))),
+            ),
+            (
+                "https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=RDAMVMdQw4w9WgXcQ",
+                Some(YoutubeInput::Music(MusicUrl::Track(ID.to_string()))),
+            ),
+            (
+                "https://music.youtube.com/playlist?list=RDCLAK5uy_kmPRjHDECIcuVwnKsx2Ng7fyNgFKWNJFs",
+                Some(YoutubeInput::Music(MusicUrl::Playlist {
+                    playlist_id: "RDCLAK5uy_kmPRjHDECIcuVwnKsx2Ng7fyNgFKWNJFs".to_string(),
+                    video_id: None,
+                })),
+            ),
+            (
+                "https://www.youtube.com/@LofiGirl/streams",
+                Some(YoutubeInput::Channel(ChannelUrl {
+                    path: "@LofiGirl".to_string(),
+                    channel_id: None,
+                    tab: ChannelTab::Streams,
+                })),
+            ),
+            (
+                "ytsearch:5:lofi hip hop",
+                Some(YoutubeInput::Search(SearchQuery {
+                    query: "lofi hip hop".to_string(),
+                    limit: 5,
+                })),
+            ),
+            ("https://www.youtube.com/feed/trending", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_input(input), expected, "{input}");
+        }
+    }
+}
@@ -4,19 +4,20 @@
 //! Extracts the player response embedded in the YouTube watch page HTML,
 //! decrypts signatureCipher/n via EJS, and downloads with a Web browser UA.
 
-use crate::download::{download_binary_chunked, download_text};
-use crate::headers::{download_headers, with_origin, with_sec_fetch};
+use crate::download::download_binary_chunked;
 use crate::error::{MusicFreeError, Result};
+use crate::headers::{download_headers, with_origin, with_sec_fetch};
 use crate::youtube::core::{
-    extract_audio_formats_web, get_player_url, parse_player_response_from_html,
-    select_best_audio_format,
+    extract_audio_formats_web, parse_player_response_from_html, parse_ytcfg, select_audio_format,
 };
-use reqwest::header::HeaderMap;
+use crate::youtube::potoken::with_gvs_token;
 
 use super::utils::WEB_USER_AGENT;
 
 #[cfg(feature = "ytdlp-ejs")]
 use crate::youtube::ejs::{solve_cipher, solve_n};
+#[cfg(feature = "ytdlp-ejs")]
+use crate::youtube::player::load_player;
 
 /// Download audio via Web HTML player response + EJS decryption.
 ///
@@ -25,7 +26,15 @@ use crate::youtube::ejs::{solve_cipher, solve_n};
 /// 3. Decrypt signatureCipher/n via EJS
 /// 4. Download with Web UA + Accept-Encoding: identity
 pub async fn web_download(html: &str) -> Result<Vec<u8>> {
+    web_download_format(None, html).await
+}
+
+/// Like `web_download`, using the format `itag` when it is available
+pub(crate) async fn web_download_format(itag: Option<u64>, html: &str) -> Result<Vec<u8>> {
     let player_response = parse_player_response_from_html(html)?;
+    if let Some(e) = player_response.playability_error() {
+        return Err(e);
+    }
 
     #[cfg(debug_assertions)]
     {
@@ -34,7 +43,7 @@ pub async fn web_download(html: &str) -> Result<Vec<u8>> {
     }
 
     let formats = extract_audio_formats_web(&player_response)?;
-    let format = select_best_audio_format(&formats)?;
+    let format = select_audio_format(&formats, itag)?;
 
     #[cfg(debug_assertions)]
     eprintln!(
@@ -43,21 +52,40 @@ pub async fn web_download(html: &str) -> Result<Vec<u8>> {
     );
 
     let download_url = resolve_web_url(format, html).await?;
+    let visitor_data = parse_ytcfg(html).ok().and_then(|ytcfg| ytcfg.visitor_data);
+    let download_url = with_gvs_token(
+        download_url,
+        "web",
+        &player_response.video_details.video_id,
+        visitor_data.as_deref(),
+    )
+    .await;
 
     #[cfg(debug_assertions)]
-    eprintln!("[debug] Web download URL: {download_url}");
+    eprintln!(
+        "[debug] Web download URL: {}",
+        crate::utils::strip_query(&download_url)
+    );
+
+    download_web_url(&download_url).await
+}
 
+/// Download a resolved stream URL of the web player
+pub(crate) async fn download_web_url(download_url: &str) -> Result<Vec<u8>> {
     let mut dl_headers = download_headers(WEB_USER_AGENT, "https://www.youtube.com/");
     with_origin(&mut dl_headers, "https://www.youtube.com");
     with_sec_fetch(&mut dl_headers);
 
-    download_binary_chunked(&download_url, dl_headers).await
+    download_binary_chunked(download_url, dl_headers).await
 }
 
 /// Resolve the download URL for a Web format.
 /// Web formats often use `signatureCipher` which needs both s and n decryption,
 /// or have a plain `url` with just `&n=` that needs decryption.
-async fn resolve_web_url(format: &crate::youtube::types::Format, html: &str) -> Result<String> {
+pub(crate) async fn resolve_web_url(
+    format: &crate::youtube::types::Format,
+    html: &str,
+) -> Result<String> {
     #[cfg(not(feature = "ytdlp-ejs"))]
     {
         format
@@ -69,38 +97,41 @@ async fn resolve_web_url(format: &crate::youtube::types::Format, html: &str) ->
 
     #[cfg(feature = "ytdlp-ejs")]
     {
-        let player_url = get_player_url(html)
-            .await
-            .ok_or(MusicFreeError::PlayerJsNotFound)?;
-        let player_js_content = download_text(&player_url, HeaderMap::new()).await?;
+        let player = load_player(html).await?;
 
         #[cfg(debug_assertions)]
         {
-            let js_preview: String = player_js_content.chars().take(200).collect();
-            eprintln!("[debug] Web Player JS URL: {player_url}");
+            let js_preview: String = player.code.chars().take(200).collect();
+            eprintln!("[debug] Web Player JS id: {}", player.id);
             eprintln!("[debug] Web Player JS preview: {js_preview}...");
         }
 
         if let Some(raw_url) = &format.url {
             #[cfg(debug_assertions)]
-            eprintln!("[debug] Web raw URL: {raw_url}");
+            eprintln!(
+                "[debug] Web raw URL: {}",
+                crate::utils::strip_query(raw_url)
+            );
 
             if raw_url.contains("&n=") {
-                let decrypted = solve_n(raw_url, player_js_content.clone())?;
+                let decrypted = solve_n(raw_url, &player)?;
                 #[cfg(debug_assertions)]
-                eprintln!("[debug] Web n-decrypted URL: {decrypted}");
+                eprintln!(
+                    "[debug] Web n-decrypted URL: {}",
+                    crate::utils::strip_query(&decrypted)
+                );
                 return Ok(decrypted);
             }
             return Ok(raw_url.clone());
         }
 
         if let Some(cipher) = &format.signature_cipher {
+            let decrypted = solve_cipher(cipher, &player)?;
             #[cfg(debug_assertions)]
-            eprintln!("[debug] Web cipher: {cipher}");
-
-            let decrypted = solve_cipher(cipher, player_js_content)?;
-            #[cfg(debug_assertions)]
-            eprintln!("[debug] Web decrypted from cipher: {decrypted}");
+            eprintln!(
+                "[debug] Web decrypted from cipher: {}",
+                crate::utils::strip_query(&decrypted)
+            );
             return Ok(decrypted);
         }
 